@@ -4,8 +4,8 @@
 use crate::error::ClientError;
 use anchor_client::solana_sdk::pubkey::Pubkey;
 
-/// Placeholder constant for PumpFun program ID
-pub const PUMP_PROGRAM: &str = "PumpFunProgramIDPlaceholder11111111111111111";
+/// PumpFun bonding curve program ID
+pub const PUMP_PROGRAM: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
 
 /// Placeholder struct for PumpFun DEX functionality
 pub struct PumpFun;
@@ -17,14 +17,14 @@ impl PumpFun {
     }
     
     /// Placeholder method for PumpFun buy operations
-    pub async fn buy(&self, _token_mint: &Pubkey, _amount: u64) -> Result<String, ClientError> {
+    pub async fn buy(&self, _token_mint: &Pubkey, _amount: u64, _slippage: u64) -> Result<String, ClientError> {
         // Placeholder implementation
         // Add actual PumpFun buy logic here when needed
         Err(ClientError::Other("PumpFun buy not implemented  ".to_string()))
     }
     
     /// Placeholder method for PumpFun sell operations
-    pub async fn sell(&self, _token_mint: &Pubkey, _amount: u64) -> Result<String, ClientError> {
+    pub async fn sell(&self, _token_mint: &Pubkey, _amount: u64, _slippage: u64) -> Result<String, ClientError> {
         // Placeholder implementation
         // Add actual PumpFun sell logic here when needed
         Err(ClientError::Other("PumpFun sell not implemented  ".to_string()))
@@ -4,6 +4,9 @@
 use crate::error::ClientError;
 use anchor_client::solana_sdk::pubkey::Pubkey;
 
+/// PumpSwap AMM program ID
+pub const PUMP_SWAP_PROGRAM: &str = "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA";
+
 /// Placeholder struct for PumpSwapPool data
 #[derive(Debug, Clone)]
 pub struct PumpSwapPool {
@@ -35,14 +38,14 @@ impl PumpSwap {
     }
     
     /// Placeholder method for PumpSwap buy operations
-    pub async fn buy(&self, _token_mint: &Pubkey, _amount: u64) -> Result<String, ClientError> {
+    pub async fn buy(&self, _token_mint: &Pubkey, _amount: u64, _slippage: u64) -> Result<String, ClientError> {
         // Placeholder implementation
         // Add actual PumpSwap buy logic here when needed
         Err(ClientError::Other("PumpSwap buy not implemented  ".to_string()))
     }
     
     /// Placeholder method for PumpSwap sell operations
-    pub async fn sell(&self, _token_mint: &Pubkey, _amount: u64) -> Result<String, ClientError> {
+    pub async fn sell(&self, _token_mint: &Pubkey, _amount: u64, _slippage: u64) -> Result<String, ClientError> {
         // Placeholder implementation
         // Add actual PumpSwap sell logic here when needed
         Err(ClientError::Other("PumpSwap sell not implemented  ".to_string()))
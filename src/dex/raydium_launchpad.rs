@@ -41,6 +41,9 @@ lazy_static::lazy_static! {
     static ref SELL_DISCRIMINATOR: [u8; 8] = [149, 39, 222, 155, 211, 124, 152, 26]; //sell_exact_in discriminator
 }
 
+/// Raydium Launchpad program ID
+pub const RAYDIUM_LAUNCHPAD_PROGRAM_ID: &str = "LanMV9sAd7wArD4vJFi2qDdfnVhFxYSUg6eADduJ3uj";
+
 const TEN_THOUSAND: u64 = 10000;
 const POOL_VAULT_SEED: &[u8] = b"pool_vault";
 
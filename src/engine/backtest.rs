@@ -0,0 +1,1078 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::str::FromStr;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use lazy_static::lazy_static;
+use serde::Serialize;
+use yellowstone_grpc_proto::geyser::{subscribe_update::UpdateOneof, SubscribeUpdate};
+use yellowstone_grpc_proto::prost::Message;
+
+use crate::engine::comprehensive_selling::{
+    evaluate_exit, parse_take_profit_tiers, ExitState, SellDecision, SellingStrategyConfig, TakeProfitTier,
+};
+use crate::engine::copy_trading::{target_sell_percentage, BuyClaim, ConsensusConfig, MirrorSellMode, TargetBuyRecord};
+use crate::engine::creator_registry::{judge_creator, CreatorFilterConfig, CreatorRecord, CreatorVerdict};
+use crate::engine::journal::csv_field;
+use crate::engine::launch_filter::LaunchFilterConfig;
+use crate::engine::monitor::{check_retracement_levels, TokenTrackingInfo};
+use crate::engine::paper::{
+    self, PaperConfig, SimulatedFill, PUMP_FUN_INITIAL_VIRTUAL_SOL, PUMP_FUN_INITIAL_VIRTUAL_TOKENS,
+};
+use crate::engine::position_sizing::{PositionSizingConfig, SizingDecision};
+use crate::engine::risk_management::{
+    self, check_risk_limits, exposure_headroom, position_size_limit, ExposureBook, RiskManagementConfig, TokenFlow,
+};
+use crate::engine::sniper::is_sniper_enabled;
+use crate::engine::swap::{SwapDirection, SwapProtocol};
+use crate::engine::target_profiles;
+use crate::engine::transaction_parser::{self, DexType, LaunchInfo, TradeInfoFromToken};
+use crate::library::logger::Logger;
+
+lazy_static! {
+    static ref GRPC_RECORD_FILE: Option<String> =
+        std::env::var("GRPC_RECORD_FILE").ok().filter(|path| !path.is_empty());
+    static ref RECORDER: Mutex<Sender<Vec<u8>>> = Mutex::new(start_recorder());
+}
+
+/// Append a transaction update to `GRPC_RECORD_FILE`; no-op when it is unset
+///
+/// Each frame is the receive time in unix milliseconds (u64 LE), the payload length (u32 LE)
+/// and the protobuf-encoded `SubscribeUpdate`.
+pub fn record_update(update: &SubscribeUpdate) {
+    if GRPC_RECORD_FILE.is_none() {
+        return;
+    }
+    if !matches!(update.update_oneof, Some(UpdateOneof::Transaction(_))) {
+        return;
+    }
+    let payload = update.encode_to_vec();
+    let mut frame = Vec::with_capacity(12 + payload.len());
+    frame.extend_from_slice(&(chrono::Utc::now().timestamp_millis() as u64).to_le_bytes());
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.extend_from_slice(&payload);
+
+    let recorder = RECORDER.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let _ = recorder.send(frame);
+}
+
+fn start_recorder() -> Sender<Vec<u8>> {
+    let (sender, receiver) = channel::<Vec<u8>>();
+    std::thread::spawn(move || {
+        let Some(path) = GRPC_RECORD_FILE.as_ref() else { return };
+        let logger = Logger::new("[GRPC-RECORDER] => ".blue().to_string());
+        let mut file = None;
+        while let Ok(frame) = receiver.recv() {
+            if file.is_none() {
+                match OpenOptions::new().create(true).append(true).open(path) {
+                    Ok(opened) => file = Some(BufWriter::new(opened)),
+                    Err(e) => {
+                        logger.log(format!("Failed to open gRPC recording {}: {}", path, e).red().to_string());
+                        continue;
+                    }
+                }
+            }
+            let Some(writer) = file.as_mut() else { continue };
+            let mut result = writer.write_all(&frame);
+            // Frames already queued go out in the same flush
+            while let Ok(frame) = receiver.try_recv() {
+                result = result.and_then(|_| writer.write_all(&frame));
+            }
+            if let Err(e) = result.and_then(|_| writer.flush()) {
+                logger.log(format!("Failed to record gRPC update to {}: {}", path, e).red().to_string());
+                // Reopen on the next frame
+                file = None;
+            }
+        }
+    });
+    sender
+}
+
+/// Every update of a recording with its receive time in unix milliseconds
+pub fn read_recording(path: &str) -> Result<Vec<(i64, SubscribeUpdate)>> {
+    let file = File::open(path).map_err(|e| anyhow!("Failed to open recording {}: {}", path, e))?;
+    let mut reader = BufReader::new(file);
+    let mut updates = Vec::new();
+    let mut header = [0u8; 12];
+    loop {
+        match reader.read_exact(&mut header) {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(anyhow!("Failed to read recording {}: {}", path, e)),
+        }
+        let time_ms = u64::from_le_bytes(header[..8].try_into()?) as i64;
+        let length = u32::from_le_bytes(header[8..].try_into()?) as usize;
+        let mut payload = vec![0u8; length];
+        // A frame cut short by a crash ends the recording
+        if reader.read_exact(&mut payload).is_err() {
+            break;
+        }
+        let update = SubscribeUpdate::decode(payload.as_slice())
+            .map_err(|e| anyhow!("Corrupt update #{} in {}: {}", updates.len(), path, e))?;
+        updates.push((time_ms, update));
+    }
+    Ok(updates)
+}
+
+#[derive(Debug, Clone)]
+pub enum ReplayEvent {
+    Launch(Box<LaunchInfo>),
+    Trade(Box<TradeInfoFromToken>),
+}
+
+/// Parse a recording into launches and trades once, so every sweep replays the same events
+///
+/// Updates received by several streams are kept once, by signature.
+pub fn load_events(path: &str) -> Result<Vec<(i64, ReplayEvent)>> {
+    let mut seen = HashSet::new();
+    let mut events = Vec::new();
+    for (time_ms, update) in read_recording(path)? {
+        let Some(UpdateOneof::Transaction(txn)) = update.update_oneof else { continue };
+        let signature = txn.transaction.as_ref().map(|tx| tx.signature.clone()).unwrap_or_default();
+        if !seen.insert(signature) {
+            continue;
+        }
+        if let Some(launch) = transaction_parser::parse_launch_transaction(&txn) {
+            events.push((time_ms, ReplayEvent::Launch(Box::new(launch))));
+        }
+        if let Some(trade) = transaction_parser::parse_transaction_data(&txn) {
+            events.push((time_ms, ReplayEvent::Trade(Box::new(trade))));
+        }
+    }
+    events.sort_by_key(|(time_ms, _)| *time_ms);
+    Ok(events)
+}
+
+/// What stays the same across a sweep
+#[derive(Debug, Clone)]
+pub struct BacktestSettings {
+    pub targets: HashSet<String>,
+    pub snipe: bool,
+    pub min_dev_buy: f64,
+    pub max_dev_buy: f64,
+    pub selling: SellingStrategyConfig,
+    pub fills: PaperConfig, // latency, slippage model and venue fee of the simulated fills
+    pub network_fee_lamports: u64,
+    pub launch_filter: LaunchFilterConfig, // names and tickers only, the socials filters need the network
+    pub creators: CreatorFilterConfig,
+    pub consensus: ConsensusConfig,
+    pub sizing: PositionSizingConfig,       // caps the sweep's buy size like a live copy
+    pub risk: Option<RiskManagementConfig>, // `None` when risk management is off
+}
+
+impl BacktestSettings {
+    /// Targets, sniper band, buy filters, risk limits, exit ladder and fill model from the same variables as a live run
+    pub fn from_env() -> Result<Self> {
+        let addresses: Vec<String> = std::env::var("COPY_TRADING_TARGET_ADDRESS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|address| address.trim().to_string())
+            .filter(|address| !address.is_empty())
+            .collect();
+        let profiles = target_profiles::load_target_profiles(&addresses)?;
+        target_profiles::register_target_profiles(&profiles);
+        Ok(Self {
+            targets: profiles.iter().map(|profile| profile.address.clone()).collect(),
+            snipe: is_sniper_enabled(),
+            min_dev_buy: std::env::var("MIN_DEV_BUY")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(0.0),
+            max_dev_buy: std::env::var("MAX_DEV_BUY")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(1.0),
+            selling: SellingStrategyConfig::from_env(),
+            fills: PaperConfig::from_env(),
+            network_fee_lamports: paper::network_fee_lamports(),
+            launch_filter: LaunchFilterConfig::from_env(),
+            creators: CreatorFilterConfig::from_env(),
+            consensus: ConsensusConfig::from_env(),
+            sizing: PositionSizingConfig::from_env(),
+            risk: risk_management::is_risk_management_enabled().then(RiskManagementConfig::from_env),
+        })
+    }
+}
+
+/// One point of a sweep
+#[derive(Debug, Clone)]
+pub struct BacktestParams {
+    pub slippage_bps: u64,
+    pub buy_sol: f64,
+    pub take_profit_tiers: Vec<TakeProfitTier>,
+    pub stop_loss_percentage: f64, // -30.0 = sell everything at -30%, 0 = off
+    pub selling_time: u64,
+}
+
+impl BacktestParams {
+    pub fn label(&self) -> String {
+        let tiers = self.take_profit_tiers
+            .iter()
+            .map(|tier| format!("{}:{}", tier.pnl_percentage, tier.sell_percentage))
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            "slip={} buy={} tp={} sl={} time={}",
+            self.slippage_bps, self.buy_sol, tiers, self.stop_loss_percentage, self.selling_time
+        )
+    }
+}
+
+/// The values swept over; the cartesian product is run
+#[derive(Debug, Clone)]
+pub struct SweepGrid {
+    pub slippage_bps: Vec<u64>,
+    pub buy_sol: Vec<f64>,
+    pub take_profit_tiers: Vec<Vec<TakeProfitTier>>,
+    pub stop_loss_percentage: Vec<f64>,
+    pub selling_time: Vec<u64>,
+}
+
+impl SweepGrid {
+    /// A single point from `SLIPPAGE`, `TOKEN_AMOUNT`, `TAKE_PROFIT_TIERS`,
+    /// `RISK_EMERGENCY_STOP_LOSS_PERCENTAGE` and `SELLING_TIME`
+    pub fn from_env(selling: &SellingStrategyConfig) -> Self {
+        Self {
+            slippage_bps: vec![std::env::var("SLIPPAGE")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(5000)
+                .min(10000)],
+            buy_sol: vec![std::env::var("TOKEN_AMOUNT")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(0.001)],
+            take_profit_tiers: vec![selling.take_profit_tiers.clone()],
+            stop_loss_percentage: vec![RiskManagementConfig::from_env().emergency_stop_loss_percentage],
+            selling_time: vec![selling.selling_time],
+        }
+    }
+
+    pub fn combinations(&self) -> Vec<BacktestParams> {
+        let mut combinations = Vec::new();
+        for slippage_bps in &self.slippage_bps {
+            for buy_sol in &self.buy_sol {
+                for tiers in &self.take_profit_tiers {
+                    for stop_loss_percentage in &self.stop_loss_percentage {
+                        for selling_time in &self.selling_time {
+                            combinations.push(BacktestParams {
+                                slippage_bps: *slippage_bps,
+                                buy_sol: *buy_sol,
+                                take_profit_tiers: tiers.clone(),
+                                stop_loss_percentage: *stop_loss_percentage,
+                                selling_time: *selling_time,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        combinations
+    }
+}
+
+/// Comma-separated sweep values
+pub fn parse_sweep<T: FromStr>(value: &str) -> Result<Vec<T>>
+where
+    T::Err: std::fmt::Display,
+{
+    let values = value
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| v.parse::<T>().map_err(|e| anyhow!("invalid sweep value {}: {}", v, e)))
+        .collect::<Result<Vec<_>>>()?;
+    if values.is_empty() {
+        return Err(anyhow!("empty sweep {}", value));
+    }
+    Ok(values)
+}
+
+/// Take-profit ladders separated by `;`, each in `TAKE_PROFIT_TIERS` form
+pub fn parse_tier_sweep(value: &str) -> Result<Vec<Vec<TakeProfitTier>>> {
+    value.split(';').map(str::trim).filter(|v| !v.is_empty()).map(parse_take_profit_tiers).collect()
+}
+
+/// Outcome of one sweep point
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BacktestResult {
+    pub label: String,
+    pub buys: usize,
+    pub sells: usize,
+    pub failed_fills: usize, // fills worse than the slippage tolerance
+    pub skipped: usize,      // buys rejected by the launch filter, creator filter or risk engine
+    pub closed: usize,
+    pub wins: usize,
+    pub open: usize,
+    pub bought_sol: f64,
+    pub realized_pnl_sol: f64,
+    pub unrealized_pnl_sol: f64,
+    pub fees_sol: f64,       // network fees; venue fees are inside the fills
+    pub venue_fees_sol: f64,
+    pub net_pnl_sol: f64,
+    pub max_drawdown_sol: f64, // of realized PnL net of fees
+    pub avg_hold_secs: f64,
+}
+
+#[derive(Debug, Clone)]
+struct SimPosition {
+    venue: SwapProtocol,
+    target: String, // empty for snipes
+    entry_ms: i64,
+    entry_price: f64,
+    current_price: f64,
+    highest_price: f64,
+    trailing_armed: bool,
+    cost_sol: f64,
+    proceeds_sol: f64,
+    tokens_bought: u64,
+    tokens_held: u64,
+    remaining_amount: f64, // % of the original position
+    sold_percentages: HashMap<String, f64>,
+    tracking: TokenTrackingInfo,
+    selling: bool,         // a sell order is in flight
+}
+
+impl SimPosition {
+    fn pnl(&self) -> f64 {
+        if self.entry_price <= 0.0 {
+            return 0.0;
+        }
+        (self.current_price - self.entry_price) / self.entry_price * 100.0
+    }
+}
+
+#[derive(Debug, Clone)]
+enum OrderKind {
+    Buy { sol: f64, target: String },
+    Sell { key: Option<String>, percentage: f64 }, // % of the original position, `None` key sells all
+}
+
+#[derive(Debug, Clone)]
+struct Order {
+    due_ms: i64,
+    mint: String,
+    venue: SwapProtocol,
+    kind: OrderKind,
+}
+
+/// A launch seen in the replay, for the creator history
+#[derive(Debug, Clone)]
+struct SimLaunch {
+    creator: String,
+    launched_ms: i64,
+    dev_sold: bool,
+    migrated: bool,
+}
+
+/// The risk engine's counters, rolled on the virtual clock
+#[derive(Debug, Clone, Default)]
+struct SimRisk {
+    hour: i64, // hours since the epoch of `hourly_trades`
+    hourly_trades: usize,
+    day: String,
+    day_start_equity: f64,
+    peak_equity: f64,
+    consecutive_wins: usize,
+    consecutive_losses: usize,
+}
+
+struct Simulation<'a> {
+    settings: &'a BacktestSettings,
+    params: &'a BacktestParams,
+    reserves: HashMap<String, (u64, u64)>, // (SOL lamports, raw tokens)
+    positions: HashMap<String, SimPosition>,
+    orders: Vec<Order>,
+    result: BacktestResult,
+    hold_secs: Vec<f64>,
+    equity_peak: f64,
+    cash_sol: f64,
+    launches: HashMap<String, SimLaunch>,
+    creators: HashMap<String, CreatorRecord>, // built from the replay only, live history would leak the future
+    recent_tickers: HashMap<String, (String, i64)>,
+    target_buys: HashMap<String, TargetBuyRecord>,
+    flows: HashMap<String, TokenFlow>,
+    risk: SimRisk,
+}
+
+fn spot_price((sol, tokens): (u64, u64)) -> f64 {
+    if tokens == 0 {
+        return 0.0;
+    }
+    (sol as f64 / 1_000_000_000.0) / (tokens as f64 / 1_000_000.0)
+}
+
+impl<'a> Simulation<'a> {
+    fn new(settings: &'a BacktestSettings, params: &'a BacktestParams) -> Self {
+        Self {
+            settings,
+            params,
+            reserves: HashMap::new(),
+            positions: HashMap::new(),
+            orders: Vec::new(),
+            result: BacktestResult { label: params.label(), ..Default::default() },
+            hold_secs: Vec::new(),
+            equity_peak: 0.0,
+            cash_sol: settings.fills.starting_sol,
+            launches: HashMap::new(),
+            creators: HashMap::new(),
+            recent_tickers: HashMap::new(),
+            target_buys: HashMap::new(),
+            flows: HashMap::new(),
+            risk: SimRisk::default(),
+        }
+    }
+
+    fn observe(&mut self, now_ms: i64, trade: &TradeInfoFromToken) {
+        self.record_creator_trade(now_ms, trade);
+        if let Some(config) = self.settings.risk.as_ref() {
+            self.flows.entry(trade.mint.clone()).or_default().record(now_ms / 1000, trade, config);
+        }
+        let Some(reserves) = trade.pool_reserves().filter(|(sol, tokens)| *sol > 0 && *tokens > 0) else { return };
+        self.reserves.insert(trade.mint.clone(), reserves);
+        if let Some(position) = self.positions.get_mut(&trade.mint) {
+            // The curve or pool migrated under the position
+            if trade.dex_type == DexType::PumpSwap {
+                position.venue = SwapProtocol::PumpSwap;
+            }
+            update_price(position, spot_price(reserves));
+        }
+    }
+
+    /// Dev sells and migrations of the launches seen in the replay, as the creator registry records them live
+    ///
+    /// The replay has no migration events, a launch counts as migrated at its first PumpSwap trade.
+    fn record_creator_trade(&mut self, now_ms: i64, trade: &TradeInfoFromToken) {
+        let Some(launch) = self.launches.get_mut(&trade.mint) else { return };
+        let Some(creator) = self.creators.get_mut(&launch.creator) else { return };
+        if trade.dex_type == DexType::PumpSwap && !launch.migrated {
+            launch.migrated = true;
+            creator.migrated += 1;
+        }
+        if !trade.is_buy && trade.user == launch.creator && !launch.dev_sold {
+            launch.dev_sold = true;
+            creator.dev_sells += 1;
+            creator.dev_sell_secs_total += ((now_ms - launch.launched_ms).max(0) / 1000) as u64;
+        }
+    }
+
+    fn record_launch(&mut self, now_ms: i64, launch: &LaunchInfo) {
+        if launch.creator.is_empty() || self.launches.contains_key(&launch.mint) {
+            return;
+        }
+        self.launches.insert(launch.mint.clone(), SimLaunch {
+            creator: launch.creator.clone(),
+            launched_ms: now_ms,
+            dev_sold: false,
+            migrated: false,
+        });
+        let creator = self.creators.entry(launch.creator.clone()).or_insert_with(|| CreatorRecord {
+            creator: launch.creator.clone(),
+            ..Default::default()
+        });
+        creator.tokens_launched += 1;
+    }
+
+    /// Cash plus the open positions at their current price
+    fn equity(&self) -> f64 {
+        self.cash_sol
+            + self.positions
+                .values()
+                .map(|position| position.tokens_held as f64 / 1_000_000.0 * position.current_price)
+                .sum::<f64>()
+    }
+
+    /// Open positions and buys in flight, as the live exposure book sees them
+    fn exposure(&self) -> ExposureBook {
+        let mut book = ExposureBook::default();
+        for (mint, position) in &self.positions {
+            book.add(mint, &position.target, &position.venue, position.cost_sol * position.remaining_amount / 100.0);
+        }
+        for order in &self.orders {
+            if let OrderKind::Buy { sol, target } = &order.kind {
+                book.add(&order.mint, target, &order.venue, *sol);
+            }
+        }
+        book
+    }
+
+    /// Roll the hourly and daily counters and track the equity high-water mark at `now_ms`
+    fn mark_equity(&mut self, now_ms: i64) {
+        let Some(config) = self.settings.risk.as_ref() else { return };
+        let equity = self.equity();
+        let hour = now_ms / 3_600_000;
+        if hour != self.risk.hour {
+            self.risk.hour = hour;
+            self.risk.hourly_trades = 0;
+            // Trading days start on the hour
+            let day = risk_management::trading_day(now_ms / 1000, config.day_start_hour_utc);
+            if day != self.risk.day {
+                self.risk.day = day;
+                self.risk.day_start_equity = equity;
+            }
+        }
+        self.risk.peak_equity = self.risk.peak_equity.max(equity);
+    }
+
+    /// The risk engine's verdict on a buy at `now_ms`: the largest size allowed, `None` when risk management is off
+    ///
+    /// Limits are taken of the equity, cash plus open positions, as live takes them of the portfolio value.
+    fn approve_risk(&self, now_ms: i64, mint: &str, target: Option<&str>, venue: &SwapProtocol) -> Result<Option<f64>, String> {
+        let Some(config) = self.settings.risk.as_ref() else { return Ok(None) };
+        let equity = self.equity();
+        let percentage_of = |reference: f64| if reference > 0.0 { (equity - reference) / reference * 100.0 } else { 0.0 };
+        let metrics = self.flows.get(mint).map(|flow| flow.metrics(now_ms / 1000, config)).unwrap_or_default();
+        check_risk_limits(
+            config,
+            self.risk.hourly_trades,
+            percentage_of(self.risk.day_start_equity),
+            percentage_of(self.risk.peak_equity),
+            &metrics.market_condition,
+        )?;
+        let max_size = position_size_limit(
+            config,
+            equity,
+            metrics.volatility_score,
+            self.risk.consecutive_wins,
+            self.risk.consecutive_losses,
+        );
+        let (headroom, limit) = exposure_headroom(equity, &self.exposure(), mint, target, venue, config);
+        if headroom <= 0.0 {
+            return Err(format!("{} reached", limit));
+        }
+        Ok(Some(max_size.min(headroom)))
+    }
+
+    fn submit_buy(&mut self, now_ms: i64, mint: &str, target: &str, venue: SwapProtocol, sol: f64) {
+        self.risk.hourly_trades += 1;
+        self.submit(now_ms, mint, venue, OrderKind::Buy { sol, target: target.to_string() });
+    }
+
+    fn is_busy(&self, mint: &str) -> bool {
+        self.positions.contains_key(mint) || self.orders.iter().any(|order| order.mint == mint)
+    }
+
+    fn submit(&mut self, now_ms: i64, mint: &str, venue: SwapProtocol, kind: OrderKind) {
+        self.orders.push(Order {
+            due_ms: now_ms + self.settings.fills.latency_ms as i64,
+            mint: mint.to_string(),
+            venue,
+            kind,
+        });
+    }
+
+    fn on_launch(&mut self, now_ms: i64, launch: &LaunchInfo) {
+        if launch.dex_type != DexType::PumpFun {
+            return;
+        }
+        match &launch.dev_buy {
+            Some(dev_buy) => self.observe(now_ms, dev_buy),
+            None => {
+                self.reserves.insert(launch.mint.clone(), (PUMP_FUN_INITIAL_VIRTUAL_SOL, PUMP_FUN_INITIAL_VIRTUAL_TOKENS));
+            }
+        }
+        self.record_launch(now_ms, launch);
+        let filter = &self.settings.launch_filter;
+        let duplicate = filter.register_ticker(&mut self.recent_tickers, &launch.symbol, &launch.mint, now_ms / 1000);
+        let dev_buy = launch.dev_buy_sol();
+        if !self.settings.snipe
+            || dev_buy < self.settings.min_dev_buy
+            || dev_buy > self.settings.max_dev_buy
+            || self.is_busy(&launch.mint)
+        {
+            return;
+        }
+
+        // Same gates and order as the live sniper
+        let mut buy_sol = self.params.buy_sol;
+        match judge_creator(self.creators.get(&launch.creator), &self.settings.creators) {
+            CreatorVerdict::Blocked(_) => {
+                self.result.skipped += 1;
+                return;
+            }
+            CreatorVerdict::Preferred(_) => buy_sol *= self.settings.creators.preferred_multiplier,
+            CreatorVerdict::Unknown | CreatorVerdict::Neutral => {}
+        }
+        if filter.check_names(&launch.name, &launch.symbol).is_err() || duplicate.is_some() {
+            self.result.skipped += 1;
+            return;
+        }
+        match self.approve_risk(now_ms, &launch.mint, None, &SwapProtocol::PumpFun) {
+            Ok(risk_cap) => buy_sol = risk_cap.map_or(buy_sol, |cap| buy_sol.min(cap)),
+            Err(_) => {
+                self.result.skipped += 1;
+                return;
+            }
+        }
+        if buy_sol <= 0.0 {
+            self.result.skipped += 1;
+            return;
+        }
+        self.submit_buy(now_ms, &launch.mint, "", SwapProtocol::PumpFun, buy_sol);
+    }
+
+    fn on_target_trade(&mut self, now_ms: i64, trade: &TradeInfoFromToken) {
+        let venue = SwapProtocol::from(&trade.dex_type);
+        if !matches!(venue, SwapProtocol::PumpFun | SwapProtocol::PumpSwap) {
+            return;
+        }
+        let profile = target_profiles::get_target_profile(&trade.user);
+        if trade.is_buy {
+            let allowed = profile.as_ref().is_none_or(|profile| profile.enabled && profile.allows_venue(&venue));
+            if !allowed || self.is_busy(&trade.mint) {
+                return;
+            }
+            self.copy_buy(now_ms, trade, venue);
+            return;
+        }
+
+        let Some(position) = self.positions.get(&trade.mint) else { return };
+        if position.selling {
+            return;
+        }
+        let mode = profile.map(|profile| profile.mirror_sell_mode()).unwrap_or(MirrorSellMode::Off);
+        let percentage = match mode {
+            MirrorSellMode::Off => return,
+            MirrorSellMode::FullExit => 100.0,
+            MirrorSellMode::Proportional => match target_sell_percentage(trade) {
+                Some(percentage) => percentage,
+                None => return,
+            },
+        };
+        let kind = if percentage >= 99.0 {
+            OrderKind::Sell { key: None, percentage: 100.0 }
+        } else {
+            OrderKind::Sell {
+                key: Some(format!("mirror_{}", trade.signature)),
+                percentage: position.remaining_amount * percentage / 100.0,
+            }
+        };
+        let venue = position.venue.clone();
+        if let Some(position) = self.positions.get_mut(&trade.mint) {
+            position.selling = true;
+        }
+        self.submit(now_ms, &trade.mint, venue, kind);
+    }
+
+    /// Same gates and order as a live copy buy
+    fn copy_buy(&mut self, now_ms: i64, trade: &TradeInfoFromToken, venue: SwapProtocol) {
+        let creator_verdict = trade.coin_creator
+            .as_deref()
+            .map(|creator| judge_creator(self.creators.get(creator), &self.settings.creators))
+            .unwrap_or(CreatorVerdict::Unknown);
+        if let CreatorVerdict::Blocked(_) = creator_verdict {
+            self.result.skipped += 1;
+            return;
+        }
+        let record = self.target_buys.entry(trade.mint.clone()).or_default();
+        let agreeing = match record.claim(&trade.user, now_ms, &self.settings.consensus) {
+            BuyClaim::Copy(agreeing) => agreeing,
+            BuyClaim::Waiting(_) | BuyClaim::AlreadyCopied => return,
+        };
+        let mut sizing = SizingDecision {
+            amount_sol: self.params.buy_sol,
+            reason: format!("backtest {} SOL", self.params.buy_sol),
+        };
+        match self.approve_risk(now_ms, &trade.mint, Some(&trade.user), &venue) {
+            Ok(risk_cap) => self.settings.sizing.adjust(
+                &mut sizing,
+                &self.settings.consensus,
+                agreeing,
+                &creator_verdict,
+                self.settings.creators.preferred_multiplier,
+                risk_cap,
+            ),
+            Err(_) => sizing.amount_sol = 0.0,
+        }
+        if sizing.amount_sol <= 0.0 {
+            if let Some(record) = self.target_buys.get_mut(&trade.mint) {
+                record.copied = false;
+            }
+            self.result.skipped += 1;
+            return;
+        }
+        self.submit_buy(now_ms, &trade.mint, &trade.user, venue, sizing.amount_sol);
+    }
+
+    /// Run the exit ladder over every open position at `now_ms`
+    fn check_exits(&mut self, now_ms: i64) {
+        let selling = SellingStrategyConfig {
+            take_profit_tiers: self.params.take_profit_tiers.clone(),
+            selling_time: self.params.selling_time,
+            ..self.settings.selling.clone()
+        };
+        let mut exits = Vec::new();
+        for (mint, position) in self.positions.iter_mut().filter(|(_, position)| !position.selling) {
+            let pnl = position.pnl();
+            if !position.trailing_armed && pnl >= selling.trailing_stop_activation {
+                position.trailing_armed = true;
+            }
+            let decision = if self.params.stop_loss_percentage < 0.0 && pnl <= self.params.stop_loss_percentage {
+                SellDecision::All { reason: "stop loss".to_string() }
+            } else {
+                let trailing_stop = if position.highest_price > 0.0 {
+                    (position.current_price - position.highest_price) / position.highest_price * 100.0
+                } else {
+                    0.0
+                };
+                let decision = evaluate_exit(&ExitState {
+                    pnl,
+                    trailing_stop,
+                    highest_price: position.highest_price,
+                    trailing_armed: position.trailing_armed,
+                    held_secs: ((now_ms - position.entry_ms).max(0) / 1000) as u64,
+                    selling_time: selling.selling_time,
+                    remaining_amount: position.remaining_amount,
+                    sold_percentages: &position.sold_percentages,
+                }, &selling);
+                match decision {
+                    SellDecision::Hold if !selling.retracement_levels.is_empty() => {
+                        match check_retracement_levels(&mut position.tracking, pnl, &selling.retracement_levels) {
+                            Some(level) => SellDecision::Partial {
+                                key: level.key(),
+                                percentage: position.remaining_amount * level.sell_amount as f64 / 100.0,
+                                reason: "retracement".to_string(),
+                            },
+                            None => SellDecision::Hold,
+                        }
+                    }
+                    decision => decision,
+                }
+            };
+            let kind = match decision {
+                SellDecision::Hold => continue,
+                SellDecision::Partial { key, percentage, .. } => OrderKind::Sell { key: Some(key), percentage },
+                SellDecision::All { .. } => OrderKind::Sell { key: None, percentage: 100.0 },
+            };
+            position.selling = true;
+            exits.push((mint.clone(), position.venue.clone(), kind));
+        }
+        for (mint, venue, kind) in exits {
+            self.submit(now_ms, &mint, venue, kind);
+        }
+    }
+
+    /// Fill every order that landed by `now_ms` against the reserves observed by then
+    fn settle_orders(&mut self, now_ms: i64) {
+        let (due, pending): (Vec<Order>, Vec<Order>) =
+            std::mem::take(&mut self.orders).into_iter().partition(|order| order.due_ms <= now_ms);
+        self.orders = pending;
+        for order in due {
+            self.fill(order);
+        }
+    }
+
+    fn simulate(&self, mint: &str, direction: SwapDirection, amount: u64) -> Option<SimulatedFill> {
+        let reserves = *self.reserves.get(mint)?;
+        let fills = &self.settings.fills;
+        let simulated = paper::simulate_fill(
+            reserves, &direction, amount, &fills.slippage_model, fills.extra_slippage_bps, fills.venue_fee_bps,
+        )?;
+        let min_out = simulated.quoted_out as u128 * 10_000u128.saturating_sub(self.params.slippage_bps as u128) / 10_000;
+        (simulated.amount_out > 0 && simulated.amount_out as u128 >= min_out).then_some(simulated)
+    }
+
+    fn fill(&mut self, order: Order) {
+        let network_fee_sol = self.settings.network_fee_lamports as f64 / 1_000_000_000.0;
+        match order.kind {
+            OrderKind::Buy { sol, target } => {
+                let lamports = (sol * 1_000_000_000.0) as u64;
+                let Some(simulated) = self.simulate(&order.mint, SwapDirection::Buy, lamports) else {
+                    self.result.failed_fills += 1;
+                    return;
+                };
+                self.result.buys += 1;
+                self.result.bought_sol += sol;
+                self.result.fees_sol += network_fee_sol;
+                self.result.venue_fees_sol += simulated.venue_fee as f64 / 1_000_000_000.0;
+                self.cash_sol -= sol + network_fee_sol;
+                self.reserves.insert(order.mint.clone(), simulated.reserves_after);
+                let entry_price = sol / (simulated.amount_out as f64 / 1_000_000.0);
+                let mut position = SimPosition {
+                    venue: order.venue,
+                    target,
+                    entry_ms: order.due_ms,
+                    entry_price,
+                    current_price: entry_price,
+                    highest_price: entry_price,
+                    trailing_armed: false,
+                    cost_sol: sol,
+                    proceeds_sol: 0.0,
+                    tokens_bought: simulated.amount_out,
+                    tokens_held: simulated.amount_out,
+                    remaining_amount: 100.0,
+                    sold_percentages: HashMap::new(),
+                    tracking: TokenTrackingInfo::new(),
+                    selling: false,
+                };
+                update_price(&mut position, spot_price(simulated.reserves_after));
+                self.positions.insert(order.mint, position);
+            }
+            OrderKind::Sell { key, percentage } => {
+                let Some(position) = self.positions.get(&order.mint) else { return };
+                let tokens = match key {
+                    None => position.tokens_held,
+                    Some(_) => ((position.tokens_bought as f64 * percentage / 100.0) as u64).min(position.tokens_held),
+                };
+                let simulated = if tokens > 0 { self.simulate(&order.mint, SwapDirection::Sell, tokens) } else { None };
+                let Some(simulated) = simulated else {
+                    // Too much slippage, the ladder retries on the next event
+                    self.result.failed_fills += 1;
+                    if let Some(position) = self.positions.get_mut(&order.mint) {
+                        position.selling = false;
+                    }
+                    return;
+                };
+                self.result.sells += 1;
+                self.result.fees_sol += network_fee_sol;
+                self.result.venue_fees_sol += simulated.venue_fee as f64 / 1_000_000_000.0;
+                self.cash_sol += simulated.amount_out as f64 / 1_000_000_000.0 - network_fee_sol;
+                self.reserves.insert(order.mint.clone(), simulated.reserves_after);
+
+                let Some(position) = self.positions.get_mut(&order.mint) else { return };
+                let sold = if key.is_none() { position.remaining_amount } else { percentage.min(position.remaining_amount) };
+                position.proceeds_sol += simulated.amount_out as f64 / 1_000_000_000.0;
+                position.tokens_held -= tokens;
+                position.remaining_amount = (position.remaining_amount - sold).max(0.0);
+                if let Some(level) = self.settings.selling.retracement_levels.iter().find(|level| Some(level.key()) == key) {
+                    position.tracking.complete(level);
+                }
+                position.sold_percentages.insert(key.unwrap_or_else(|| "exit".to_string()), sold);
+                position.selling = false;
+                update_price(position, spot_price(simulated.reserves_after));
+
+                if position.remaining_amount <= 0.0 || position.tokens_held == 0 {
+                    let pnl_sol = position.proceeds_sol - position.cost_sol;
+                    self.hold_secs.push((order.due_ms - position.entry_ms).max(0) as f64 / 1000.0);
+                    self.result.closed += 1;
+                    if pnl_sol > 0.0 {
+                        self.result.wins += 1;
+                        self.risk.consecutive_wins += 1;
+                        self.risk.consecutive_losses = 0;
+                    } else {
+                        self.risk.consecutive_losses += 1;
+                        self.risk.consecutive_wins = 0;
+                    }
+                    self.result.realized_pnl_sol += pnl_sol;
+                    self.positions.remove(&order.mint);
+                }
+                let equity = self.result.realized_pnl_sol - self.result.fees_sol;
+                self.equity_peak = self.equity_peak.max(equity);
+                self.result.max_drawdown_sol = self.result.max_drawdown_sol.max(self.equity_peak - equity);
+            }
+        }
+    }
+
+    fn finish(mut self) -> BacktestResult {
+        self.settle_orders(i64::MAX);
+        self.result.open = self.positions.len();
+        self.result.unrealized_pnl_sol = self.positions
+            .values()
+            .map(|position| {
+                position.proceeds_sol + position.tokens_held as f64 / 1_000_000.0 * position.current_price - position.cost_sol
+            })
+            .sum();
+        self.result.net_pnl_sol = self.result.realized_pnl_sol + self.result.unrealized_pnl_sol - self.result.fees_sol;
+        if !self.hold_secs.is_empty() {
+            self.result.avg_hold_secs = self.hold_secs.iter().sum::<f64>() / self.hold_secs.len() as f64;
+        }
+        self.result
+    }
+}
+
+fn update_price(position: &mut SimPosition, price: f64) {
+    if price <= 0.0 {
+        return;
+    }
+    position.current_price = price;
+    position.highest_price = position.highest_price.max(price);
+}
+
+/// Replay `events` with one set of parameters on a virtual clock driven by the recorded receive times
+pub fn run_backtest(events: &[(i64, ReplayEvent)], settings: &BacktestSettings, params: &BacktestParams) -> BacktestResult {
+    let mut simulation = Simulation::new(settings, params);
+    for (time_ms, event) in events {
+        simulation.settle_orders(*time_ms);
+        match event {
+            ReplayEvent::Launch(launch) => simulation.on_launch(*time_ms, launch),
+            ReplayEvent::Trade(trade) => {
+                simulation.observe(*time_ms, trade);
+                if settings.targets.contains(&trade.user) {
+                    simulation.on_target_trade(*time_ms, trade);
+                }
+            }
+        }
+        simulation.check_exits(*time_ms);
+        simulation.mark_equity(*time_ms);
+    }
+    simulation.finish()
+}
+
+/// Run every point of `grid`, best net PnL first
+pub fn run_sweep(events: &[(i64, ReplayEvent)], settings: &BacktestSettings, grid: &SweepGrid) -> Vec<BacktestResult> {
+    let mut results: Vec<BacktestResult> = grid
+        .combinations()
+        .iter()
+        .map(|params| run_backtest(events, settings, params))
+        .collect();
+    results.sort_by(|a, b| b.net_pnl_sol.total_cmp(&a.net_pnl_sol));
+    results
+}
+
+/// Render sweep results as `table`, `csv` or `jsonl`
+pub fn format_backtest_report(results: &[BacktestResult], format: &str) -> Result<String> {
+    let mut out = String::new();
+    match format {
+        "csv" => {
+            out.push_str("label,buys,sells,failed_fills,skipped,closed,wins,open,bought_sol,realized_pnl_sol,unrealized_pnl_sol,fees_sol,venue_fees_sol,net_pnl_sol,max_drawdown_sol,avg_hold_secs\n");
+            for result in results {
+                out.push_str(&format!(
+                    "{},{},{},{},{},{},{},{},{:.9},{:.9},{:.9},{:.9},{:.9},{:.9},{:.9},{:.1}\n",
+                    csv_field(&result.label), result.buys, result.sells, result.failed_fills, result.skipped,
+                    result.closed, result.wins, result.open, result.bought_sol, result.realized_pnl_sol, result.unrealized_pnl_sol,
+                    result.fees_sol, result.venue_fees_sol, result.net_pnl_sol, result.max_drawdown_sol,
+                    result.avg_hold_secs
+                ));
+            }
+        }
+        "jsonl" => {
+            for result in results {
+                out.push_str(&serde_json::to_string(result)?);
+                out.push('\n');
+            }
+        }
+        "table" => {
+            out.push_str(&format!(
+                "{:<60} {:>4} {:>5} {:>6} {:>7} {:>6} {:>7} {:>11} {:>11} {:>8} {:>10} {:>9} {:>8}\n",
+                "parameters", "buys", "sells", "failed", "skipped", "closed", "win%", "realized", "unrealized", "fees", "net", "drawdown", "hold s"
+            ));
+            for result in results {
+                let win_rate = if result.closed > 0 { result.wins as f64 / result.closed as f64 * 100.0 } else { 0.0 };
+                out.push_str(&format!(
+                    "{:<60} {:>4} {:>5} {:>6} {:>7} {:>6} {:>7.1} {:>+11.4} {:>+11.4} {:>8.4} {:>+10.4} {:>9.4} {:>8.1}\n",
+                    result.label, result.buys, result.sells, result.failed_fills, result.skipped, result.closed, win_rate,
+                    result.realized_pnl_sol, result.unrealized_pnl_sol, result.fees_sol, result.net_pnl_sol,
+                    result.max_drawdown_sol, result.avg_hold_secs
+                ));
+            }
+        }
+        other => return Err(anyhow!("Unknown report format {}, use table, csv or jsonl", other)),
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pump_fun_trade(mint: &str, user: &str, is_buy: bool, sol: u64, reserves: (u64, u64)) -> TradeInfoFromToken {
+        TradeInfoFromToken {
+            dex_type: DexType::PumpFun,
+            signature: format!("{}-{}-{}", mint, user, reserves.0),
+            mint: mint.to_string(),
+            user: user.to_string(),
+            is_buy,
+            sol_amount: Some(sol),
+            virtual_sol_reserves: Some(reserves.0),
+            virtual_token_reserves: Some(reserves.1),
+            ..Default::default()
+        }
+    }
+
+    fn launch(mint: &str, creator: &str) -> LaunchInfo {
+        LaunchInfo {
+            mint: mint.to_string(),
+            creator: creator.to_string(),
+            name: "Test Token".to_string(),
+            symbol: mint.to_uppercase(),
+            dex_type: DexType::PumpFun,
+            dev_buy: Some(pump_fun_trade(mint, creator, true, 500_000_000, (30_500_000_000, 1_055_500_000_000_000))),
+            ..Default::default()
+        }
+    }
+
+    fn settings() -> BacktestSettings {
+        let mut creators = CreatorFilterConfig::from_env();
+        creators.min_launches = 2;
+        creators.min_migration_rate = 50.0;
+        BacktestSettings {
+            targets: HashSet::new(),
+            snipe: true,
+            min_dev_buy: 0.1,
+            max_dev_buy: 1.0,
+            selling: SellingStrategyConfig::from_env(),
+            fills: PaperConfig {
+                starting_sol: 10.0,
+                latency_ms: 0,
+                latency_jitter_ms: 0,
+                slippage_model: paper::SlippageModel::Amm,
+                extra_slippage_bps: 0,
+                venue_fee_bps: 100,
+                book_file: String::new(),
+            },
+            network_fee_lamports: 5_000,
+            launch_filter: LaunchFilterConfig::from_env(),
+            creators,
+            consensus: ConsensusConfig::from_env(),
+            sizing: PositionSizingConfig::from_env(),
+            risk: Some(RiskManagementConfig::from_env()),
+        }
+    }
+
+    #[test]
+    fn read_recording_stops_at_a_truncated_frame() {
+        let path = std::env::temp_dir().join(format!("backtest-recording-{}.bin", std::process::id()));
+        let mut bytes = Vec::new();
+        for (time_ms, filter) in [(1_000u64, "first"), (2_000u64, "second")] {
+            let payload = SubscribeUpdate { filters: vec![filter.to_string()], ..Default::default() }.encode_to_vec();
+            bytes.extend_from_slice(&time_ms.to_le_bytes());
+            bytes.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&payload);
+        }
+        // A crash mid-write leaves a header promising more than was written
+        bytes.extend_from_slice(&3_000u64.to_le_bytes());
+        bytes.extend_from_slice(&64u32.to_le_bytes());
+        bytes.extend_from_slice(&[0u8; 10]);
+        std::fs::write(&path, &bytes).unwrap();
+
+        let updates = read_recording(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0].0, 1_000);
+        assert_eq!(updates[0].1.filters, vec!["first".to_string()]);
+        assert_eq!(updates[1].0, 2_000);
+        assert_eq!(updates[1].1.filters, vec!["second".to_string()]);
+    }
+
+    #[test]
+    fn sweep_applies_the_live_gates() {
+        let events = vec![
+            (1_000, ReplayEvent::Launch(Box::new(launch("mint1", "dev")))),
+            (2_000, ReplayEvent::Trade(Box::new(pump_fun_trade("mint1", "whale", true, 30_000_000_000, (60_000_000_000, 536_500_000_000_000))))),
+            (3_000, ReplayEvent::Trade(Box::new(pump_fun_trade("mint1", "whale", true, 1_000_000_000, (61_000_000_000, 527_700_000_000_000))))),
+            // The creator's second launch, none migrated yet
+            (4_000, ReplayEvent::Launch(Box::new(launch("mint2", "dev")))),
+        ];
+        let grid = SweepGrid {
+            slippage_bps: vec![1_000],
+            buy_sol: vec![0.1, 0.2],
+            take_profit_tiers: vec![parse_take_profit_tiers("20:100").unwrap()],
+            stop_loss_percentage: vec![0.0],
+            selling_time: vec![3_600],
+        };
+
+        let results = run_sweep(&events, &settings(), &grid);
+        assert_eq!(results.len(), 2);
+        for result in &results {
+            assert_eq!(result.buys, 1);
+            assert_eq!(result.closed, 1);
+            assert_eq!(result.wins, 1);
+            assert_eq!(result.skipped, 1, "the blocked creator's second launch is not sniped");
+        }
+        assert!(results[0].net_pnl_sol >= results[1].net_pnl_sol);
+        assert!(results[0].label.contains("buy=0.2"));
+    }
+}
@@ -0,0 +1,289 @@
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use colored::Colorize;
+use lazy_static::lazy_static;
+
+use crate::engine::copy_trading::set_buying_enabled;
+use crate::engine::position_sizing::get_wallet_balance_sol;
+use crate::engine::risk_management;
+use crate::library::{config::AppState, logger::Logger};
+use crate::utilities::telegram;
+
+lazy_static! {
+    pub static ref BREAKER_CONFIG: CircuitBreakerConfig = CircuitBreakerConfig::from_env();
+    static ref BREAKER: Mutex<BreakerState> = Mutex::new(BreakerState::default());
+    static ref BREAKER_STARTED: AtomicBool = AtomicBool::new(false);
+    static ref CONSECUTIVE_FAILED_TXS: AtomicUsize = AtomicUsize::new(0);
+    // Unix time of the last gRPC message, 0 until the first one
+    static ref LAST_STREAM_ACTIVITY: AtomicI64 = AtomicI64::new(0);
+}
+
+/// Conditions that halt every new buy
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    pub enabled: bool,
+    pub max_failed_txs: usize,         // consecutive failed swaps
+    pub max_losing_trades: usize,      // consecutive closed positions at a loss
+    pub max_daily_loss_percentage: f64, // -10.0 = halt at -10% daily PnL
+    pub outage_secs: u64,              // gRPC silent or RPC failing this long
+    pub min_wallet_sol: f64,
+    pub cooldown_secs: u64,            // halted at least this long before reviving on its own
+}
+
+impl CircuitBreakerConfig {
+    /// Load from `CIRCUIT_BREAKER_ENABLED`, `BREAKER_MAX_FAILED_TXS`, `BREAKER_MAX_LOSING_TRADES`,
+    /// `BREAKER_MAX_DAILY_LOSS_PERCENTAGE`, `BREAKER_OUTAGE_SECS`, `BREAKER_MIN_WALLET_SOL` and `BREAKER_COOLDOWN_SECS`
+    pub fn from_env() -> Self {
+        Self {
+            enabled: std::env::var("CIRCUIT_BREAKER_ENABLED")
+                .ok()
+                .and_then(|v| v.parse::<bool>().ok())
+                .unwrap_or(true),
+            max_failed_txs: std::env::var("BREAKER_MAX_FAILED_TXS")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(5),
+            max_losing_trades: std::env::var("BREAKER_MAX_LOSING_TRADES")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(5),
+            max_daily_loss_percentage: std::env::var("BREAKER_MAX_DAILY_LOSS_PERCENTAGE")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(-10.0),
+            outage_secs: std::env::var("BREAKER_OUTAGE_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(60),
+            min_wallet_sol: std::env::var("BREAKER_MIN_WALLET_SOL")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(0.05),
+            cooldown_secs: std::env::var("BREAKER_COOLDOWN_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(900),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    tripped: Option<(String, Instant)>, // reason, since
+    manual: bool,                       // halted by command, only a command resumes
+    losses_baseline: usize,             // consecutive losses already acknowledged by a resume
+    daily_loss_acknowledged: Option<String>, // trading day a manual resume overrode the loss limit for
+    rpc_failing_since: Option<Instant>,
+    wallet_sol: Option<f64>,
+}
+
+fn breaker() -> MutexGuard<'static, BreakerState> {
+    BREAKER.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Whether the breaker currently halts buys
+pub fn is_tripped() -> bool {
+    breaker().tripped.is_some()
+}
+
+/// Count a swap outcome; too many failures in a row trips the breaker
+pub fn record_transaction(success: bool) {
+    if success {
+        CONSECUTIVE_FAILED_TXS.store(0, Ordering::SeqCst);
+        return;
+    }
+    let failed = CONSECUTIVE_FAILED_TXS.fetch_add(1, Ordering::SeqCst) + 1;
+    if BREAKER_CONFIG.enabled && failed >= BREAKER_CONFIG.max_failed_txs {
+        trip(format!("{} consecutive failed transactions", failed), false);
+    }
+}
+
+/// Mark the gRPC stream as alive
+pub fn record_stream_activity() {
+    LAST_STREAM_ACTIVITY.store(chrono::Utc::now().timestamp(), Ordering::Relaxed);
+}
+
+fn breaker_logger() -> Logger {
+    Logger::new("[CIRCUIT-BREAKER] => ".bright_red().bold().to_string())
+}
+
+/// Halt new buys and announce why; no-op when already halted
+pub fn trip(reason: String, manual: bool) {
+    {
+        let mut state = breaker();
+        if let Some((current, _)) = state.tripped.clone() {
+            if manual && !state.manual {
+                state.manual = true;
+                breaker_logger().log(format!("Circuit breaker ({}) now held by command", current).red().to_string());
+            }
+            return;
+        }
+        state.tripped = Some((reason.clone(), Instant::now()));
+        state.manual = manual;
+    }
+    set_buying_enabled(false);
+    breaker_logger().log(format!("🛑 Circuit breaker tripped: {}", reason).red().bold().to_string());
+    tokio::spawn(async move {
+        if let Err(e) = telegram::send_circuit_breaker_notification(true, &reason).await {
+            breaker_logger().log(format!("Failed to send circuit breaker notification: {}", e).red().to_string());
+        }
+    });
+}
+
+/// Re-enable buys; counters that caused the trip start over
+pub fn resume(reason: String, manual: bool) {
+    {
+        let mut state = breaker();
+        if state.tripped.take().is_none() {
+            return;
+        }
+        state.manual = false;
+        state.losses_baseline = risk_management::risk_engine().state().consecutive_losses;
+        if manual {
+            state.daily_loss_acknowledged = Some(risk_management::risk_engine().state().day);
+        }
+    }
+    CONSECUTIVE_FAILED_TXS.store(0, Ordering::SeqCst);
+    set_buying_enabled(true);
+    breaker_logger().log(format!("✅ Circuit breaker reset: {}", reason).green().bold().to_string());
+    tokio::spawn(async move {
+        if let Err(e) = telegram::send_circuit_breaker_notification(false, &reason).await {
+            breaker_logger().log(format!("Failed to send circuit breaker notification: {}", e).red().to_string());
+        }
+    });
+}
+
+/// One line describing the breaker
+pub fn status() -> String {
+    let state = breaker();
+    match &state.tripped {
+        Some((reason, since)) => format!(
+            "Buying halted{} for {}s: {} | failed txs {} | wallet {}",
+            if state.manual { " by command" } else { "" },
+            since.elapsed().as_secs(),
+            reason,
+            CONSECUTIVE_FAILED_TXS.load(Ordering::SeqCst),
+            state.wallet_sol.map(|sol| format!("{:.4} SOL", sol)).unwrap_or_else(|| "unknown".to_string())
+        ),
+        None => format!(
+            "Buying enabled | failed txs {} | wallet {}",
+            CONSECUTIVE_FAILED_TXS.load(Ordering::SeqCst),
+            state.wallet_sol.map(|sol| format!("{:.4} SOL", sol)).unwrap_or_else(|| "unknown".to_string())
+        ),
+    }
+}
+
+/// First trip condition that currently holds
+fn check_conditions(config: &CircuitBreakerConfig) -> Option<String> {
+    let failed = CONSECUTIVE_FAILED_TXS.load(Ordering::SeqCst);
+    if failed >= config.max_failed_txs {
+        return Some(format!("{} consecutive failed transactions", failed));
+    }
+
+    let risk = risk_management::risk_engine().state();
+    let daily_pnl = risk_management::risk_engine().daily_pnl();
+    let state = breaker();
+    let losses = risk.consecutive_losses.saturating_sub(state.losses_baseline);
+    if losses >= config.max_losing_trades {
+        return Some(format!("{} consecutive losing trades", losses));
+    }
+    if daily_pnl <= config.max_daily_loss_percentage && state.daily_loss_acknowledged.as_deref() != Some(risk.day.as_str()) {
+        return Some(format!("daily PnL {:.2}% below {}%", daily_pnl, config.max_daily_loss_percentage));
+    }
+
+    let outage = Duration::from_secs(config.outage_secs);
+    let last_activity = LAST_STREAM_ACTIVITY.load(Ordering::Relaxed);
+    if last_activity > 0 && chrono::Utc::now().timestamp() - last_activity > config.outage_secs as i64 {
+        return Some(format!("no gRPC updates for {}s", chrono::Utc::now().timestamp() - last_activity));
+    }
+    if let Some(since) = state.rpc_failing_since {
+        if since.elapsed() >= outage {
+            return Some(format!("RPC failing for {}s", since.elapsed().as_secs()));
+        }
+    }
+    if let Some(sol) = state.wallet_sol {
+        if sol < config.min_wallet_sol {
+            return Some(format!("wallet balance {:.4} SOL below {} SOL", sol, config.min_wallet_sol));
+        }
+    }
+    None
+}
+
+/// Start checking the trip conditions every few seconds; later calls are no-ops
+///
+/// A tripped breaker revives on its own once `cooldown_secs` passed and no condition holds,
+/// unless it was halted by command.
+pub fn start_circuit_breaker(app_state: Arc<AppState>) {
+    let config = &*BREAKER_CONFIG;
+    if !config.enabled || BREAKER_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let logger = breaker_logger();
+    logger.log(format!(
+        "Halting buys after {} failed txs, {} losing trades, {}% daily PnL, {}s outage or < {} SOL | cooldown {}s",
+        config.max_failed_txs, config.max_losing_trades, config.max_daily_loss_percentage,
+        config.outage_secs, config.min_wallet_sol, config.cooldown_secs
+    ));
+    telegram::start_command_listener(handle_command);
+
+    tokio::spawn(async move {
+        let mut checks = tokio::time::interval(Duration::from_secs(5));
+        loop {
+            checks.tick().await;
+            match get_wallet_balance_sol(&app_state).await {
+                Ok(sol) => {
+                    let mut state = breaker();
+                    state.wallet_sol = Some(sol);
+                    state.rpc_failing_since = None;
+                }
+                Err(e) => {
+                    logger.log(format!("RPC check failed: {}", e).red().to_string());
+                    breaker().rpc_failing_since.get_or_insert_with(Instant::now);
+                }
+            }
+
+            let cooled_down = {
+                let state = breaker();
+                match &state.tripped {
+                    Some(_) if state.manual => continue,
+                    Some((_, since)) => Some(since.elapsed() >= Duration::from_secs(config.cooldown_secs)),
+                    None => None,
+                }
+            };
+            match cooled_down {
+                None => {
+                    if let Some(reason) = check_conditions(config) {
+                        trip(reason, false);
+                    }
+                }
+                Some(true) => {
+                    // Give the counters a fresh start, revive only if nothing else still holds
+                    CONSECUTIVE_FAILED_TXS.store(0, Ordering::SeqCst);
+                    let consecutive_losses = risk_management::risk_engine().state().consecutive_losses;
+                    breaker().losses_baseline = consecutive_losses;
+                    if check_conditions(config).is_none() {
+                        resume("cooldown elapsed".to_string(), false);
+                    }
+                }
+                Some(false) => {}
+            }
+        }
+    });
+}
+
+/// Telegram commands: `/halt`, `/resume` and `/status`
+fn handle_command(command: &str) -> Option<String> {
+    match command.split_whitespace().next()? {
+        "/halt" => {
+            trip("halted by command".to_string(), true);
+            Some(status())
+        }
+        "/resume" => {
+            resume("resumed by command".to_string(), true);
+            Some(status())
+        }
+        "/status" => Some(format!("{}\n{}", status(), risk_management::risk_engine().get_risk_status())),
+        _ => None,
+    }
+}
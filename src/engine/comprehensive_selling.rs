@@ -1,35 +1,375 @@
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant};
 use anyhow::{anyhow, Result};
 use anchor_client::solana_sdk::pubkey::Pubkey;
 use anchor_client::solana_sdk::signature::Signer;
 use colored::Colorize;
 use dashmap::DashMap;
 use lazy_static::lazy_static;
+use once_cell::sync::OnceCell;
 use spl_associated_token_account::get_associated_token_address;
-use tokio::time::sleep;
+use tokio::task::JoinHandle;
+use tokio::time::interval;
 use tokio_util::sync::CancellationToken;
 
 use crate::library::{
-    config::{AppState, SwapConfig, TransactionLandingMode},
+    config::{AppState, SwapConfig},
     logger::Logger,
 };
-use crate::engine::transaction_parser::TradeInfoFromToken;
-use crate::engine::swap::{SwapDirection, SwapProtocol};
+use crate::engine::transaction_parser::{DexType, TradeInfoFromToken};
+use crate::engine::copy_trading::TOKEN_TRACKING;
+use crate::engine::journal::{self, JournalEntry, JournalEvent};
+use crate::engine::paper;
+use crate::engine::monitor::{check_retracement_levels, parse_retracement_levels, RetracementLevel, TokenTrackingInfo};
+use crate::engine::swap::{execute_swap, SwapDirection, SwapProtocol};
+use crate::engine::position_sizing::SizingDecision;
+use crate::engine::position_store::{self, StoredPosition};
+use crate::engine::risk_management;
+use crate::engine::target_scoring;
+use crate::tx_processor::fill::{self, Fill};
+use crate::utilities::telegram;
 
 
+// Sell attempts of an emergency exit before the position goes back to normal monitoring
+const EMERGENCY_EXIT_ATTEMPTS: u32 = 3;
+// Longest wait between retries of a failed ladder sell
+const SELL_RETRY_MAX_DELAY: Duration = Duration::from_secs(60);
+
+// Seller used by the emergency stop-loss watchdog
+static STOP_LOSS_WATCHDOG: OnceCell<ComprehensiveSelling> = OnceCell::new();
+
 // Global bought token tracking
 lazy_static! {
     static ref BOUGHT_TOKEN_LIST: Arc<DashMap<String, BoughtTokenInfo>> = Arc::new(DashMap::new());
     static ref MONITORING_TASKS: Arc<DashMap<String, CancellationToken>> = Arc::new(DashMap::new());
+    // Positions the emergency stop-loss watchdog is exiting
+    static ref STOP_LOSS_EXITS: Arc<DashMap<String, Instant>> = Arc::new(DashMap::new());
+    static ref POSITIONS_RESTORED: AtomicBool = AtomicBool::new(false);
+}
+
+/// A take-profit step: once PnL reaches `pnl_percentage`, sell `sell_percentage` of the original position
+#[derive(Debug, Clone, Copy)]
+pub struct TakeProfitTier {
+    pub pnl_percentage: f64,
+    pub sell_percentage: f64,
+}
+
+/// Realized PnL of a position after one of its sells was booked
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BookedPnl {
+    pub realized_pnl_sol: f64,
+    pub pnl_percentage: f64,
+}
+
+/// Exit rules applied to every bought position
+#[derive(Debug, Clone)]
+pub struct SellingStrategyConfig {
+    pub take_profit_tiers: Vec<TakeProfitTier>,  // sorted by pnl_percentage
+    pub trailing_stop_activation: f64,           // 20.0 = arm the trailing stop after +20%
+    pub trailing_stop_percentage: f64,           // 10.0 = sell everything after a 10% pullback from the high
+    pub selling_time: u64,                       // seconds before a position that never armed the trailing stop is closed, 0 = off
+    pub retracement_levels: Vec<RetracementLevel>, // sorted by threshold, highest first
+    pub check_interval_ms: u64,
+    pub max_sell_attempts: u32,                  // consecutive failed sells before the monitor gives up
+}
+
+impl Default for SellingStrategyConfig {
+    fn default() -> Self {
+        Self {
+            take_profit_tiers: vec![
+                TakeProfitTier { pnl_percentage: 20.0, sell_percentage: 25.0 },
+                TakeProfitTier { pnl_percentage: 50.0, sell_percentage: 25.0 },
+                TakeProfitTier { pnl_percentage: 100.0, sell_percentage: 25.0 },
+            ],
+            trailing_stop_activation: 20.0,
+            trailing_stop_percentage: 10.0,
+            selling_time: 0,
+            retracement_levels: Vec::new(),
+            check_interval_ms: 500,
+            max_sell_attempts: 6,
+        }
+    }
+}
+
+impl SellingStrategyConfig {
+    /// Load the strategy from the environment, keeping defaults for anything unset
+    ///
+    /// `TAKE_PROFIT_TIERS` is a comma-separated list of `pnl:sell` pairs, e.g. `20:25,50:25,100:50`.
+    /// `SELL_MAX_ATTEMPTS` caps consecutive failed sells of a position before its monitor stops.
+    pub fn from_env() -> Self {
+        let logger = Logger::new("[COMPREHENSIVE-SELLING] => ".magenta().to_string());
+        let mut config = Self::default();
+
+        if let Ok(tiers) = std::env::var("TAKE_PROFIT_TIERS") {
+            match parse_take_profit_tiers(&tiers) {
+                Ok(parsed) => config.take_profit_tiers = parsed,
+                Err(e) => {
+                    logger.log(format!("Ignoring TAKE_PROFIT_TIERS: {}", e).red().to_string());
+                }
+            }
+        }
+
+        config.trailing_stop_activation = std::env::var("TRAILING_STOP_ACTIVATION")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(config.trailing_stop_activation);
+
+        config.trailing_stop_percentage = std::env::var("TRAILING_STOP_PERCENTAGE")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .map(f64::abs)
+            .unwrap_or(config.trailing_stop_percentage);
+
+        if let Ok(levels) = std::env::var("RETRACEMENT_LEVELS") {
+            match parse_retracement_levels(&levels) {
+                Ok(parsed) => config.retracement_levels = parsed,
+                Err(e) => {
+                    logger.log(format!("Ignoring RETRACEMENT_LEVELS: {}", e).red().to_string());
+                }
+            }
+        }
+
+        config.selling_time = std::env::var("SELLING_TIME")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(config.selling_time);
+
+        config.check_interval_ms = std::env::var("SELL_CHECK_INTERVAL_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(config.check_interval_ms);
+
+        config.max_sell_attempts = std::env::var("SELL_MAX_ATTEMPTS")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .filter(|attempts| *attempts > 0)
+            .unwrap_or(config.max_sell_attempts);
+
+        config
+    }
+}
+
+/// Parse `pnl:sell` pairs into take-profit tiers sorted by PnL
+pub fn parse_take_profit_tiers(value: &str) -> Result<Vec<TakeProfitTier>> {
+    let mut tiers = Vec::new();
+
+    for pair in value.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (pnl, sell) = pair
+            .split_once(':')
+            .ok_or_else(|| anyhow!("expected pnl:sell, got {}", pair))?;
+        let pnl_percentage = pnl.trim().parse::<f64>()
+            .map_err(|e| anyhow!("invalid pnl in {}: {}", pair, e))?;
+        let sell_percentage = sell.trim().parse::<f64>()
+            .map_err(|e| anyhow!("invalid sell percentage in {}: {}", pair, e))?;
+        if sell_percentage <= 0.0 || sell_percentage > 100.0 {
+            return Err(anyhow!("sell percentage out of range in {}", pair));
+        }
+        tiers.push(TakeProfitTier { pnl_percentage, sell_percentage });
+    }
+
+    tiers.sort_by(|a, b| a.pnl_percentage.total_cmp(&b.pnl_percentage));
+    Ok(tiers)
+}
+
+/// What the monitor wants to do with a position on this tick
+#[derive(Debug, Clone, PartialEq)]
+pub enum SellDecision {
+    Hold,
+    /// Sell `percentage` of the original position and remember it under `key`
+    Partial { key: String, percentage: f64, reason: String },
+    /// Sell everything that is left
+    All { reason: String },
+}
+
+/// What the exit rules look at, independent of the clock the position is held on
+#[derive(Debug, Clone, Copy)]
+pub struct ExitState<'a> {
+    pub pnl: f64,
+    pub trailing_stop: f64,          // % from the highest price, <= 0
+    pub highest_price: f64,
+    pub trailing_armed: bool,
+    pub held_secs: u64,
+    pub selling_time: u64,
+    pub remaining_amount: f64,
+    pub sold_percentages: &'a HashMap<String, f64>,
+}
+
+/// Decide whether a position should be (partially) sold given its current price state
+pub fn evaluate_position(info: &BoughtTokenInfo, config: &SellingStrategyConfig) -> SellDecision {
+    evaluate_exit(&ExitState {
+        pnl: info.calculate_pnl(),
+        trailing_stop: info.calculate_trailing_stop(),
+        highest_price: info.highest_price,
+        trailing_armed: info.reached_20_percent,
+        held_secs: info.entry_time.elapsed().as_secs(),
+        selling_time: info.selling_time,
+        remaining_amount: info.remaining_amount,
+        sold_percentages: &info.sold_percentages,
+    }, config)
+}
+
+/// The selling-time, trailing-stop and take-profit rules, in that order
+pub fn evaluate_exit(state: &ExitState, config: &SellingStrategyConfig) -> SellDecision {
+    if state.remaining_amount <= 0.0 {
+        return SellDecision::Hold;
+    }
+
+    if state.selling_time > 0 && state.held_secs >= state.selling_time && !state.trailing_armed {
+        return SellDecision::All {
+            reason: format!("selling time of {}s elapsed", state.selling_time),
+        };
+    }
+
+    if state.trailing_armed && state.trailing_stop <= -config.trailing_stop_percentage {
+        return SellDecision::All {
+            reason: format!(
+                "trailing stop hit ({:.2}% from high {:.10})",
+                state.trailing_stop, state.highest_price
+            ),
+        };
+    }
+
+    for tier in &config.take_profit_tiers {
+        let key = format!("tp_{}", tier.pnl_percentage);
+        if state.pnl >= tier.pnl_percentage && !state.sold_percentages.contains_key(&key) {
+            return SellDecision::Partial {
+                key,
+                percentage: tier.sell_percentage.min(state.remaining_amount),
+                reason: format!("take profit tier +{}% reached (PnL {:.2}%)", tier.pnl_percentage, state.pnl),
+            };
+        }
+    }
+
+    SellDecision::Hold
 }
 
-/// Placeholder struct for ComprehensiveSelling functionality
-/// This was removed for public purposes
+/// Check the retracement ladder of a position; a fired level sells a share of what is left
+pub fn evaluate_retracement(
+    info: &BoughtTokenInfo,
+    tracking: &mut TokenTrackingInfo,
+    config: &SellingStrategyConfig,
+) -> SellDecision {
+    if info.remaining_amount <= 0.0 || config.retracement_levels.is_empty() {
+        return SellDecision::Hold;
+    }
+
+    let pnl = info.calculate_pnl();
+    match check_retracement_levels(tracking, pnl, &config.retracement_levels) {
+        Some(level) => SellDecision::Partial {
+            key: level.key(),
+            percentage: info.remaining_amount * level.sell_amount as f64 / 100.0,
+            reason: format!(
+                "retracement of {}% from top PnL {:.2}% after passing +{}% (PnL {:.2}%)",
+                level.percentage, tracking.top_pnl, level.threshold, pnl
+            ),
+        },
+        None => SellDecision::Hold,
+    }
+}
+
+/// Feed a new observed price for a token into its position, if we hold it
+pub fn update_token_price(token_mint: &str, price: f64) {
+    if price <= 0.0 {
+        return;
+    }
+    let pnl = match BOUGHT_TOKEN_LIST.get_mut(token_mint) {
+        Some(mut info) => {
+            info.update_price(price);
+            info.calculate_pnl()
+        }
+        None => return,
+    };
+    check_emergency_stop_loss(token_mint, pnl);
+}
+
+/// Write a position and its ladder state to the position book
+///
+/// Must not be called while holding an entry of `BOUGHT_TOKEN_LIST` or `TOKEN_TRACKING`.
+fn persist_position(token_mint: &str) {
+    let Some(info) = get_bought_token(token_mint) else { return };
+    let tracking = TOKEN_TRACKING.get(token_mint).map(|entry| entry.value().clone());
+    position_store::queue_save(StoredPosition::from_position(&info, tracking.as_ref()));
+}
+
+/// Market-sell a position past the risk engine's emergency stop-loss, whatever strategy opened it
+fn check_emergency_stop_loss(token_mint: &str, pnl: f64) {
+    let Some(selling) = STOP_LOSS_WATCHDOG.get() else { return };
+    let threshold = risk_management::emergency_stop_loss_percentage();
+    if pnl > threshold || STOP_LOSS_EXITS.contains_key(token_mint) {
+        return;
+    }
+    STOP_LOSS_EXITS.insert(token_mint.to_string(), Instant::now());
+
+    let selling = selling.clone();
+    let mint = token_mint.to_string();
+    tokio::spawn(async move {
+        let reason = format!("emergency stop-loss: PnL {:.2}% <= {}%", pnl, threshold);
+        match selling.emergency_exit(&mint, &reason).await {
+            Ok((signature, booked)) => {
+                let booked = booked.await.unwrap_or_default();
+                if let Err(e) = telegram::send_stop_loss_notification(&mint, booked.pnl_percentage, booked.realized_pnl_sol, &signature).await {
+                    selling.logger.log(format!("Failed to send stop-loss notification: {}", e).yellow().to_string());
+                }
+            }
+            Err(e) => {
+                selling.logger.log(format!("Emergency stop-loss exit of {} failed: {}", mint, e).red().to_string());
+            }
+        }
+        // A failed exit is retried on the next price update
+        STOP_LOSS_EXITS.remove(&mint);
+    });
+}
+
+/// Wait before retry `attempt` of a failed sell: twice the check interval, doubling per attempt, capped at a minute
+fn sell_retry_delay(check_interval_ms: u64, attempt: u32) -> Duration {
+    let base = Duration::from_millis(check_interval_ms.max(250) * 2);
+    base.saturating_mul(1u32 << attempt.saturating_sub(1).min(16)).min(SELL_RETRY_MAX_DELAY)
+}
+
+/// Snapshot of a held position
+pub fn get_bought_token(token_mint: &str) -> Option<BoughtTokenInfo> {
+    BOUGHT_TOKEN_LIST.get(token_mint).map(|entry| entry.value().clone())
+}
+
+/// Mints of all positions currently being monitored
+pub fn get_bought_token_mints() -> Vec<String> {
+    BOUGHT_TOKEN_LIST.iter().map(|entry| entry.key().clone()).collect()
+}
+
+/// Number of open positions that were copied from `target`
+pub fn count_positions_for_target(target: &str) -> usize {
+    BOUGHT_TOKEN_LIST.iter().filter(|entry| entry.trade_info.target == target).count()
+}
+
+/// Route a PumpFun position's exits to PumpSwap once its pool was created
+///
+/// Returns false when the position is not held or already trades on PumpSwap.
+pub fn migrate_position(token_mint: &str, pool: Option<&str>) -> bool {
+    let Some(mut info) = BOUGHT_TOKEN_LIST.get_mut(token_mint) else { return false };
+    if pool.is_some() {
+        info.trade_info.pool = pool.map(str::to_string);
+    }
+    if info.protocol == SwapProtocol::PumpSwap {
+        drop(info);
+        persist_position(token_mint);
+        return false;
+    }
+    info.protocol = SwapProtocol::PumpSwap;
+    info.trade_info.dex_type = DexType::PumpSwap;
+    drop(info);
+    persist_position(token_mint);
+    true
+}
+
+/// Runs exit monitoring for bought positions: tiered take-profit, trailing stop and time exit
+#[derive(Clone)]
 pub struct ComprehensiveSelling {
     app_state: Arc<AppState>,
     swap_config: Arc<SwapConfig>,
+    selling_config: Arc<SellingStrategyConfig>,
+    logger: Logger,
 }
 
 impl ComprehensiveSelling {
@@ -38,29 +378,646 @@ impl ComprehensiveSelling {
         Self {
             app_state,
             swap_config,
+            selling_config: Arc::new(SellingStrategyConfig::from_env()),
+            logger: Logger::new("[COMPREHENSIVE-SELLING] => ".magenta().to_string()),
+        }
+    }
+
+    pub fn app_state(&self) -> &AppState {
+        &self.app_state
+    }
+
+    pub fn swap_config(&self) -> &SwapConfig {
+        &self.swap_config
+    }
+
+    /// Let every price update exit positions past the emergency stop-loss through this seller
+    pub fn start_stop_loss_watchdog(&self) {
+        if STOP_LOSS_WATCHDOG.set(self.clone()).is_ok() {
+            self.logger.log(format!(
+                "Emergency stop-loss watchdog armed at {}%",
+                risk_management::emergency_stop_loss_percentage()
+            ).red().to_string());
         }
     }
-    
-    /// Placeholder method for starting comprehensive selling monitoring
-    pub async fn start_monitoring(&self, _token_mint: &str, _trade_info: TradeInfoFromToken) -> Result<()> {
-        // Placeholder implementation
-        // Add actual comprehensive selling logic here when needed
-        println!("ComprehensiveSelling: monitoring started for token  ");
+
+    /// Register a bought position and spawn its monitor task
+    pub async fn start_monitoring(
+        &self,
+        token_mint: &str,
+        signature: &str,
+        trade_info: TradeInfoFromToken,
+        sizing: SizingDecision,
+    ) -> Result<()> {
+        if MONITORING_TASKS.contains_key(token_mint) {
+            self.logger.log(format!("Already monitoring {}", token_mint).yellow().to_string());
+            return Ok(());
+        }
+
+        let entry_price = trade_info.price_in_sol().unwrap_or(0.0);
+        let selling_time = self.selling_config.selling_time;
+
+        BOUGHT_TOKEN_LIST.insert(token_mint.to_string(), BoughtTokenInfo {
+            token_mint: token_mint.to_string(),
+            entry_price,
+            entry_amount: sizing.amount_sol,
+            entry_time: Instant::now(),
+            highest_price: entry_price,
+            lowest_price_after_highest: entry_price,
+            current_price: entry_price,
+            protocol: SwapProtocol::from(&trade_info.dex_type),
+            trade_info,
+            app_state: self.app_state.clone(),
+            swap_config: self.swap_config.clone(),
+            selling_time,
+            reached_20_percent: false,
+            sold_percentages: HashMap::new(),
+            remaining_amount: 100.0,
+            sizing_reason: sizing.reason,
+            realized_pnl_sol: 0.0,
+            exit_price: 0.0,
+            entry_slippage_percentage: 0.0,
+            exit_slippage_percentage: 0.0,
+        });
+
+        TOKEN_TRACKING.insert(token_mint.to_string(), TokenTrackingInfo::new());
+        persist_position(token_mint);
+        let selling = self.clone();
+        let (mint, signature) = (token_mint.to_string(), signature.to_string());
+        tokio::spawn(async move {
+            selling.record_buy_fill(&mint, &signature).await;
+        });
+
+        self.logger.log(format!(
+            "Monitoring {} | Entry price: {:.10} SOL | Tiers: {} | Trailing: {}% after +{}% | Selling time: {}s",
+            token_mint,
+            entry_price,
+            self.selling_config.take_profit_tiers.len(),
+            self.selling_config.trailing_stop_percentage,
+            self.selling_config.trailing_stop_activation,
+            selling_time
+        ).green().to_string());
+
+        self.spawn_monitor(token_mint);
         Ok(())
     }
-    
-    /// Placeholder method for stopping monitoring
-    pub async fn stop_monitoring(&self, _token_mint: &str) -> Result<()> {
-        // Placeholder implementation
-        println!("ComprehensiveSelling: monitoring stopped for token  ");
+
+    /// Replace the quoted entry with the buy as it landed; the quote is journaled when the fill cannot be measured
+    async fn record_buy_fill(&self, token_mint: &str, signature: &str) {
+        let Some(info) = get_bought_token(token_mint) else { return };
+        let quoted_price = info.entry_price;
+        match self.fetch_fill(token_mint, signature, SwapDirection::Buy).await {
+            Ok(fill) => {
+                let slippage = fill.slippage_percentage(quoted_price, &SwapDirection::Buy);
+                if let Some(mut position) = BOUGHT_TOKEN_LIST.get_mut(token_mint) {
+                    position.entry_price = fill.price;
+                    position.entry_amount = fill.sol_amount;
+                    position.entry_slippage_percentage = slippage;
+                    // No newer price seen yet, the high starts at the real entry
+                    if position.highest_price <= quoted_price {
+                        position.highest_price = fill.price;
+                        position.lowest_price_after_highest = fill.price;
+                    }
+                }
+                persist_position(token_mint);
+                self.logger.log(format!(
+                    "Buy of {} filled {} tokens for {:.6} SOL at {:.10} (quoted {:.10}, slippage {:.2}%) | fee {:.6} tip {:.6} rent {:.6} venue {:.6} SOL",
+                    token_mint, fill.token_amount, fill.sol_amount, fill.price, quoted_price, slippage,
+                    fill.fee_sol(), fill.tip_sol, fill.rent_sol, fill.venue_fee_sol
+                ));
+                journal::record_fill(token_mint, &info.trade_info.target, &info.protocol, &SwapDirection::Buy, &fill, quoted_price, 0.0);
+            }
+            Err(e) => {
+                self.logger.log(format!("Keeping the quoted entry of {}: {}", token_mint, e).yellow().to_string());
+                journal::record(JournalEntry {
+                    event: JournalEvent::Fill,
+                    mint: token_mint.to_string(),
+                    target: info.trade_info.target.clone(),
+                    venue: format!("{:?}", info.protocol),
+                    side: "buy".to_string(),
+                    signature: signature.to_string(),
+                    sol_amount: info.entry_amount,
+                    price: quoted_price,
+                    quoted_price,
+                    estimated: true,
+                    ..Default::default()
+                });
+            }
+        }
+    }
+
+    /// Measure what our swap `signature` on `token_mint` actually moved
+    async fn fetch_fill(&self, token_mint: &str, signature: &str, direction: SwapDirection) -> Result<Fill> {
+        let mint = token_mint.parse::<Pubkey>()
+            .map_err(|e| anyhow!("Invalid mint {}: {}", token_mint, e))?;
+        let wallet = self.app_state.wallet.try_pubkey()
+            .map_err(|e| anyhow!("Failed to get wallet pubkey: {}", e))?;
+        fill::fetch_fill(self.app_state.rpc_nonblocking_client.clone(), signature, &wallet, &mint, direction).await
+    }
+
+    fn spawn_monitor(&self, token_mint: &str) {
+        let cancel_token = CancellationToken::new();
+        MONITORING_TASKS.insert(token_mint.to_string(), cancel_token.clone());
+
+        let selling = self.clone();
+        let mint = token_mint.to_string();
+        tokio::spawn(async move {
+            selling.monitor_position(mint, cancel_token).await;
+        });
+    }
+
+    /// Rebuild the open positions from the position book and resume their monitors; later calls are no-ops
+    ///
+    /// Positions whose tokens are no longer in the wallet were closed while offline and are dropped.
+    /// Every position is then written back every 10 seconds so price highs survive a restart.
+    pub async fn restore_positions(&self) -> Result<usize> {
+        if POSITIONS_RESTORED.swap(true, Ordering::SeqCst) {
+            return Ok(0);
+        }
+        position_store::open_position_store()?;
+        let wallet = self.app_state.wallet.try_pubkey()
+            .map_err(|e| anyhow!("Failed to get wallet pubkey: {}", e))?;
+
+        let mut restored = 0;
+        for stored in position_store::load_positions()? {
+            if BOUGHT_TOKEN_LIST.contains_key(&stored.mint) {
+                continue;
+            }
+            let Ok(mint) = stored.mint.parse::<Pubkey>() else { continue };
+            let balance = match self.held_balance(&wallet, &mint).await {
+                Ok(Some(balance)) => Some(balance),
+                Ok(None) => {
+                    self.logger.log(format!("{} no longer held, dropping it from the position book", stored.mint).yellow().to_string());
+                    position_store::queue_delete(&stored.mint);
+                    continue;
+                }
+                Err(e) => {
+                    // Keep the row and manage the position as stored rather than forget tokens we may hold
+                    self.logger.log(format!("Restoring {} as stored, balance unknown: {}", stored.mint, e).yellow().to_string());
+                    None
+                }
+            };
+            // The wallet holds what is left, whatever sells the store missed
+            let remaining_amount = match balance {
+                Some((_, ui_balance)) if stored.entry_price > 0.0 && stored.entry_amount > 0.0 => {
+                    let bought = stored.entry_amount / stored.entry_price;
+                    (ui_balance / bought * 100.0).min(100.0)
+                }
+                _ => stored.remaining_amount,
+            };
+
+            let elapsed = (chrono::Utc::now().timestamp() - stored.entry_timestamp).max(0) as u64;
+            let entry_time = Instant::now().checked_sub(Duration::from_secs(elapsed)).unwrap_or_else(Instant::now);
+            let trade_info = TradeInfoFromToken {
+                dex_type: stored.dex_type(),
+                signature: stored.signature.clone(),
+                target: stored.target.clone(),
+                mint: stored.mint.clone(),
+                coin_creator: stored.coin_creator.clone(),
+                pool: stored.pool.clone(),
+                bonding_curve: stored.bonding_curve.clone(),
+                ..Default::default()
+            };
+            BOUGHT_TOKEN_LIST.insert(stored.mint.clone(), BoughtTokenInfo {
+                token_mint: stored.mint.clone(),
+                entry_price: stored.entry_price,
+                entry_amount: stored.entry_amount,
+                entry_time,
+                highest_price: stored.highest_price,
+                lowest_price_after_highest: stored.lowest_price_after_highest,
+                current_price: stored.current_price,
+                protocol: stored.swap_protocol(),
+                trade_info,
+                app_state: self.app_state.clone(),
+                swap_config: self.swap_config.clone(),
+                selling_time: self.selling_config.selling_time,
+                reached_20_percent: stored.reached_20_percent,
+                sold_percentages: stored.sold_percentages.clone(),
+                remaining_amount,
+                sizing_reason: stored.sizing_reason.clone(),
+                realized_pnl_sol: stored.realized_pnl_sol,
+                exit_price: 0.0,
+                entry_slippage_percentage: 0.0,
+                exit_slippage_percentage: 0.0,
+            });
+            TOKEN_TRACKING.insert(stored.mint.clone(), TokenTrackingInfo {
+                top_pnl: stored.top_pnl,
+                completed_intervals: stored.completed_intervals.clone(),
+                ..TokenTrackingInfo::new()
+            });
+            self.logger.log(format!(
+                "Restored {} | {:.4} SOL at {:.10} | {:.1}% left (stored {:.1}%) | balance {}",
+                stored.mint, stored.entry_amount, stored.entry_price, remaining_amount, stored.remaining_amount,
+                balance.map(|(raw, _)| raw.to_string()).unwrap_or_else(|| "unknown".to_string())
+            ).green().to_string());
+            if remaining_amount != stored.remaining_amount {
+                persist_position(&stored.mint);
+            }
+            self.spawn_monitor(&stored.mint);
+            restored += 1;
+        }
+
+        tokio::spawn(async {
+            let mut flush_interval = interval(Duration::from_secs(10));
+            loop {
+                flush_interval.tick().await;
+                for mint in get_bought_token_mints() {
+                    persist_position(&mint);
+                }
+            }
+        });
+        Ok(restored)
+    }
+
+    /// Raw and UI token balance of `mint` in the wallet, `None` when its token account is confirmed missing
+    ///
+    /// RPC failures are retried a few times before giving up, so a flaky node never reads as an empty wallet.
+    async fn held_balance(&self, wallet: &Pubkey, mint: &Pubkey) -> Result<Option<(u64, f64)>> {
+        if paper::is_paper_mode() {
+            let raw = paper::token_balance(&mint.to_string());
+            return Ok((raw > 0).then(|| (raw, raw as f64 / 1_000_000.0)));
+        }
+        let token_account = get_associated_token_address(wallet, mint);
+        let client = &self.app_state.rpc_nonblocking_client;
+        let mut last_error = anyhow!("No balance lookup attempted for {}", mint);
+        for attempt in 0..3 {
+            if attempt > 0 {
+                tokio::time::sleep(Duration::from_millis(500)).await;
+            }
+            match client.get_account_with_commitment(&token_account, client.commitment()).await {
+                Ok(response) if response.value.is_none() => return Ok(None),
+                Ok(_) => {}
+                Err(e) => {
+                    last_error = anyhow!("Failed to look up token account of {}: {}", mint, e);
+                    continue;
+                }
+            }
+            match client.get_token_account_balance(&token_account).await {
+                Ok(balance) => {
+                    let raw = balance.amount.parse::<u64>()
+                        .map_err(|e| anyhow!("Invalid token balance for {}: {}", mint, e))?;
+                    if raw == 0 {
+                        return Ok(None);
+                    }
+                    let ui = balance.ui_amount.unwrap_or(raw as f64 / 10f64.powi(balance.decimals as i32));
+                    return Ok(Some((raw, ui)));
+                }
+                Err(e) => last_error = anyhow!("Failed to get token balance for {}: {}", mint, e),
+            }
+        }
+        Err(last_error)
+    }
+
+    /// Sell the whole position right away, bypassing the exit ladder
+    ///
+    /// The monitor task is stopped first so no ladder step races the exit. If every attempt fails the
+    /// monitor is restarted so the position is not left unmanaged. Returns the sell signature and the
+    /// task booking the position's realized PnL from the measured fill.
+    pub async fn emergency_exit(&self, token_mint: &str, reason: &str) -> Result<(String, JoinHandle<BookedPnl>)> {
+        if let Some((_, cancel_token)) = MONITORING_TASKS.remove(token_mint) {
+            cancel_token.cancel();
+        }
+        self.logger.log(format!("🚨 EMERGENCY EXIT {} | {}", token_mint, reason).red().bold().to_string());
+        if let Err(e) = telegram::send_emergency_exit_notification(token_mint, reason).await {
+            self.logger.log(format!("Failed to send emergency exit notification: {}", e).yellow().to_string());
+        }
+
+        let mut last_error = anyhow!("No sell attempted for {}", token_mint);
+        for attempt in 1..=EMERGENCY_EXIT_ATTEMPTS {
+            match self.sell_position_share(token_mint, None, 100.0, None).await {
+                Ok(sale) => {
+                    let _ = self.stop_monitoring(token_mint).await;
+                    return Ok(sale);
+                }
+                Err(e) => {
+                    self.logger.log(format!(
+                        "Emergency sell attempt {}/{} failed for {}: {}",
+                        attempt, EMERGENCY_EXIT_ATTEMPTS, token_mint, e
+                    ).red().to_string());
+                    last_error = e;
+                }
+            }
+        }
+
+        if BOUGHT_TOKEN_LIST.contains_key(token_mint) && !MONITORING_TASKS.contains_key(token_mint) {
+            self.spawn_monitor(token_mint);
+        }
+        Err(last_error)
+    }
+
+    /// Cancel the monitor task of a position and drop it from the bought list
+    pub async fn stop_monitoring(&self, token_mint: &str) -> Result<()> {
+        if let Some((_, cancel_token)) = MONITORING_TASKS.remove(token_mint) {
+            cancel_token.cancel();
+        }
+        BOUGHT_TOKEN_LIST.remove(token_mint);
+        TOKEN_TRACKING.remove(token_mint);
+        position_store::queue_delete(token_mint);
+        self.logger.log(format!("Monitoring stopped for {}", token_mint));
         Ok(())
     }
-    
-    /// Placeholder method for executing sell
-    pub async fn execute_sell(&self, _token_mint: &str, _percentage: f64) -> Result<String> {
-        // Placeholder implementation
-        // Return a placeholder transaction signature
-        Ok("ComprehensiveSellingPlaceholderTxSignature1111111111111".to_string())
+
+    /// Monitor loop for a single position, runs until the position is closed or cancelled
+    async fn monitor_position(&self, token_mint: String, cancel_token: CancellationToken) {
+        let mut ticker = interval(Duration::from_millis(self.selling_config.check_interval_ms));
+        // Failed sells back off exponentially so a broken venue is not hammered every tick
+        let mut failed_sells = 0u32;
+        let mut retry_at: Option<Instant> = None;
+
+        loop {
+            tokio::select! {
+                _ = cancel_token.cancelled() => {
+                    self.logger.log(format!("Monitor for {} cancelled", token_mint));
+                    return;
+                }
+                _ = ticker.tick() => {}
+            }
+            if retry_at.is_some_and(|at| Instant::now() < at) {
+                continue;
+            }
+
+            let mut armed = false;
+            let decision = match BOUGHT_TOKEN_LIST.get_mut(&token_mint) {
+                Some(mut info) => {
+                    if !info.reached_20_percent
+                        && info.calculate_pnl() >= self.selling_config.trailing_stop_activation
+                    {
+                        info.reached_20_percent = true;
+                        armed = true;
+                        self.logger.log(format!(
+                            "{} reached +{}%, trailing stop armed",
+                            token_mint, self.selling_config.trailing_stop_activation
+                        ).cyan().to_string());
+                    }
+                    let mut decision = evaluate_position(&info, &self.selling_config);
+                    if decision == SellDecision::Hold {
+                        if let Some(mut tracking) = TOKEN_TRACKING.get_mut(&token_mint) {
+                            decision = evaluate_retracement(&info, &mut tracking, &self.selling_config);
+                        }
+                    }
+                    decision
+                }
+                None => {
+                    MONITORING_TASKS.remove(&token_mint);
+                    return;
+                }
+            };
+            if armed {
+                persist_position(&token_mint);
+            }
+
+            let result = match decision {
+                SellDecision::Hold => continue,
+                SellDecision::Partial { key, percentage, reason } => {
+                    self.logger.log(format!("{} | {} -> selling {:.2}% of position", token_mint, reason, percentage).yellow().to_string());
+                    match self.sell_position_share(&token_mint, Some(key.clone()), percentage, None).await {
+                        Ok(_) => {
+                            if let Some(mut tracking) = TOKEN_TRACKING.get_mut(&token_mint) {
+                                if let Some(level) = self.selling_config.retracement_levels.iter().find(|l| l.key() == key) {
+                                    tracking.complete(level);
+                                }
+                            }
+                            persist_position(&token_mint);
+                            Ok(())
+                        }
+                        Err(e) => Err(format!("Partial sell failed for {}: {}", token_mint, e)),
+                    }
+                }
+                SellDecision::All { reason } => {
+                    self.logger.log(format!("{} | {} -> selling everything", token_mint, reason).yellow().to_string());
+                    match self.sell_position_share(&token_mint, None, 100.0, None).await {
+                        Ok(_) => {
+                            let _ = self.stop_monitoring(&token_mint).await;
+                            return;
+                        }
+                        Err(e) => Err(format!("Full sell failed for {}: {}", token_mint, e)),
+                    }
+                }
+            };
+
+            match result {
+                Ok(()) => {
+                    failed_sells = 0;
+                    retry_at = None;
+                }
+                Err(error) => {
+                    failed_sells += 1;
+                    if failed_sells >= self.selling_config.max_sell_attempts {
+                        self.give_up_monitoring(&token_mint, failed_sells, &error).await;
+                        return;
+                    }
+                    let delay = sell_retry_delay(self.selling_config.check_interval_ms, failed_sells);
+                    self.logger.log(format!(
+                        "{} (attempt {}/{}), retrying in {:?}",
+                        error, failed_sells, self.selling_config.max_sell_attempts, delay
+                    ).red().to_string());
+                    retry_at = Some(Instant::now() + delay);
+                }
+            }
+        }
+    }
+
+    /// Stop a monitor whose sells keep failing; the position stays held and booked for a manual exit
+    async fn give_up_monitoring(&self, token_mint: &str, attempts: u32, error: &str) {
+        MONITORING_TASKS.remove(token_mint);
+        self.logger.log(format!(
+            "{} | giving up after {} failed sells, position left unmonitored",
+            error, attempts
+        ).red().bold().to_string());
+        let alert = format!(
+            "Selling {} failed {} times in a row, its monitor is stopped. Last error: {}",
+            token_mint, attempts, error
+        );
+        if let Err(e) = telegram::send_error_notification(&alert).await {
+            self.logger.log(format!("Failed to send sell failure alert: {}", e).yellow().to_string());
+        }
+    }
+
+    /// Sell `percentage` of what is left of a position, closing it when (nearly) everything goes
+    ///
+    /// `slippage` overrides the configured slippage, e.g. with the copied target's.
+    pub async fn sell_holdings_share(&self, token_mint: &str, percentage: f64, key: String, slippage: Option<u64>) -> Result<String> {
+        if percentage >= 99.0 {
+            let (signature, _) = self.sell_position_share(token_mint, None, 100.0, slippage).await?;
+            self.stop_monitoring(token_mint).await?;
+            return Ok(signature);
+        }
+
+        let remaining = BOUGHT_TOKEN_LIST
+            .get(token_mint)
+            .map(|info| info.remaining_amount)
+            .ok_or_else(|| anyhow!("No position for {}", token_mint))?;
+        self.sell_position_share(token_mint, Some(key), remaining * percentage / 100.0, slippage)
+            .await
+            .map(|(signature, _)| signature)
+    }
+
+    /// Sell a share of the original position and update the bookkeeping on success
+    ///
+    /// `percentage` is relative to the original position; `None` as key means sell all that is left.
+    /// The proceeds are booked by the returned task once the fill was measured.
+    async fn sell_position_share(
+        &self,
+        token_mint: &str,
+        key: Option<String>,
+        percentage: f64,
+        slippage: Option<u64>,
+    ) -> Result<(String, JoinHandle<BookedPnl>)> {
+        let remaining = BOUGHT_TOKEN_LIST
+            .get(token_mint)
+            .map(|info| info.remaining_amount)
+            .ok_or_else(|| anyhow!("No position for {}", token_mint))?;
+        if remaining <= 0.0 {
+            return Err(anyhow!("Position {} is already closed", token_mint));
+        }
+
+        // Convert from "percent of original" to "percent of what we still hold"
+        let share_of_holdings = if key.is_none() {
+            100.0
+        } else {
+            (percentage / remaining * 100.0).min(100.0)
+        };
+
+        let signature = self.execute_sell(token_mint, share_of_holdings, slippage).await?;
+
+        // Shares leave the position right away; their proceeds are booked once the fill is measured
+        let Some(mut info) = BOUGHT_TOKEN_LIST.get_mut(token_mint) else {
+            return Err(anyhow!("Position {} closed while selling", token_mint));
+        };
+        let sold = if key.is_none() { info.remaining_amount } else { percentage.min(info.remaining_amount) };
+        info.remaining_amount = (info.remaining_amount - sold).max(0.0);
+        info.sold_percentages.insert(key.unwrap_or_else(|| "exit".to_string()), sold);
+        let sale = info.clone();
+        let closed = info.remaining_amount <= 0.0;
+        drop(info);
+        if !closed {
+            persist_position(token_mint);
+        }
+
+        let selling = self.clone();
+        let mint = token_mint.to_string();
+        let booking_signature = signature.clone();
+        let booked = tokio::spawn(async move {
+            selling.book_sell(&mint, &booking_signature, sale, sold, closed).await
+        });
+        Ok((signature, booked))
+    }
+
+    /// Book the proceeds of a sell of `sold` percent of the original position, measured from the landed
+    /// transaction or valued at the last price; `position` is the position as it was once the shares left
+    ///
+    /// Closing sells also feed the risk engine and target stats. Returns the position's realized PnL.
+    async fn book_sell(&self, token_mint: &str, signature: &str, position: BoughtTokenInfo, sold: f64, closed: bool) -> BookedPnl {
+        let cost_basis_sol = position.entry_amount * sold / 100.0;
+        let quoted_price = position.current_price;
+        let pnl_sol = match self.fetch_fill(token_mint, signature, SwapDirection::Sell).await {
+            Ok(fill) => {
+                let slippage = fill.slippage_percentage(quoted_price, &SwapDirection::Sell);
+                self.logger.log(format!(
+                    "Sell of {} filled {} tokens for {:.6} SOL at {:.10} (quoted {:.10}, slippage {:.2}%) | fee {:.6} tip {:.6} rent {:.6} venue {:.6} SOL",
+                    token_mint, fill.token_amount, fill.sol_amount, fill.price, quoted_price, slippage,
+                    fill.fee_sol(), fill.tip_sol, fill.rent_sol, fill.venue_fee_sol
+                ));
+                journal::record_fill(token_mint, &position.trade_info.target, &position.protocol, &SwapDirection::Sell, &fill, quoted_price, cost_basis_sol);
+                if let Some(mut info) = BOUGHT_TOKEN_LIST.get_mut(token_mint) {
+                    info.exit_price = fill.price;
+                    info.exit_slippage_percentage = slippage;
+                }
+                fill.sol_amount - cost_basis_sol
+            }
+            Err(e) => {
+                self.logger.log(format!("Valuing the sell of {} at the last price: {}", token_mint, e).yellow().to_string());
+                let pnl_sol = cost_basis_sol * position.calculate_pnl() / 100.0;
+                journal::record(JournalEntry {
+                    event: JournalEvent::Fill,
+                    mint: token_mint.to_string(),
+                    target: position.trade_info.target.clone(),
+                    venue: format!("{:?}", position.protocol),
+                    side: "sell".to_string(),
+                    signature: signature.to_string(),
+                    sol_amount: cost_basis_sol + pnl_sol,
+                    price: quoted_price,
+                    cost_basis_sol,
+                    quoted_price,
+                    estimated: true,
+                    ..Default::default()
+                });
+                pnl_sol
+            }
+        };
+
+        // A closed position may already be gone from the list, then the snapshot carries the earlier sells
+        let realized_pnl_sol = match BOUGHT_TOKEN_LIST.get_mut(token_mint) {
+            Some(mut info) => {
+                info.realized_pnl_sol += pnl_sol;
+                info.realized_pnl_sol
+            }
+            None => position.realized_pnl_sol + pnl_sol,
+        };
+        if !closed {
+            persist_position(token_mint);
+        }
+        let pnl_percentage = if position.entry_amount > 0.0 { realized_pnl_sol / position.entry_amount * 100.0 } else { 0.0 };
+
+        let protocol = format!("{:?}", position.protocol);
+        if let Err(e) = telegram::send_copy_trade_notification(&position.trade_info, signature, &protocol, "SOLD").await {
+            self.logger.log(format!("Failed to send sell notification: {}", e).yellow().to_string());
+        }
+        if closed {
+            risk_management::record_position_closed(realized_pnl_sol, pnl_percentage);
+            if !position.trade_info.target.is_empty() {
+                target_scoring::record_copied_close(&position.trade_info.target, token_mint, realized_pnl_sol, pnl_percentage).await;
+            }
+        }
+        BookedPnl { realized_pnl_sol, pnl_percentage }
+    }
+
+    /// Sell `percentage` of the wallet's current balance of `token_mint` through the position's venue
+    ///
+    /// `slippage` overrides the configured slippage, `None` keeps it.
+    pub async fn execute_sell(&self, token_mint: &str, percentage: f64, slippage: Option<u64>) -> Result<String> {
+        let mint = token_mint.parse::<Pubkey>()
+            .map_err(|e| anyhow!("Invalid mint {}: {}", token_mint, e))?;
+        let protocol = BOUGHT_TOKEN_LIST
+            .get(token_mint)
+            .map(|info| info.protocol.clone())
+            .unwrap_or_else(|| self.app_state.protocol_preference.clone());
+
+        let balance = if paper::is_paper_mode() {
+            paper::token_balance(token_mint)
+        } else {
+            let wallet = self.app_state.wallet.try_pubkey()
+                .map_err(|e| anyhow!("Failed to get wallet pubkey: {}", e))?;
+            let token_account = get_associated_token_address(&wallet, &mint);
+            self.app_state.rpc_nonblocking_client
+                .get_token_account_balance(&token_account)
+                .await
+                .map_err(|e| anyhow!("Failed to get token balance for {}: {}", token_mint, e))?
+                .amount
+                .parse::<u64>()
+                .map_err(|e| anyhow!("Invalid token balance for {}: {}", token_mint, e))?
+        };
+
+        let amount = if percentage >= 100.0 {
+            balance
+        } else {
+            (balance as f64 * percentage / 100.0) as u64
+        };
+        if amount == 0 {
+            return Err(anyhow!("Nothing to sell for {} (balance {})", token_mint, balance));
+        }
+
+        self.logger.log(format!(
+            "Selling {} / {} tokens of {} ({:.2}%) via {:?}",
+            amount, balance, token_mint, percentage, protocol
+        ));
+
+        let signature = execute_swap(&protocol, SwapDirection::Sell, &mint, amount, slippage.unwrap_or(self.swap_config.slippage))
+            .await
+            .map_err(|e| anyhow!("Sell failed for {}: {}", token_mint, e))?;
+
+        self.logger.log(format!("Sold {} of {}: {}", amount, token_mint, signature).green().to_string());
+        Ok(signature)
     }
 }
 
@@ -81,6 +1038,11 @@ pub struct BoughtTokenInfo {
     pub reached_20_percent: bool,      // Whether 20% PnL was reached
     pub sold_percentages: HashMap<String, f64>, // Track sold amounts per threshold
     pub remaining_amount: f64,         // Remaining amount to sell (starts at 100%)
+    pub sizing_reason: String,         // Why entry_amount was chosen
+    pub realized_pnl_sol: f64,         // PnL of the shares sold so far
+    pub exit_price: f64,               // Measured price of the last sell, 0 before any
+    pub entry_slippage_percentage: f64, // Measured entry vs quoted price, positive when paid more
+    pub exit_slippage_percentage: f64, // Measured last exit vs quoted price, positive when received less
 }
 
 impl BoughtTokenInfo {
@@ -99,10 +1061,25 @@ impl BoughtTokenInfo {
     }
 
     pub fn should_sell_all_time_based(&self) -> bool {
+        if self.selling_time == 0 {
+            return false;
+        }
         let elapsed = self.entry_time.elapsed().as_secs();
         elapsed >= self.selling_time && !self.reached_20_percent
     }
-}
 
-// Placeholder: Comprehensive selling functionality was removed for public purposes
-// Add actual comprehensive selling logic here when needed
\ No newline at end of file
+    /// Record a newly observed price, tracking the high and the low since that high
+    pub fn update_price(&mut self, price: f64) {
+        if self.entry_price <= 0.0 {
+            // Entry price was unknown at buy time, the first observed price becomes the reference
+            self.entry_price = price;
+        }
+        self.current_price = price;
+        if price > self.highest_price {
+            self.highest_price = price;
+            self.lowest_price_after_highest = price;
+        } else if price < self.lowest_price_after_highest {
+            self.lowest_price_after_highest = price;
+        }
+    }
+}
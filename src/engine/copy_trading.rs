@@ -1,42 +1,176 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::Instant;
-use crate::library::config::import_env_var;
-use anyhow::Result;
-use anchor_client::solana_sdk::{pubkey::Pubkey, signature::Signature};
-use solana_sdk::signature::Signer;
-use solana_sdk::signer::keypair::Keypair;
-use solana_sdk::instruction::Instruction;
-use spl_associated_token_account::get_associated_token_address;
+use anyhow::{anyhow, Result};
+use anchor_client::solana_sdk::pubkey::Pubkey;
 use colored::Colorize;
+use serde::Deserialize;
 use tokio::time;
 use tokio::time::sleep;
 use futures_util::stream::StreamExt;
-use futures_util::{SinkExt, Sink};
+use futures_util::SinkExt;
 use yellowstone_grpc_client::{ClientTlsConfig, GeyserGrpcClient};
 use yellowstone_grpc_proto::geyser::{
     subscribe_update::UpdateOneof, CommitmentLevel, SubscribeRequest, SubscribeRequestPing,
-    SubscribeRequestFilterTransactions,  SubscribeUpdate,
+    SubscribeRequestFilterTransactions, SubscribeUpdateTransaction,
 };
-use solana_transaction_status::TransactionConfirmationStatus;
-use crate::engine::transaction_parser;
+use crate::engine::transaction_parser::{self, TradeInfoFromToken};
 use crate::library::{
-    config::{AppState, SwapConfig, JUPITER_PROGRAM, OKX_DEX_PROGRAM, TransactionLandingMode},
+    config::{AppState, SwapConfig, TransactionLandingMode},
     logger::Logger,
-    cache::WALLET_TOKEN_ACCOUNTS,
 };
 use crate::engine::monitor::TokenTrackingInfo;
-use crate::engine::swap::{SwapDirection, SwapProtocol};
-use crate::engine::comprehensive_selling::ComprehensiveSelling;
-
-use crate::utilities::wallet_monitoring_grpc;
+use crate::engine::swap::{execute_swap, SwapDirection, SwapProtocol};
+use crate::engine::backtest;
+use crate::engine::circuit_breaker;
+use crate::engine::comprehensive_selling::{self, ComprehensiveSelling};
+use crate::engine::creator_registry::{self, CreatorVerdict};
+use crate::engine::dump_detector;
+use crate::engine::journal;
+use crate::engine::migration;
+use crate::engine::risk_management;
+use crate::engine::rug_check::{self, RugCheckConfig};
+use crate::engine::sniper;
+use crate::engine::position_sizing::{get_wallet_balance_sol, PositionSizingConfig, SizingDecision};
+use crate::engine::target_profiles::{self, CopyBlock, TargetProfile};
+use crate::engine::target_scoring;
+use crate::utilities::telegram;
 use tokio_util::sync::CancellationToken;
 use dashmap::DashMap;
 
+/// How a target's sells are mirrored on a position we hold in the same mint
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MirrorSellMode {
+    /// Ignore the target's sells and leave exits to the selling strategy
+    Off,
+    /// Sell the same percentage of our position as the target sold of theirs
+    #[default]
+    Proportional,
+    /// Exit the whole position on the target's first sell
+    #[serde(alias = "full")]
+    FullExit,
+}
+
+impl FromStr for MirrorSellMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "off" | "false" => Ok(MirrorSellMode::Off),
+            "proportional" | "true" => Ok(MirrorSellMode::Proportional),
+            "full" | "full_exit" => Ok(MirrorSellMode::FullExit),
+            _ => Err(format!("Invalid mirror sell mode: {}", s)),
+        }
+    }
+}
+
+/// Require K of the tracked targets to buy a mint before we copy it
+#[derive(Debug, Clone)]
+pub struct ConsensusConfig {
+    pub min_targets: usize,        // K, 1 = copy every buy
+    pub window_secs: u64,          // the K buys must fall within this window
+    pub scale_percentage: f64,     // extra size per agreeing target beyond the first, 0 = no scaling
+    pub max_multiplier: f64,
+}
+
+impl Default for ConsensusConfig {
+    fn default() -> Self {
+        Self {
+            min_targets: 1,
+            window_secs: 300,
+            scale_percentage: 0.0,
+            max_multiplier: 3.0,
+        }
+    }
+}
+
+impl ConsensusConfig {
+    /// Load from `CONSENSUS_MIN_TARGETS`, `CONSENSUS_WINDOW_SECS`, `CONSENSUS_SCALE_PERCENTAGE`
+    /// and `CONSENSUS_MAX_MULTIPLIER`
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        Self {
+            min_targets: std::env::var("CONSENSUS_MIN_TARGETS")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(default.min_targets)
+                .max(1),
+            window_secs: std::env::var("CONSENSUS_WINDOW_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(default.window_secs),
+            scale_percentage: std::env::var("CONSENSUS_SCALE_PERCENTAGE")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(default.scale_percentage),
+            max_multiplier: std::env::var("CONSENSUS_MAX_MULTIPLIER")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(default.max_multiplier)
+                .max(1.0),
+        }
+    }
+
+    /// Size multiplier for `agreeing` targets
+    pub fn multiplier(&self, agreeing: usize) -> f64 {
+        let extra = agreeing.saturating_sub(1) as f64;
+        (1.0 + extra * self.scale_percentage / 100.0).min(self.max_multiplier)
+    }
+}
+
+/// Targets that bought a mint recently, and whether we already copied it
+#[derive(Debug, Clone, Default)]
+pub struct TargetBuyRecord {
+    pub targets: HashMap<String, i64>, // unix milliseconds of each target's latest buy
+    pub copied: bool, // a copy buy is in flight or done
+}
+
+/// Outcome of registering a target's buy of a mint
+#[derive(Debug, PartialEq)]
+pub enum BuyClaim {
+    /// Copy now, with the number of agreeing targets
+    Copy(usize),
+    /// Not enough targets agree yet
+    Waiting(usize),
+    AlreadyCopied,
+}
+
+impl TargetBuyRecord {
+    /// Record `target` buying at `now_ms` and claim the copy once enough targets agree within the window
+    pub fn claim(&mut self, target: &str, now_ms: i64, consensus: &ConsensusConfig) -> BuyClaim {
+        let window_ms = consensus.window_secs as i64 * 1000;
+        self.targets.insert(target.to_string(), now_ms);
+        self.targets.retain(|_, bought_at| now_ms - *bought_at <= window_ms);
+
+        if self.copied {
+            return BuyClaim::AlreadyCopied;
+        }
+        let agreeing = self.targets.len();
+        if agreeing < consensus.min_targets {
+            return BuyClaim::Waiting(agreeing);
+        }
+        self.copied = true;
+        BuyClaim::Copy(agreeing)
+    }
+}
+
+/// Record `target` buying `mint` and claim the copy once enough targets agree
+fn register_target_buy(mint: &str, target: &str, consensus: &ConsensusConfig) -> BuyClaim {
+    let mut record = TARGET_BUY_TOKENS.entry(mint.to_string()).or_default();
+    record.claim(target, chrono::Utc::now().timestamp_millis(), consensus)
+}
+
+/// Give up the claim on a mint after a failed copy so a later buy can retry
+fn release_target_buy(mint: &str) {
+    if let Some(mut record) = TARGET_BUY_TOKENS.get_mut(mint) {
+        record.copied = false;
+    }
+}
+
 /// Configuration struct for copy trading functionality
-/// This was removed for public purposes
 #[derive(Clone)]
 pub struct CopyTradingConfig {
     pub yellowstone_grpc_http: String,
@@ -53,36 +187,501 @@ pub struct CopyTradingConfig {
     pub transaction_landing_mode: TransactionLandingMode,
     pub max_dev_buy: f64,
     pub min_dev_buy: f64,
+    pub target_profiles: Vec<TargetProfile>,
+    pub position_sizing: PositionSizingConfig,
+    pub consensus: ConsensusConfig,
+    pub rug_check: RugCheckConfig,
+}
+
+impl CopyTradingConfig {
+    /// Mirror sell mode for a target, targets without a profile are not mirrored
+    pub fn mirror_sell_mode(&self, target: &str) -> MirrorSellMode {
+        target_profiles::get_target_profile(target)
+            .map(|profile| profile.mirror_sell_mode())
+            .unwrap_or(MirrorSellMode::Off)
+    }
 }
 
-/// Placeholder function for starting copy trading
-/// This was removed for public purposes
+/// Start copy trading: stream the targets' transactions over Yellowstone gRPC and follow their trades
 pub async fn start_copy_trading(config: CopyTradingConfig) -> Result<()> {
     let logger = Logger::new("[COPY-TRADING] => ".green().to_string());
-    
+
     logger.log(format!("Monitoring {} target addresses", config.target_addresses.len()));
     logger.log(format!("Protocol preference: {:?}", config.protocol_preference));
-    logger.log(format!("Buy amount: {} SOL", config.buy_in_sell));
-    
+    logger.log(format!(
+        "Buy sizing: {:?} | Amount: {} ({:?}) | Clamp: {} - {} SOL",
+        config.position_sizing.mode,
+        config.swap_config.amount_in,
+        config.swap_config.in_type,
+        config.position_sizing.min_amount_sol,
+        config.position_sizing.max_amount_sol
+    ));
+    if config.consensus.min_targets > 1 {
+        logger.log(format!(
+            "Consensus: {} targets within {}s | +{}% size per extra target, max x{}",
+            config.consensus.min_targets,
+            config.consensus.window_secs,
+            config.consensus.scale_percentage,
+            config.consensus.max_multiplier
+        ));
+    }
+
     // Initialize global state
     init_global_state();
-    
-    // Placeholder: Add actual copy trading logic here when needed
-    // This would typically:
-    // 1. Connect to Yellowstone gRPC for transaction monitoring
-    // 2. Subscribe to target wallet addresses
-    // 3. Parse incoming transactions
-    // 4. Execute copy trades based on configuration
-    // 5. Monitor positions and execute selling strategy
-    
+    target_profiles::register_target_profiles(&config.target_profiles);
+    creator_registry::init_creator_registry();
+    if !sniper::is_sniper_enabled() {
+        // Launches and migrations are recorded from the sniper's launch stream
+        logger.log("Sniper off, creator checks only use the saved creator history".yellow().to_string());
+    }
+    risk_management::init_risk_engine(config.app_state.clone());
+    circuit_breaker::start_circuit_breaker(config.app_state.clone());
+    if let Err(e) = target_scoring::init_target_scoring() {
+        logger.log(format!("Target stats not loaded: {}", e).red().to_string());
+    }
+    target_scoring::start_stats_flusher();
+
+    // Periodic target performance report
+    let report_interval = target_scoring::SCORING_CONFIG.report_interval_secs;
+    if report_interval > 0 {
+        let logger = logger.clone();
+        tokio::spawn(async move {
+            let mut report_interval = time::interval(Duration::from_secs(report_interval));
+            report_interval.tick().await;
+            loop {
+                report_interval.tick().await;
+                let lines = target_scoring::format_target_report(&target_scoring::get_target_stats());
+                if let Err(e) = telegram::send_target_report(&lines).await {
+                    logger.log(format!("Failed to send target report: {}", e).red().to_string());
+                }
+            }
+        });
+    }
+
+    // Cleanup old entries periodically
+    tokio::spawn(async {
+        let mut cleanup_interval = time::interval(Duration::from_secs(60));
+        loop {
+            cleanup_interval.tick().await;
+            cleanup_target_buy_tokens();
+        }
+    });
+
+    let selling = Arc::new(ComprehensiveSelling::new(config.app_state.clone(), config.swap_config.clone()));
+    selling.start_stop_loss_watchdog();
+    match selling.restore_positions().await {
+        Ok(restored) if restored > 0 => {
+            logger.log(format!("Restored {} open positions", restored).green().to_string());
+        }
+        Ok(_) => {}
+        Err(e) => {
+            logger.log(format!("Position book not restored: {}", e).red().to_string());
+        }
+    }
+    dump_detector::start_dump_detector(
+        config.yellowstone_grpc_http.clone(),
+        config.yellowstone_grpc_token.clone(),
+        selling.clone(),
+    );
+    migration::start_migration_watcher(
+        config.yellowstone_grpc_http.clone(),
+        config.yellowstone_grpc_token.clone(),
+        selling.clone(),
+    );
+    let config = Arc::new(config);
+
+    loop {
+        match run_transaction_stream(config.clone(), selling.clone(), &logger).await {
+            Ok(_) => logger.log("gRPC stream closed, reconnecting...".yellow().to_string()),
+            Err(e) => logger.log(format!("gRPC stream error: {}, reconnecting...", e).red().to_string()),
+        };
+        sleep(Duration::from_secs(2)).await;
+    }
+}
+
+/// Subscribe to the targets (and the mints we hold, for pricing) and dispatch every transaction
+async fn run_transaction_stream(
+    config: Arc<CopyTradingConfig>,
+    selling: Arc<ComprehensiveSelling>,
+    logger: &Logger,
+) -> Result<()> {
+    let mut client = GeyserGrpcClient::build_from_shared(config.yellowstone_grpc_http.clone())?
+        .x_token(Some(config.yellowstone_grpc_token.clone()))?
+        .tls_config(ClientTlsConfig::new().with_native_roots())?
+        .connect()
+        .await?;
+    let (mut subscribe_tx, mut stream) = client.subscribe().await?;
+
+    let mut held_mints = held_token_mints();
+    subscribe_tx.send(build_subscribe_request(&config, &held_mints)).await?;
+    logger.log("Subscribed to target transactions".green().to_string());
+
+    let mut refresh_interval = time::interval(Duration::from_secs(5));
+
     loop {
-        // Cleanup old entries periodically
-        cleanup_target_buy_tokens();
-        
-        // Placeholder sleep - in real implementation this would be event-driven
-        tokio::time::sleep(Duration::from_secs(60)).await;
-        
-        logger.log("Copy trading monitoring...  ".to_string());
+        tokio::select! {
+            message = stream.next() => {
+                let update = match message {
+                    Some(update) => update.map_err(|e| anyhow!("{}", e))?,
+                    None => return Ok(()),
+                };
+                circuit_breaker::record_stream_activity();
+                backtest::record_update(&update);
+                match update.update_oneof {
+                    Some(UpdateOneof::Transaction(txn)) => {
+                        let config = config.clone();
+                        let selling = selling.clone();
+                        tokio::spawn(async move {
+                            process_transaction(config, selling, txn).await;
+                        });
+                    }
+                    Some(UpdateOneof::Ping(_)) => {
+                        subscribe_tx.send(SubscribeRequest {
+                            ping: Some(SubscribeRequestPing { id: 1 }),
+                            ..Default::default()
+                        }).await?;
+                    }
+                    _ => {}
+                }
+            }
+            _ = refresh_interval.tick() => {
+                // Positions opened or closed: resubscribe so their mints keep being priced
+                let mints = held_token_mints();
+                if mints != held_mints {
+                    held_mints = mints;
+                    subscribe_tx.send(build_subscribe_request(&config, &held_mints)).await?;
+                    logger.log(format!("Subscription updated, pricing {} held tokens", held_mints.len()));
+                }
+            }
+        }
+    }
+}
+
+fn held_token_mints() -> Vec<String> {
+    let mut mints = comprehensive_selling::get_bought_token_mints();
+    mints.sort();
+    mints
+}
+
+fn build_subscribe_request(config: &CopyTradingConfig, held_mints: &[String]) -> SubscribeRequest {
+    let mut transactions = HashMap::new();
+    transactions.insert("targets".to_string(), SubscribeRequestFilterTransactions {
+        vote: Some(false),
+        failed: Some(false),
+        signature: None,
+        account_include: config.target_addresses.clone(),
+        account_exclude: config.excluded_addresses.clone(),
+        account_required: Vec::new(),
+    });
+    if !held_mints.is_empty() {
+        transactions.insert("positions".to_string(), SubscribeRequestFilterTransactions {
+            vote: Some(false),
+            failed: Some(false),
+            signature: None,
+            account_include: held_mints.to_vec(),
+            account_exclude: Vec::new(),
+            account_required: Vec::new(),
+        });
+    }
+
+    SubscribeRequest {
+        transactions,
+        commitment: Some(CommitmentLevel::Processed as i32),
+        ..Default::default()
+    }
+}
+
+/// Parse a streamed transaction, update prices of held tokens and follow target trades
+async fn process_transaction(
+    config: Arc<CopyTradingConfig>,
+    selling: Arc<ComprehensiveSelling>,
+    txn: SubscribeUpdateTransaction,
+) {
+    let logger = Logger::new("[COPY-TRADING] => ".green().to_string());
+
+    let mut trade_info = match transaction_parser::parse_transaction_data(&txn) {
+        Some(trade_info) => trade_info,
+        None => return,
+    };
+
+    if let Some(price) = trade_info.price_in_sol() {
+        comprehensive_selling::update_token_price(&trade_info.mint, price);
+    }
+    creator_registry::record_trade(&trade_info);
+    risk_management::record_market_trade(&trade_info);
+
+    if !config.target_addresses.contains(&trade_info.user) {
+        return;
+    }
+    trade_info.target = trade_info.user.clone();
+    telegram::record_target_transaction(&trade_info).await;
+    target_scoring::record_target_trade(&trade_info);
+    journal::record_detected(&trade_info);
+
+    logger.log(format!(
+        "Target {} ({}) {} {} on {:?} | {:.4} SOL | tx: {}",
+        target_profiles::target_label(&trade_info.target),
+        trade_info.target,
+        if trade_info.is_buy { "bought" } else { "sold" },
+        trade_info.mint,
+        trade_info.dex_type,
+        trade_info.sol_amount.unwrap_or(0) as f64 / 1_000_000_000.0,
+        trade_info.signature
+    ).cyan().to_string());
+
+    let (mint, target, is_buy) = (trade_info.mint.clone(), trade_info.target.clone(), trade_info.is_buy);
+    let result = if is_buy {
+        handle_target_buy(&config, &selling, trade_info).await
+    } else {
+        handle_target_sell(&config, &selling, trade_info).await
+    };
+
+    if let Err(e) = result {
+        logger.log(format!("{}", e).red().to_string());
+        if is_buy {
+            journal::record_decision(&mint, &target, false, &e.to_string());
+        }
+    }
+}
+
+/// Copy a target's buy
+async fn handle_target_buy(
+    config: &CopyTradingConfig,
+    selling: &ComprehensiveSelling,
+    trade_info: TradeInfoFromToken,
+) -> Result<()> {
+    let logger = Logger::new("[COPY-BUY] => ".green().to_string());
+    let mint = trade_info.mint.clone();
+    let profile = target_profiles::get_target_profile(&trade_info.target)
+        .unwrap_or_else(|| TargetProfile::with_defaults(&trade_info.target));
+    let label = profile.display_name();
+
+    if !is_buying_enabled() {
+        skip_buy(&logger, &trade_info, format!("Buying disabled, skipping {}", mint));
+        return Ok(());
+    }
+    if !profile.enabled {
+        skip_buy(&logger, &trade_info, format!("[{}] Target disabled, skipping {}", label, mint));
+        return Ok(());
+    }
+    let protocol = resolve_protocol(&config.protocol_preference, &trade_info);
+    if !profile.allows_venue(&protocol) {
+        skip_buy(&logger, &trade_info, format!("[{}] Venue {:?} not allowed, skipping {}", label, protocol, mint));
+        return Ok(());
+    }
+    let open_positions = comprehensive_selling::count_positions_for_target(&profile.address);
+    let mut reservation = match target_profiles::reserve_copy(&profile, open_positions) {
+        Ok(reservation) => reservation,
+        Err(CopyBlock::Cooldown(remaining)) => {
+            skip_buy(&logger, &trade_info, format!("[{}] Cooldown active for {}s, skipping {}", label, remaining, mint));
+            return Ok(());
+        }
+        Err(CopyBlock::MaxOpenPositions(max)) => {
+            skip_buy(&logger, &trade_info, format!("[{}] Max open positions {} reached, skipping {}", label, max, mint));
+            return Ok(());
+        }
+    };
+    if comprehensive_selling::get_bought_token(&mint).is_some() {
+        skip_buy(&logger, &trade_info, format!("Already holding {}, skipping", mint));
+        return Ok(());
+    }
+    let creator_verdict = trade_info.coin_creator
+        .as_deref()
+        .map(creator_registry::evaluate_creator)
+        .unwrap_or(CreatorVerdict::Unknown);
+    if let CreatorVerdict::Blocked(reason) = &creator_verdict {
+        skip_buy(&logger, &trade_info, format!("[{}] Creator of {} blocked: {}", label, mint, reason));
+        return Ok(());
+    }
+    if config.counter_limit > 0
+        && comprehensive_selling::get_bought_token_mints().len() as u64 >= config.counter_limit
+    {
+        skip_buy(&logger, &trade_info, format!("Open position limit {} reached, skipping {}", config.counter_limit, mint));
+        return Ok(());
+    }
+    let agreeing = match register_target_buy(&mint, &profile.address, &config.consensus) {
+        BuyClaim::Copy(agreeing) => agreeing,
+        BuyClaim::Waiting(agreeing) => {
+            skip_buy(&logger, &trade_info, format!(
+                "[{}] {} bought by {}/{} targets, waiting for consensus",
+                label, mint, agreeing, config.consensus.min_targets
+            ));
+            return Ok(());
+        }
+        BuyClaim::AlreadyCopied => {
+            skip_buy(&logger, &trade_info, format!("Buy of {} already in flight, skipping", mint));
+            return Ok(());
+        }
+    };
+    if config.rug_check.enabled {
+        match rug_check::check_mint(&config.app_state, &config.rug_check, &trade_info).await {
+            Ok(verdict) if !risk_management::veto_rug_check(&verdict) => {
+                logger.log(format!("[{}] Rug check {}: {}", label, mint, verdict.summary()));
+            }
+            Ok(verdict) => {
+                release_target_buy(&mint);
+                skip_buy(&logger, &trade_info, format!("[{}] Rug check failed for {}: {}", label, mint, verdict.summary()));
+                return Ok(());
+            }
+            Err(e) => {
+                release_target_buy(&mint);
+                return Err(anyhow!("Rug check of {} failed: {}", mint, e));
+            }
+        }
+    }
+    let mut exposure = match risk_management::approve_new_position(&mint, Some(&trade_info.target), &protocol) {
+        Ok(exposure) => exposure,
+        Err(status) => {
+            release_target_buy(&mint);
+            skip_buy(&logger, &trade_info, format!("[{}] Risk engine blocked {}: {}", label, mint, status));
+            return Ok(());
+        }
+    };
+
+    let target_sol = trade_info.sol_amount.unwrap_or(0) as f64 / 1_000_000_000.0;
+    let mut sizing = match profile.buy_amount {
+        Some(amount_sol) => SizingDecision {
+            amount_sol,
+            reason: format!("profile {} fixed {} SOL", label, amount_sol),
+        },
+        None => {
+            let wallet_balance = if config.position_sizing.needs_wallet_balance(&config.swap_config) {
+                match get_wallet_balance_sol(&config.app_state).await {
+                    Ok(balance) => Some(balance),
+                    Err(e) => {
+                        release_target_buy(&mint);
+                        return Err(e);
+                    }
+                }
+            } else {
+                None
+            };
+            match config.position_sizing.calculate(&config.swap_config, target_sol, wallet_balance) {
+                Ok(sizing) => sizing,
+                Err(e) => {
+                    release_target_buy(&mint);
+                    return Err(anyhow!("Sizing failed for {}: {}", mint, e));
+                }
+            }
+        }
+    };
+    config.position_sizing.adjust(
+        &mut sizing,
+        &config.consensus,
+        agreeing,
+        &creator_verdict,
+        creator_registry::CREATOR_CONFIG.preferred_multiplier,
+        exposure.max_size(),
+    );
+    if sizing.amount_sol <= 0.0 {
+        release_target_buy(&mint);
+        skip_buy(&logger, &trade_info, format!("Skipping {}: {}", mint, sizing.reason));
+        return Ok(());
+    }
+    exposure.resize(sizing.amount_sol);
+    logger.log(format!("[{}] Sizing {} at {:.4} SOL: {}", label, mint, sizing.amount_sol, sizing.reason));
+    journal::record_decision(&mint, &trade_info.target, true, &format!("{:.4} SOL: {}", sizing.amount_sol, sizing.reason));
+
+    let mint_pubkey = Pubkey::from_str(&mint).map_err(|e| anyhow!("Invalid mint {}: {}", mint, e))?;
+    let amount = (sizing.amount_sol * 1_000_000_000.0) as u64;
+    let slippage = profile.slippage.unwrap_or(config.swap_config.slippage);
+
+    let signature = match execute_swap(&protocol, SwapDirection::Buy, &mint_pubkey, amount, slippage).await {
+        Ok(signature) => signature,
+        Err(e) => {
+            release_target_buy(&mint);
+            return Err(anyhow!("Copy buy of {} failed: {}", mint, e));
+        }
+    };
+
+    logger.log(format!("[{}] Copied buy of {} for {:.4} SOL: {}", label, mint, sizing.amount_sol, signature).green().to_string());
+    reservation.commit();
+    risk_management::record_new_position();
+    increment_counter(&BOUGHT_TOKENS);
+    LAST_BUY_TIME.insert((), Some(Instant::now()));
+
+    if let Err(e) = telegram::send_copy_trade_notification(&trade_info, &signature, &format!("{:?}", protocol), "COPIED").await {
+        logger.log(format!("Failed to send Telegram notification: {}", e).yellow().to_string());
+    }
+
+    let mut position_trade = trade_info;
+    position_trade.dex_type = match protocol {
+        SwapProtocol::PumpSwap => transaction_parser::DexType::PumpSwap,
+        SwapProtocol::PumpFun => transaction_parser::DexType::PumpFun,
+        _ => position_trade.dex_type,
+    };
+    let monitoring = selling.start_monitoring(&mint, &signature, position_trade, sizing).await;
+    // The position now carries the exposure
+    drop(exposure);
+    monitoring
+}
+
+/// Log and journal why a target's buy is not copied
+fn skip_buy(logger: &Logger, trade_info: &TradeInfoFromToken, reason: String) {
+    logger.log(reason.yellow().to_string());
+    journal::record_decision(&trade_info.mint, &trade_info.target, false, &reason);
+}
+
+/// Mirror a target's sell on our position in the same mint
+async fn handle_target_sell(
+    config: &CopyTradingConfig,
+    selling: &ComprehensiveSelling,
+    trade_info: TradeInfoFromToken,
+) -> Result<()> {
+    let logger = Logger::new("[MIRROR-SELL] => ".yellow().to_string());
+    let mint = trade_info.mint.clone();
+
+    let mode = config.mirror_sell_mode(&trade_info.target);
+    if mode == MirrorSellMode::Off {
+        return Ok(());
+    }
+    // Only the position copied from this target follows its sells, not snipes or other targets' copies
+    let copied_from_target = comprehensive_selling::get_bought_token(&mint)
+        .is_some_and(|position| position.trade_info.target == trade_info.target);
+    if !copied_from_target {
+        return Ok(());
+    }
+
+    let percentage = match mode {
+        MirrorSellMode::FullExit => 100.0,
+        _ => target_sell_percentage(&trade_info)
+            .ok_or_else(|| anyhow!("Cannot derive sell percentage for {} from {}", mint, trade_info.signature))?,
+    };
+
+    logger.log(format!(
+        "Target {} sold {:.2}% of {} ({:?}), mirroring",
+        target_profiles::target_label(&trade_info.target), percentage, mint, mode
+    ).yellow().to_string());
+
+    let slippage = target_profiles::get_target_profile(&trade_info.target).and_then(|profile| profile.slippage);
+    let signature = selling
+        .sell_holdings_share(&mint, percentage, format!("mirror_{}", trade_info.signature), slippage)
+        .await?;
+
+    if comprehensive_selling::get_bought_token(&mint).is_none() {
+        increment_counter(&SOLD_TOKENS);
+    }
+    logger.log(format!("Mirrored sell of {:.2}% of {}: {}", percentage, mint, signature).green().to_string());
+    Ok(())
+}
+
+/// Percentage of their position the trader sold, from their pre-trade token balance
+pub fn target_sell_percentage(trade_info: &TradeInfoFromToken) -> Option<f64> {
+    let pre = trade_info.user_pre_token_balance?;
+    if pre == 0 {
+        return None;
+    }
+    let sold = match trade_info.user_post_token_balance {
+        Some(post) => pre.saturating_sub(post),
+        None => trade_info.token_amount?,
+    };
+    Some((sold as f64 / pre as f64 * 100.0).min(100.0))
+}
+
+fn resolve_protocol(preference: &SwapProtocol, trade_info: &TradeInfoFromToken) -> SwapProtocol {
+    match preference {
+        SwapProtocol::Auto | SwapProtocol::Unknown => SwapProtocol::from(&trade_info.dex_type),
+        preference => preference.clone(),
     }
 }
 
@@ -93,12 +692,12 @@ lazy_static::lazy_static! {
     static ref BOUGHT_TOKENS: Arc<DashMap<(), u64>> = Arc::new(DashMap::new());
     static ref LAST_BUY_TIME: Arc<DashMap<(), Option<Instant>>> = Arc::new(DashMap::new());
     static ref BUYING_ENABLED: Arc<DashMap<(), bool>> = Arc::new(DashMap::new());
-    static ref TOKEN_TRACKING: Arc<DashMap<String, TokenTrackingInfo>> = Arc::new(DashMap::new());
+    pub(crate) static ref TOKEN_TRACKING: Arc<DashMap<String, TokenTrackingInfo>> = Arc::new(DashMap::new());
     // Global registry for monitoring task cancellation tokens
     static ref MONITORING_TASKS: Arc<DashMap<String, CancellationToken>> = Arc::new(DashMap::new());
-    // Cache for target buy tokens to prevent duplicate buys
-    static ref TARGET_BUY_TOKENS: Arc<DashMap<String, Instant>> = Arc::new(DashMap::new());
-    
+    // Targets that bought each mint, used for duplicate-buy protection and consensus
+    static ref TARGET_BUY_TOKENS: Arc<DashMap<String, TargetBuyRecord>> = Arc::new(DashMap::new());
+
     // Bought token list for comprehensive selling
     static ref BOUGHT_TOKEN_LIST: Arc<DashMap<String, String>> = Arc::new(DashMap::new());
 }
@@ -112,12 +711,26 @@ fn init_global_state() {
     BUYING_ENABLED.insert((), true);
 }
 
+fn increment_counter(counter: &DashMap<(), u64>) {
+    *counter.entry(()).or_insert(0) += 1;
+}
+
+/// Whether new buys are currently allowed
+pub fn is_buying_enabled() -> bool {
+    BUYING_ENABLED.get(&()).map(|enabled| *enabled).unwrap_or(true)
+}
+
+/// Allow or halt every new buy, used by the circuit breaker
+pub fn set_buying_enabled(enabled: bool) {
+    BUYING_ENABLED.insert((), enabled);
+}
+
 // Clean up old target buy tokens to prevent unbounded growth
 fn cleanup_target_buy_tokens() {
-    let now = Instant::now();
-    let timeout = Duration::from_secs(3600); // 1 hour timeout
-    
-    TARGET_BUY_TOKENS.retain(|_, &mut timestamp| {
-        now.duration_since(timestamp) < timeout
+    let now = chrono::Utc::now().timestamp_millis();
+    let timeout_ms = 3_600_000; // 1 hour timeout
+
+    TARGET_BUY_TOKENS.retain(|_, record| {
+        record.targets.values().any(|timestamp| now - timestamp < timeout_ms)
     });
 }
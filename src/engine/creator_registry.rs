@@ -0,0 +1,369 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::transaction_parser::{LaunchInfo, TradeInfoFromToken};
+use crate::library::logger::Logger;
+
+// PumpFun and Launchpad tokens launch with a fixed 1B supply
+const TOKEN_SUPPLY: f64 = 1_000_000_000.0;
+
+// Creator history by wallet, and the launches still being followed by mint
+lazy_static! {
+    static ref CREATORS: Arc<DashMap<String, CreatorRecord>> = Arc::new(DashMap::new());
+    static ref LAUNCHES: Arc<DashMap<String, LaunchRecord>> = Arc::new(DashMap::new());
+    static ref REGISTRY_STARTED: AtomicBool = AtomicBool::new(false);
+    pub static ref CREATOR_CONFIG: CreatorFilterConfig = CreatorFilterConfig::from_env();
+}
+
+/// Aggregated launch history of a creator wallet
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreatorRecord {
+    pub creator: String,
+    pub tokens_launched: u64,
+    pub migrated: u64,
+    pub dev_sells: u64,
+    pub dev_sell_secs_total: u64,
+    pub finished_launches: u64, // launches whose peak market cap is final
+    pub peak_mcap_sol_total: f64,
+}
+
+impl CreatorRecord {
+    pub fn migration_rate(&self) -> f64 {
+        if self.tokens_launched == 0 {
+            return 0.0;
+        }
+        self.migrated as f64 / self.tokens_launched as f64 * 100.0
+    }
+
+    /// Average seconds between launch and the creator's first sell
+    pub fn avg_dev_sell_secs(&self) -> Option<f64> {
+        if self.dev_sells == 0 {
+            return None;
+        }
+        Some(self.dev_sell_secs_total as f64 / self.dev_sells as f64)
+    }
+
+    pub fn avg_peak_mcap_sol(&self) -> Option<f64> {
+        if self.finished_launches == 0 {
+            return None;
+        }
+        Some(self.peak_mcap_sol_total / self.finished_launches as f64)
+    }
+}
+
+/// A launch being followed until its peak market cap is settled
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LaunchRecord {
+    pub mint: String,
+    pub creator: String,
+    pub launched_at: i64,
+    pub peak_mcap_sol: f64,
+    pub migrated: bool,
+    pub dev_sold: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RegistryFile {
+    creators: Vec<CreatorRecord>,
+    launches: Vec<LaunchRecord>,
+}
+
+/// How a creator's history affects buying their tokens
+#[derive(Debug, Clone, PartialEq)]
+pub enum CreatorVerdict {
+    /// Not enough history to judge
+    Unknown,
+    Neutral,
+    Preferred(String),
+    Blocked(String),
+}
+
+/// Thresholds for judging creators
+#[derive(Debug, Clone)]
+pub struct CreatorFilterConfig {
+    pub db_file: String,
+    pub min_launches: u64,             // launches needed before a creator is judged
+    pub min_migration_rate: f64,       // block below this migration rate (%), 0 = off
+    pub min_dev_hold_secs: u64,        // block when the dev bag is dumped faster on average, 0 = off
+    pub preferred_migration_rate: f64, // prefer at or above this migration rate (%), 0 = off
+    pub preferred_multiplier: f64,     // buy size multiplier for preferred creators
+    pub track_secs: u64,               // how long a launch's market cap is followed
+}
+
+impl CreatorFilterConfig {
+    /// Load from `CREATOR_DB_FILE`, `CREATOR_MIN_LAUNCHES`, `CREATOR_MIN_MIGRATION_RATE`,
+    /// `CREATOR_MIN_DEV_HOLD_SECS`, `CREATOR_PREFERRED_MIGRATION_RATE`,
+    /// `CREATOR_PREFERRED_MULTIPLIER` and `CREATOR_TRACK_SECS`
+    pub fn from_env() -> Self {
+        let f64_var = |name: &str, default: f64| {
+            std::env::var(name).ok().and_then(|v| v.parse::<f64>().ok()).unwrap_or(default)
+        };
+        let u64_var = |name: &str, default: u64| {
+            std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok()).unwrap_or(default)
+        };
+        Self {
+            db_file: std::env::var("CREATOR_DB_FILE").unwrap_or_else(|_| "creators.json".to_string()),
+            min_launches: u64_var("CREATOR_MIN_LAUNCHES", 3),
+            min_migration_rate: f64_var("CREATOR_MIN_MIGRATION_RATE", 0.0),
+            min_dev_hold_secs: u64_var("CREATOR_MIN_DEV_HOLD_SECS", 0),
+            preferred_migration_rate: f64_var("CREATOR_PREFERRED_MIGRATION_RATE", 0.0),
+            preferred_multiplier: f64_var("CREATOR_PREFERRED_MULTIPLIER", 1.0),
+            track_secs: u64_var("CREATOR_TRACK_SECS", 6 * 3600),
+        }
+    }
+}
+
+/// Load the registry from disk and start saving it periodically; later calls are no-ops
+pub fn init_creator_registry() {
+    if REGISTRY_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let logger = Logger::new("[CREATOR-REGISTRY] => ".cyan().to_string());
+    let config = &*CREATOR_CONFIG;
+
+    match read_registry_file(&config.db_file) {
+        Ok(file) => {
+            for creator in file.creators {
+                CREATORS.insert(creator.creator.clone(), creator);
+            }
+            for launch in file.launches {
+                LAUNCHES.insert(launch.mint.clone(), launch);
+            }
+            logger.log(format!("Loaded {} creators, following {} launches", CREATORS.len(), LAUNCHES.len()));
+        }
+        Err(e) => {
+            logger.log(format!("{}", e).red().to_string());
+        }
+    }
+
+    tokio::spawn(async move {
+        let mut save_interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            save_interval.tick().await;
+            finish_old_launches();
+            let saved = tokio::task::spawn_blocking(save_registry)
+                .await
+                .map_err(|e| anyhow!("Creator registry save task failed: {}", e))
+                .and_then(|result| result);
+            if let Err(e) = saved {
+                logger.log(format!("{}", e).red().to_string());
+            }
+        }
+    });
+}
+
+fn read_registry_file(path: &str) -> Result<RegistryFile> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| anyhow!("Failed to parse creator registry {}: {}", path, e)),
+        Err(_) => Ok(RegistryFile::default()),
+    }
+}
+
+fn save_registry() -> Result<()> {
+    let file = RegistryFile {
+        creators: CREATORS.iter().map(|entry| entry.value().clone()).collect(),
+        launches: LAUNCHES.iter().map(|entry| entry.value().clone()).collect(),
+    };
+    let path = &CREATOR_CONFIG.db_file;
+    std::fs::write(path, serde_json::to_string(&file)?)
+        .map_err(|e| anyhow!("Failed to write creator registry {}: {}", path, e))
+}
+
+/// Fold launches older than the tracking window into their creator's averages
+fn finish_old_launches() {
+    let cutoff = chrono::Utc::now().timestamp() - CREATOR_CONFIG.track_secs as i64;
+    LAUNCHES.retain(|_, launch| {
+        if launch.launched_at > cutoff {
+            return true;
+        }
+        let mut creator = CREATORS.entry(launch.creator.clone()).or_insert_with(|| CreatorRecord {
+            creator: launch.creator.clone(),
+            ..Default::default()
+        });
+        creator.finished_launches += 1;
+        creator.peak_mcap_sol_total += launch.peak_mcap_sol;
+        false
+    });
+}
+
+/// Record a parsed create transaction
+pub fn record_launch(launch: &LaunchInfo) {
+    if launch.creator.is_empty() || LAUNCHES.contains_key(&launch.mint) {
+        return;
+    }
+    let peak_mcap_sol = launch.dev_buy
+        .as_ref()
+        .and_then(|trade| trade.price_in_sol())
+        .map(|price| price * TOKEN_SUPPLY)
+        .unwrap_or(0.0);
+
+    LAUNCHES.insert(launch.mint.clone(), LaunchRecord {
+        mint: launch.mint.clone(),
+        creator: launch.creator.clone(),
+        launched_at: chrono::Utc::now().timestamp(),
+        peak_mcap_sol,
+        ..Default::default()
+    });
+    let mut creator = CREATORS.entry(launch.creator.clone()).or_insert_with(|| CreatorRecord {
+        creator: launch.creator.clone(),
+        ..Default::default()
+    });
+    creator.tokens_launched += 1;
+}
+
+/// Record a parsed trade on a followed launch: peak market cap and the dev's first sell
+pub fn record_trade(trade: &TradeInfoFromToken) {
+    let Some(mut launch) = LAUNCHES.get_mut(&trade.mint) else { return };
+
+    if let Some(price) = trade.price_in_sol() {
+        launch.peak_mcap_sol = launch.peak_mcap_sol.max(price * TOKEN_SUPPLY);
+    }
+
+    let dev_sell_secs = if !trade.is_buy && trade.user == launch.creator && !launch.dev_sold {
+        launch.dev_sold = true;
+        Some((chrono::Utc::now().timestamp() - launch.launched_at).max(0) as u64)
+    } else {
+        None
+    };
+    let creator_key = launch.creator.clone();
+    drop(launch);
+
+    if let Some(secs) = dev_sell_secs {
+        if let Some(mut creator) = CREATORS.get_mut(&creator_key) {
+            creator.dev_sells += 1;
+            creator.dev_sell_secs_total += secs;
+        }
+    }
+}
+
+/// Record that a followed launch's curve completed, as decoded by the migration watcher
+pub fn record_migration(mint: &str) {
+    let creator_key = {
+        let Some(mut launch) = LAUNCHES.get_mut(mint) else { return };
+        if launch.migrated {
+            return;
+        }
+        launch.migrated = true;
+        launch.creator.clone()
+    };
+    if let Some(mut creator) = CREATORS.get_mut(&creator_key) {
+        creator.migrated += 1;
+    }
+}
+
+/// Creator of a launch still being followed
+pub fn launch_creator(mint: &str) -> Option<String> {
+    LAUNCHES.get(mint).map(|launch| launch.creator.clone())
+}
+
+pub fn get_creator(creator: &str) -> Option<CreatorRecord> {
+    CREATORS.get(creator).map(|entry| entry.value().clone())
+}
+
+/// Judge a creator against the configured thresholds
+pub fn evaluate_creator(creator: &str) -> CreatorVerdict {
+    judge_creator(get_creator(creator).as_ref(), &CREATOR_CONFIG)
+}
+
+/// Judge a creator's history against `config`, `None` when they never launched
+pub fn judge_creator(record: Option<&CreatorRecord>, config: &CreatorFilterConfig) -> CreatorVerdict {
+    let record = match record {
+        Some(record) if record.tokens_launched >= config.min_launches => record,
+        _ => return CreatorVerdict::Unknown,
+    };
+
+    let migration_rate = record.migration_rate();
+    if config.min_migration_rate > 0.0 && migration_rate < config.min_migration_rate {
+        return CreatorVerdict::Blocked(format!(
+            "{} launches, {:.0}% migrated (< {}%)",
+            record.tokens_launched, migration_rate, config.min_migration_rate
+        ));
+    }
+    if let Some(avg_secs) = record.avg_dev_sell_secs() {
+        if config.min_dev_hold_secs > 0 && avg_secs < config.min_dev_hold_secs as f64 {
+            return CreatorVerdict::Blocked(format!(
+                "dev bag sold after {:.0}s on average (< {}s)",
+                avg_secs, config.min_dev_hold_secs
+            ));
+        }
+    }
+    if config.preferred_migration_rate > 0.0 && migration_rate >= config.preferred_migration_rate {
+        return CreatorVerdict::Preferred(format!(
+            "{} launches, {:.0}% migrated, avg peak {:.1} SOL",
+            record.tokens_launched,
+            migration_rate,
+            record.avg_peak_mcap_sol().unwrap_or(0.0)
+        ));
+    }
+    CreatorVerdict::Neutral
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> CreatorFilterConfig {
+        CreatorFilterConfig {
+            db_file: String::new(),
+            min_launches: 3,
+            min_migration_rate: 10.0,
+            min_dev_hold_secs: 60,
+            preferred_migration_rate: 50.0,
+            preferred_multiplier: 2.0,
+            track_secs: 3600,
+        }
+    }
+
+    fn record(tokens_launched: u64, migrated: u64, dev_sells: u64, dev_sell_secs_total: u64) -> CreatorRecord {
+        CreatorRecord {
+            creator: "creator".to_string(),
+            tokens_launched,
+            migrated,
+            dev_sells,
+            dev_sell_secs_total,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn judges_creators_against_the_thresholds() {
+        let config = config();
+        let verdict = |record: Option<&CreatorRecord>| match judge_creator(record, &config) {
+            CreatorVerdict::Unknown => "unknown",
+            CreatorVerdict::Neutral => "neutral",
+            CreatorVerdict::Preferred(_) => "preferred",
+            CreatorVerdict::Blocked(_) => "blocked",
+        };
+
+        assert_eq!(verdict(None), "unknown");
+        // Too few launches to judge, however bad they were
+        assert_eq!(verdict(Some(&record(2, 0, 2, 0))), "unknown");
+        // 1 of 20 migrated is below 10%
+        assert_eq!(verdict(Some(&record(20, 1, 0, 0))), "blocked");
+        // Dev bag sold after 30s on average
+        assert_eq!(verdict(Some(&record(4, 1, 2, 60))), "blocked");
+        assert_eq!(verdict(Some(&record(4, 1, 2, 600))), "neutral");
+        assert_eq!(verdict(Some(&record(4, 2, 0, 0))), "preferred");
+        // Blocking wins over the preferred migration rate
+        assert_eq!(verdict(Some(&record(4, 4, 1, 5))), "blocked");
+    }
+
+    #[test]
+    fn zero_thresholds_turn_the_checks_off() {
+        let config = CreatorFilterConfig {
+            min_migration_rate: 0.0,
+            min_dev_hold_secs: 0,
+            preferred_migration_rate: 0.0,
+            ..config()
+        };
+        assert_eq!(judge_creator(Some(&record(20, 0, 20, 0)), &config), CreatorVerdict::Neutral);
+        assert_eq!(judge_creator(Some(&record(20, 20, 0, 0)), &config), CreatorVerdict::Neutral);
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use colored::Colorize;
+use dashmap::DashMap;
+use futures_util::stream::StreamExt;
+use futures_util::SinkExt;
+use lazy_static::lazy_static;
+use tokio::time::{interval, sleep};
+use yellowstone_grpc_client::{ClientTlsConfig, GeyserGrpcClient};
+use yellowstone_grpc_proto::geyser::{
+    subscribe_update::UpdateOneof, CommitmentLevel, SubscribeRequest, SubscribeRequestFilterTransactions,
+    SubscribeRequestPing,
+};
+
+use crate::engine::comprehensive_selling::{self, ComprehensiveSelling};
+use crate::engine::risk_management;
+use crate::engine::rug_check;
+use crate::engine::transaction_parser::{self, TradeInfoFromToken};
+use crate::library::{config::AppState, logger::Logger};
+use crate::tx_processor::token;
+
+// Held tokens being watched for dev sells and dumps, by mint
+lazy_static! {
+    static ref WATCHED_TOKENS: Arc<DashMap<String, WatchedToken>> = Arc::new(DashMap::new());
+    static ref DETECTOR_STARTED: AtomicBool = AtomicBool::new(false);
+    pub static ref DUMP_CONFIG: DumpDetectorConfig = DumpDetectorConfig::from_env();
+}
+
+/// Thresholds for the emergency exit on held positions
+#[derive(Debug, Clone)]
+pub struct DumpDetectorConfig {
+    pub enabled: bool,
+    pub dev_sell_percentage: f64,     // exit once the creator sold more than this % of their bag
+    pub slot_window: u64,             // slots a coordinated dump is measured over
+    pub min_sellers: usize,           // distinct top holders selling inside the window
+    pub min_supply_percentage: f64,   // % of supply they must sell together
+    pub top_holders: usize,           // largest wallets watched per token
+}
+
+impl DumpDetectorConfig {
+    /// Load from `DUMP_DETECTION_ENABLED`, `DEV_SELL_EXIT_PERCENTAGE`, `DUMP_SLOT_WINDOW`,
+    /// `DUMP_MIN_SELLERS`, `DUMP_MIN_SUPPLY_PERCENTAGE` and `DUMP_TOP_HOLDERS`
+    pub fn from_env() -> Self {
+        Self {
+            enabled: std::env::var("DUMP_DETECTION_ENABLED")
+                .ok()
+                .and_then(|v| v.parse::<bool>().ok())
+                .unwrap_or(true),
+            dev_sell_percentage: std::env::var("DEV_SELL_EXIT_PERCENTAGE")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(20.0),
+            slot_window: std::env::var("DUMP_SLOT_WINDOW")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(3),
+            min_sellers: std::env::var("DUMP_MIN_SELLERS")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(3),
+            min_supply_percentage: std::env::var("DUMP_MIN_SUPPLY_PERCENTAGE")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(5.0),
+            top_holders: std::env::var("DUMP_TOP_HOLDERS")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(10),
+        }
+    }
+}
+
+/// What is known about a held token's creator and top holders
+#[derive(Debug, Clone, Default)]
+struct WatchedToken {
+    creator: Option<String>,
+    supply: u64,
+    top_holders: HashSet<String>,
+    creator_bag: Option<u64>, // creator balance before their first observed sell
+    creator_sold: u64,
+    recent_sells: VecDeque<(u64, String, u64)>, // slot, seller, tokens
+    exiting: bool,
+}
+
+/// Start watching held positions for creator sells and coordinated dumps; later calls are no-ops
+pub fn start_dump_detector(
+    yellowstone_grpc_http: String,
+    yellowstone_grpc_token: String,
+    selling: Arc<ComprehensiveSelling>,
+) {
+    if !DUMP_CONFIG.enabled || DETECTOR_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let logger = Logger::new("[DUMP-DETECTOR] => ".bright_red().to_string());
+    let config = &*DUMP_CONFIG;
+    logger.log(format!(
+        "Exit when the creator sells > {}% of their bag or {} top holders sell {}% of supply within {} slots",
+        config.dev_sell_percentage, config.min_sellers, config.min_supply_percentage, config.slot_window
+    ));
+
+    tokio::spawn(async move {
+        loop {
+            match run_position_stream(&yellowstone_grpc_http, &yellowstone_grpc_token, selling.clone(), &logger).await {
+                Ok(_) => logger.log("Position stream closed, reconnecting...".yellow().to_string()),
+                Err(e) => logger.log(format!("Position stream error: {}, reconnecting...", e).red().to_string()),
+            };
+            sleep(Duration::from_secs(2)).await;
+        }
+    });
+}
+
+/// Stream every transaction touching a held mint, resubscribing whenever the held set changes
+async fn run_position_stream(
+    yellowstone_grpc_http: &str,
+    yellowstone_grpc_token: &str,
+    selling: Arc<ComprehensiveSelling>,
+    logger: &Logger,
+) -> Result<()> {
+    let mut client = GeyserGrpcClient::build_from_shared(yellowstone_grpc_http.to_string())?
+        .x_token(Some(yellowstone_grpc_token.to_string()))?
+        .tls_config(ClientTlsConfig::new().with_native_roots())?
+        .connect()
+        .await?;
+    let (mut subscribe_tx, mut stream) = client.subscribe().await?;
+
+    let mut subscribed: Vec<String> = Vec::new();
+    let mut refresh = interval(Duration::from_secs(1));
+
+    loop {
+        tokio::select! {
+            _ = refresh.tick() => {
+                let mut held = comprehensive_selling::get_bought_token_mints();
+                held.sort();
+                if held == subscribed {
+                    continue;
+                }
+                WATCHED_TOKENS.retain(|mint, _| held.contains(mint));
+                for mint in held.iter().filter(|mint| !WATCHED_TOKENS.contains_key(*mint)) {
+                    // Creator sells are caught right away, supply and holders follow from RPC
+                    WATCHED_TOKENS.insert(mint.clone(), WatchedToken {
+                        creator: comprehensive_selling::get_bought_token(mint).and_then(|info| info.trade_info.coin_creator),
+                        ..Default::default()
+                    });
+                    let selling = selling.clone();
+                    let mint = mint.clone();
+                    tokio::spawn(async move {
+                        watch_token(selling.app_state(), &mint).await;
+                    });
+                }
+
+                // An empty account filter would match every transaction, so drop the filter instead
+                let mut transactions = HashMap::new();
+                if !held.is_empty() {
+                    transactions.insert("positions".to_string(), SubscribeRequestFilterTransactions {
+                        vote: Some(false),
+                        failed: Some(false),
+                        signature: None,
+                        account_include: held.clone(),
+                        account_exclude: Vec::new(),
+                        account_required: Vec::new(),
+                    });
+                }
+                subscribe_tx.send(SubscribeRequest {
+                    transactions,
+                    commitment: Some(CommitmentLevel::Processed as i32),
+                    ..Default::default()
+                }).await?;
+                logger.log(format!("Watching {} held tokens", held.len()));
+                subscribed = held;
+            }
+            message = stream.next() => {
+                let Some(message) = message else { return Ok(()) };
+                let update = message.map_err(|e| anyhow!("{}", e))?;
+                match update.update_oneof {
+                    Some(UpdateOneof::Transaction(txn)) => {
+                        if let Some(trade) = transaction_parser::parse_transaction_data(&txn) {
+                            if let Some(price) = trade.price_in_sol() {
+                                comprehensive_selling::update_token_price(&trade.mint, price);
+                            }
+                            risk_management::record_market_trade(&trade);
+                            if let Some(reason) = check_trade(&trade) {
+                                let selling = selling.clone();
+                                let logger = logger.clone();
+                                tokio::spawn(async move {
+                                    if let Err(e) = selling.emergency_exit(&trade.mint, &reason).await {
+                                        logger.log(format!("Emergency exit of {} failed: {}", trade.mint, e).red().to_string());
+                                        if let Some(mut watched) = WATCHED_TOKENS.get_mut(&trade.mint) {
+                                            watched.exiting = false;
+                                        }
+                                    }
+                                });
+                            }
+                        }
+                    }
+                    Some(UpdateOneof::Ping(_)) => {
+                        subscribe_tx.send(SubscribeRequest {
+                            ping: Some(SubscribeRequestPing { id: 1 }),
+                            ..Default::default()
+                        }).await?;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Look up the supply and top holders of a newly held token, off the stream loop
+async fn watch_token(app_state: &AppState, mint: &str) {
+    let Ok(mint_pubkey) = Pubkey::from_str(mint) else { return };
+    let supply = token::get_mint_info(app_state.rpc_nonblocking_client.clone(), app_state.wallet.clone(), mint_pubkey)
+        .await
+        .map(|mint_info| mint_info.base.supply)
+        .ok();
+    let top_holders: Option<HashSet<String>> = rug_check::largest_wallet_holders(app_state, &mint_pubkey)
+        .await
+        .ok()
+        .map(|holders| {
+            holders
+                .into_iter()
+                .take(DUMP_CONFIG.top_holders)
+                .map(|(owner, _)| owner.to_string())
+                .collect()
+        });
+
+    // The position may have closed while the lookups ran
+    if let Some(mut watched) = WATCHED_TOKENS.get_mut(mint) {
+        if let Some(supply) = supply {
+            watched.supply = supply;
+        }
+        if let Some(top_holders) = top_holders {
+            watched.top_holders = top_holders;
+        }
+    }
+}
+
+/// Record a trade on a held token, returning the exit reason when it completes a dev sell or dump
+fn check_trade(trade: &TradeInfoFromToken) -> Option<String> {
+    if trade.is_buy {
+        return None;
+    }
+    let config = &*DUMP_CONFIG;
+    let mut watched = WATCHED_TOKENS.get_mut(&trade.mint)?;
+    if watched.exiting {
+        return None;
+    }
+    if watched.creator.is_none() {
+        watched.creator = trade.coin_creator.clone();
+    }
+
+    let tokens = trade.token_amount.unwrap_or_else(|| {
+        match (trade.user_pre_token_balance, trade.user_post_token_balance) {
+            (Some(pre), Some(post)) => pre.saturating_sub(post),
+            _ => 0,
+        }
+    });
+
+    let mut reason = None;
+    if watched.creator.as_deref() == Some(trade.user.as_str()) {
+        if watched.creator_bag.is_none() {
+            watched.creator_bag = trade.user_pre_token_balance;
+        }
+        watched.creator_sold += tokens;
+        // Without a known bag the share sold cannot be measured, so the sell is no signal
+        if let Some(bag) = watched.creator_bag.filter(|bag| *bag > 0) {
+            let sold_percentage = watched.creator_sold as f64 / bag as f64 * 100.0;
+            if sold_percentage > config.dev_sell_percentage {
+                reason = Some(format!("creator {} sold {:.1}% of their bag", trade.user, sold_percentage));
+            }
+        }
+    }
+
+    if reason.is_none() && watched.top_holders.contains(&trade.user) && watched.supply > 0 {
+        watched.recent_sells.push_back((trade.slot, trade.user.clone(), tokens));
+        let oldest_slot = trade.slot.saturating_sub(config.slot_window);
+        while matches!(watched.recent_sells.front(), Some((slot, _, _)) if *slot < oldest_slot) {
+            watched.recent_sells.pop_front();
+        }
+
+        let sellers: HashSet<&String> = watched.recent_sells.iter().map(|(_, seller, _)| seller).collect();
+        let sold: u64 = watched.recent_sells.iter().map(|(_, _, tokens)| tokens).sum();
+        let sold_percentage = sold as f64 / watched.supply as f64 * 100.0;
+        if sellers.len() >= config.min_sellers && sold_percentage >= config.min_supply_percentage {
+            reason = Some(format!(
+                "{} top holders sold {:.1}% of supply within {} slots",
+                sellers.len(), sold_percentage, config.slot_window
+            ));
+        }
+    }
+
+    if reason.is_some() {
+        watched.exiting = true;
+    }
+    reason
+}
@@ -0,0 +1,462 @@
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::paper;
+use crate::engine::position_store::StoredPosition;
+use crate::engine::risk_management::trading_day;
+use crate::engine::swap::{SwapDirection, SwapProtocol};
+use crate::engine::transaction_parser::TradeInfoFromToken;
+use crate::tx_processor::fill::Fill;
+
+lazy_static! {
+    pub static ref JOURNAL_FILE: String =
+        paper::paper_path(&std::env::var("JOURNAL_FILE").unwrap_or_else(|_| "journal.jsonl".to_string()));
+    // Lines queued for the writer thread, which keeps the file open and flushes once the queue is empty
+    static ref JOURNAL_WRITER: Mutex<Sender<String>> = Mutex::new(start_writer());
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JournalEvent {
+    #[default]
+    Detected, // a target trade was seen
+    Buy,      // decided to buy
+    Skip,     // decided not to buy, `reason` says why
+    Submitted, // our transaction was sent
+    Landed,   // our transaction confirmed successfully
+    Failed,   // our transaction failed to send or land
+    Fill,     // what a landed transaction bought or sold
+}
+
+/// One line of the trade journal; fields that do not apply to an event stay empty
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JournalEntry {
+    pub timestamp: i64,
+    pub event: JournalEvent,
+    pub mint: String,
+    pub target: String,
+    pub venue: String,
+    pub side: String,          // "buy" or "sell"
+    pub signature: String,
+    pub reason: String,        // decision reason or error
+    pub sol_amount: f64,       // SOL spent on a buy, received from a sell
+    pub token_amount: u64,
+    pub price: f64,            // SOL per token
+    pub cost_basis_sol: f64,   // entry cost of the tokens a sell closed
+    pub fee_sol: f64,          // base and priority fee
+    pub tip_sol: f64,
+    pub rent_sol: f64,         // ATA rent paid, negative when refunded
+    pub venue_fee_sol: f64,    // already included in `sol_amount`
+    pub quoted_price: f64,     // price the fill was expected at
+    pub slippage_percentage: f64, // how much worse than `quoted_price` the fill was, negative when better
+    pub estimated: bool,       // fill valued from the last seen price, not the landed transaction
+}
+
+fn side(direction: &SwapDirection) -> String {
+    match direction {
+        SwapDirection::Buy => "buy".to_string(),
+        SwapDirection::Sell => "sell".to_string(),
+    }
+}
+
+/// Append an entry to `JOURNAL_FILE`; failures are logged, trading goes on
+pub fn record(mut entry: JournalEntry) {
+    if entry.timestamp == 0 {
+        entry.timestamp = chrono::Utc::now().timestamp();
+    }
+    match serde_json::to_string(&entry) {
+        Ok(line) => {
+            let writer = JOURNAL_WRITER.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            let _ = writer.send(line);
+        }
+        Err(e) => println!("{}", format!("Failed to write journal {}: {}", *JOURNAL_FILE, e).red()),
+    }
+}
+
+fn start_writer() -> Sender<String> {
+    let (sender, receiver) = channel::<String>();
+    std::thread::spawn(move || {
+        let path = &*JOURNAL_FILE;
+        let mut file = None;
+        while let Ok(line) = receiver.recv() {
+            if file.is_none() {
+                match OpenOptions::new().create(true).append(true).open(path) {
+                    Ok(opened) => file = Some(BufWriter::new(opened)),
+                    Err(e) => {
+                        println!("{}", format!("Failed to open journal {}: {}", path, e).red());
+                        continue;
+                    }
+                }
+            }
+            let Some(writer) = file.as_mut() else { continue };
+            let mut result = writeln!(writer, "{}", line);
+            // Lines already queued go out in the same flush
+            while let Ok(line) = receiver.try_recv() {
+                result = result.and_then(|_| writeln!(writer, "{}", line));
+            }
+            if let Err(e) = result.and_then(|_| writer.flush()) {
+                println!("{}", format!("Failed to write journal {}: {}", path, e).red());
+                // Reopen on the next line
+                file = None;
+            }
+        }
+    });
+    sender
+}
+
+pub fn record_detected(trade: &TradeInfoFromToken) {
+    record(JournalEntry {
+        event: JournalEvent::Detected,
+        mint: trade.mint.clone(),
+        target: trade.target.clone(),
+        venue: format!("{:?}", SwapProtocol::from(&trade.dex_type)),
+        side: if trade.is_buy { "buy" } else { "sell" }.to_string(),
+        signature: trade.signature.clone(),
+        sol_amount: trade.sol_amount.or(trade.quote_amount_out).unwrap_or(0) as f64 / 1_000_000_000.0,
+        token_amount: trade.token_amount.or(trade.base_amount_in_or_base_amount_out).unwrap_or(0),
+        price: trade.price_in_sol().unwrap_or(0.0),
+        ..Default::default()
+    });
+}
+
+/// Record a buy decision: `buy` false means skipped for `reason`
+pub fn record_decision(mint: &str, target: &str, buy: bool, reason: &str) {
+    record(JournalEntry {
+        event: if buy { JournalEvent::Buy } else { JournalEvent::Skip },
+        mint: mint.to_string(),
+        target: target.to_string(),
+        side: "buy".to_string(),
+        reason: reason.to_string(),
+        ..Default::default()
+    });
+}
+
+/// Record one of our swaps being sent or refused; `amount` is lamports for buys, raw tokens for sells
+pub fn record_transaction(
+    mint: &str,
+    venue: &SwapProtocol,
+    direction: &SwapDirection,
+    amount: u64,
+    result: &Result<String, String>,
+) {
+    let (sol_amount, token_amount) = match direction {
+        SwapDirection::Buy => (amount as f64 / 1_000_000_000.0, 0),
+        SwapDirection::Sell => (0.0, amount),
+    };
+    let (event, signature, reason) = match result {
+        Ok(signature) => (JournalEvent::Submitted, signature.clone(), String::new()),
+        Err(e) => (JournalEvent::Failed, String::new(), e.clone()),
+    };
+    record(JournalEntry {
+        event,
+        mint: mint.to_string(),
+        venue: format!("{:?}", venue),
+        side: side(direction),
+        signature,
+        reason,
+        sol_amount,
+        token_amount,
+        ..Default::default()
+    });
+}
+
+/// Record whether a submitted swap confirmed: `Err` carries why it failed or was never seen
+pub fn record_confirmation(mint: &str, direction: &SwapDirection, signature: &str, result: Result<(), String>) {
+    record(JournalEntry {
+        event: if result.is_ok() { JournalEvent::Landed } else { JournalEvent::Failed },
+        mint: mint.to_string(),
+        side: side(direction),
+        signature: signature.to_string(),
+        reason: result.err().unwrap_or_default(),
+        ..Default::default()
+    });
+}
+
+/// Record a fill measured from the confirmed transaction; `cost_basis_sol` is 0 for buys
+pub fn record_fill(
+    mint: &str,
+    target: &str,
+    venue: &SwapProtocol,
+    direction: &SwapDirection,
+    fill: &Fill,
+    quoted_price: f64,
+    cost_basis_sol: f64,
+) {
+    record(JournalEntry {
+        event: JournalEvent::Fill,
+        mint: mint.to_string(),
+        target: target.to_string(),
+        venue: format!("{:?}", venue),
+        side: side(direction),
+        signature: fill.signature.clone(),
+        sol_amount: fill.sol_amount,
+        token_amount: fill.token_amount,
+        price: fill.price,
+        cost_basis_sol,
+        fee_sol: fill.fee_sol(),
+        tip_sol: fill.tip_sol,
+        rent_sol: fill.rent_sol,
+        venue_fee_sol: fill.venue_fee_sol,
+        quoted_price,
+        slippage_percentage: fill.slippage_percentage(quoted_price, direction),
+        estimated: false,
+        ..Default::default()
+    });
+}
+
+/// Every entry of a journal file
+pub fn read_journal(path: &str) -> Result<Vec<JournalEntry>> {
+    let file = std::fs::File::open(path).map_err(|e| anyhow!("Failed to open journal {}: {}", path, e))?;
+    let mut entries = Vec::new();
+    for (number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        entries.push(
+            serde_json::from_str(&line).map_err(|e| anyhow!("Invalid journal line {}: {}", number + 1, e))?,
+        );
+    }
+    Ok(entries)
+}
+
+/// PnL of one day, target, venue or mint
+///
+/// `net_pnl_sol` is realized plus unrealized PnL minus fees, tips and rent; venue fees are
+/// already inside the fill amounts and only shown.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReportRow {
+    pub dimension: String,
+    pub key: String,
+    pub buys: usize,
+    pub sells: usize,
+    pub bought_sol: f64,
+    pub sold_sol: f64,
+    pub realized_pnl_sol: f64,
+    pub unrealized_pnl_sol: f64,
+    pub fees_sol: f64,
+    pub tips_sol: f64,
+    pub rent_sol: f64,
+    pub venue_fees_sol: f64,
+    pub net_pnl_sol: f64,
+}
+
+/// Aggregate fills and open positions by day, target, venue and mint
+///
+/// Days start at `day_start_hour_utc` like the risk engine's; open positions count towards the day of `now`.
+pub fn build_report(
+    entries: &[JournalEntry],
+    open_positions: &[StoredPosition],
+    day_start_hour_utc: u32,
+    now: i64,
+) -> Vec<ReportRow> {
+    let mut rows: BTreeMap<(usize, String), ReportRow> = BTreeMap::new();
+    let dimensions = ["day", "target", "venue", "mint"];
+    let mut add = |keys: [&str; 4], apply: &dyn Fn(&mut ReportRow)| {
+        for (index, key) in keys.iter().enumerate() {
+            let key = if key.is_empty() { "-" } else { key };
+            let row = rows.entry((index, key.to_string())).or_insert_with(|| ReportRow {
+                dimension: dimensions[index].to_string(),
+                key: key.to_string(),
+                ..Default::default()
+            });
+            apply(row);
+        }
+    };
+
+    for entry in entries.iter().filter(|entry| entry.event == JournalEvent::Fill) {
+        let day = trading_day(entry.timestamp, day_start_hour_utc);
+        add([&day, &entry.target, &entry.venue, &entry.mint], &|row| {
+            if entry.side == "sell" {
+                row.sells += 1;
+                row.sold_sol += entry.sol_amount;
+                row.realized_pnl_sol += entry.sol_amount - entry.cost_basis_sol;
+            } else {
+                row.buys += 1;
+                row.bought_sol += entry.sol_amount;
+            }
+            row.fees_sol += entry.fee_sol;
+            row.tips_sol += entry.tip_sol;
+            row.rent_sol += entry.rent_sol;
+            row.venue_fees_sol += entry.venue_fee_sol;
+        });
+    }
+
+    let today = trading_day(now, day_start_hour_utc);
+    for position in open_positions {
+        let cost_basis = position.entry_amount * position.remaining_amount / 100.0;
+        let unrealized = if position.entry_price > 0.0 {
+            cost_basis * (position.current_price / position.entry_price - 1.0)
+        } else {
+            0.0
+        };
+        add([&today, &position.target, &position.protocol, &position.mint], &|row| {
+            row.unrealized_pnl_sol += unrealized;
+        });
+    }
+
+    rows.into_values()
+        .map(|mut row| {
+            row.net_pnl_sol = row.realized_pnl_sol + row.unrealized_pnl_sol - row.fees_sol - row.tips_sol - row.rent_sol;
+            row
+        })
+        .collect()
+}
+
+/// Quote a CSV field when it holds a separator, quote or newline
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Report rows as an aligned table, CSV or JSONL
+pub fn format_report(rows: &[ReportRow], format: &str) -> Result<String> {
+    let mut out = String::new();
+    match format {
+        "csv" => {
+            out.push_str("dimension,key,buys,sells,bought_sol,sold_sol,realized_pnl_sol,unrealized_pnl_sol,fees_sol,tips_sol,rent_sol,venue_fees_sol,net_pnl_sol\n");
+            for row in rows {
+                out.push_str(&format!(
+                    "{},{},{},{},{:.9},{:.9},{:.9},{:.9},{:.9},{:.9},{:.9},{:.9},{:.9}\n",
+                    row.dimension, csv_field(&row.key), row.buys, row.sells, row.bought_sol, row.sold_sol,
+                    row.realized_pnl_sol, row.unrealized_pnl_sol, row.fees_sol, row.tips_sol, row.rent_sol,
+                    row.venue_fees_sol, row.net_pnl_sol
+                ));
+            }
+        }
+        "jsonl" => {
+            for row in rows {
+                out.push_str(&serde_json::to_string(row)?);
+                out.push('\n');
+            }
+        }
+        "table" => {
+            out.push_str(&format!(
+                "{:<7} {:<45} {:>4} {:>5} {:>10} {:>10} {:>11} {:>11} {:>8} {:>8} {:>8} {:>10}\n",
+                "by", "key", "buys", "sells", "bought", "sold", "realized", "unrealized", "fees", "tips", "rent", "net"
+            ));
+            for row in rows {
+                out.push_str(&format!(
+                    "{:<7} {:<45} {:>4} {:>5} {:>10.4} {:>10.4} {:>+11.4} {:>+11.4} {:>8.4} {:>8.4} {:>8.4} {:>+10.4}\n",
+                    row.dimension, row.key, row.buys, row.sells, row.bought_sol, row.sold_sol,
+                    row.realized_pnl_sol, row.unrealized_pnl_sol, row.fees_sol, row.tips_sol, row.rent_sol,
+                    row.net_pnl_sol
+                ));
+            }
+        }
+        other => return Err(anyhow!("Unknown report format {}, use table, csv or jsonl", other)),
+    }
+    Ok(out)
+}
+
+/// Raw journal entries as CSV or JSONL
+pub fn format_entries(entries: &[JournalEntry], format: &str) -> Result<String> {
+    let mut out = String::new();
+    match format {
+        "csv" => {
+            out.push_str("timestamp,event,mint,target,venue,side,signature,reason,sol_amount,token_amount,price,cost_basis_sol,fee_sol,tip_sol,rent_sol,venue_fee_sol,quoted_price,slippage_percentage,estimated\n");
+            for entry in entries {
+                let event = serde_json::to_value(entry.event)?;
+                out.push_str(&format!(
+                    "{},{},{},{},{},{},{},{},{:.9},{},{:.12},{:.9},{:.9},{:.9},{:.9},{:.9},{:.12},{:.4},{}\n",
+                    entry.timestamp, event.as_str().unwrap_or_default(), entry.mint, entry.target, entry.venue,
+                    entry.side, entry.signature, csv_field(&entry.reason), entry.sol_amount, entry.token_amount,
+                    entry.price, entry.cost_basis_sol, entry.fee_sol, entry.tip_sol, entry.rent_sol,
+                    entry.venue_fee_sol, entry.quoted_price, entry.slippage_percentage, entry.estimated
+                ));
+            }
+        }
+        "jsonl" => {
+            for entry in entries {
+                out.push_str(&serde_json::to_string(entry)?);
+                out.push('\n');
+            }
+        }
+        other => return Err(anyhow!("Unknown export format {}, use csv or jsonl", other)),
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(timestamp: i64, side: &str, sol_amount: f64, cost_basis_sol: f64) -> JournalEntry {
+        JournalEntry {
+            timestamp,
+            event: JournalEvent::Fill,
+            mint: "mint".to_string(),
+            target: "target".to_string(),
+            venue: "PumpSwap".to_string(),
+            side: side.to_string(),
+            sol_amount,
+            cost_basis_sol,
+            fee_sol: 0.001,
+            tip_sol: 0.002,
+            rent_sol: 0.002,
+            ..Default::default()
+        }
+    }
+
+    fn row<'a>(rows: &'a [ReportRow], dimension: &str, key: &str) -> &'a ReportRow {
+        rows.iter()
+            .find(|row| row.dimension == dimension && row.key == key)
+            .unwrap_or_else(|| panic!("no {} row {}", dimension, key))
+    }
+
+    #[test]
+    fn report_nets_costs_out_of_realized_and_unrealized_pnl() {
+        // 2023-11-14 22:13 UTC buy, sold half back for a 0.1 SOL gain
+        let entries = vec![
+            fill(1_700_000_000, "buy", 1.0, 0.0),
+            fill(1_700_000_100, "sell", 0.6, 0.5),
+            JournalEntry { event: JournalEvent::Skip, ..fill(1_700_000_200, "buy", 5.0, 0.0) },
+        ];
+        let open = vec![StoredPosition {
+            mint: "mint".to_string(),
+            target: "target".to_string(),
+            protocol: "PumpSwap".to_string(),
+            entry_price: 1.0,
+            current_price: 1.5,
+            entry_amount: 1.0,
+            remaining_amount: 50.0,
+            ..Default::default()
+        }];
+        let rows = build_report(&entries, &open, 0, 1_700_000_300);
+
+        let target = row(&rows, "target", "target");
+        assert_eq!((target.buys, target.sells), (1, 1));
+        assert!((target.realized_pnl_sol - 0.1).abs() < 1e-9);
+        assert!((target.unrealized_pnl_sol - 0.25).abs() < 1e-9);
+        assert!((target.net_pnl_sol - (0.35 - 0.01)).abs() < 1e-9);
+        assert_eq!(row(&rows, "day", "2023-11-14").buys, 1);
+    }
+
+    #[test]
+    fn report_days_follow_the_risk_day_start() {
+        // 22:13 UTC is still that day's trading day when days start at 20:00, the previous one's at 23:00
+        let rows = build_report(&[fill(1_700_000_000, "buy", 1.0, 0.0)], &[], 20, 1_700_000_000);
+        assert_eq!(row(&rows, "day", "2023-11-14").buys, 1);
+        let rows = build_report(&[fill(1_700_000_000, "buy", 1.0, 0.0)], &[], 23, 1_700_000_000);
+        assert_eq!(row(&rows, "day", "2023-11-13").buys, 1);
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_only_when_needed() {
+        assert_eq!(csv_field("plain reason"), "plain reason");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("said \"no\""), "\"said \"\"no\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+}
@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use colored::Colorize;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::engine::transaction_parser::{read_string, LaunchInfo};
+use crate::library::config::AppState;
+use crate::library::logger::Logger;
+
+// Symbols of recent launches, uppercased, with the mint and when it was seen (unix seconds), and the
+// client shared by metadata JSON fetches
+lazy_static! {
+    static ref RECENT_TICKERS: Mutex<HashMap<String, (String, i64)>> = Mutex::new(HashMap::new());
+    static ref METADATA_HTTP_CLIENT: reqwest::Client = reqwest::Client::new();
+}
+
+/// Metadata of a launch: on-chain Metaplex fields plus the socials from the off-chain JSON
+#[derive(Debug, Clone, Default)]
+pub struct TokenMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub twitter: Option<String>,
+    pub telegram: Option<String>,
+    pub website: Option<String>,
+    pub image: Option<String>,
+}
+
+/// Candidate filters for sniping, every unset filter lets the launch through
+#[derive(Debug, Clone)]
+pub struct LaunchFilterConfig {
+    pub name_allow: Option<Regex>,
+    pub name_deny: Option<Regex>,
+    pub symbol_allow: Option<Regex>,
+    pub symbol_deny: Option<Regex>,
+    pub require_twitter: bool,
+    pub require_telegram: bool,
+    pub require_website: bool,
+    pub require_image: bool,
+    pub duplicate_ticker_window_secs: u64, // 0 = allow reused tickers
+    pub fetch_timeout_ms: u64,
+}
+
+fn filter_logger() -> Logger {
+    Logger::new("[LAUNCH-FILTER] => ".magenta().to_string())
+}
+
+fn env_regex(name: &str) -> Option<Regex> {
+    let pattern = std::env::var(name).ok().filter(|p| !p.is_empty())?;
+    match Regex::new(&pattern) {
+        Ok(regex) => Some(regex),
+        Err(e) => {
+            filter_logger().log(format!("Ignoring invalid {}: {}", name, e).red().to_string());
+            None
+        }
+    }
+}
+
+fn env_flag(name: &str) -> bool {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse::<bool>().ok())
+        .unwrap_or(false)
+}
+
+impl LaunchFilterConfig {
+    /// Load from `NAME_ALLOW_REGEX`, `NAME_DENY_REGEX`, `SYMBOL_ALLOW_REGEX`, `SYMBOL_DENY_REGEX`,
+    /// `REQUIRE_TWITTER`, `REQUIRE_TELEGRAM`, `REQUIRE_WEBSITE`, `REQUIRE_IMAGE`,
+    /// `DUPLICATE_TICKER_WINDOW_SECS` and `METADATA_FETCH_TIMEOUT_MS`
+    pub fn from_env() -> Self {
+        Self {
+            name_allow: env_regex("NAME_ALLOW_REGEX"),
+            name_deny: env_regex("NAME_DENY_REGEX"),
+            symbol_allow: env_regex("SYMBOL_ALLOW_REGEX"),
+            symbol_deny: env_regex("SYMBOL_DENY_REGEX"),
+            require_twitter: env_flag("REQUIRE_TWITTER"),
+            require_telegram: env_flag("REQUIRE_TELEGRAM"),
+            require_website: env_flag("REQUIRE_WEBSITE"),
+            require_image: env_flag("REQUIRE_IMAGE"),
+            duplicate_ticker_window_secs: std::env::var("DUPLICATE_TICKER_WINDOW_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(0),
+            fetch_timeout_ms: std::env::var("METADATA_FETCH_TIMEOUT_MS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(1500),
+        }
+    }
+
+    /// Record the launch's ticker as recent, returning the other mint that used it within the window
+    pub fn register_launch(&self, launch: &LaunchInfo) -> Option<String> {
+        let mut recent = RECENT_TICKERS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        self.register_ticker(&mut recent, &launch.symbol, &launch.mint, chrono::Utc::now().timestamp())
+    }
+
+    /// Run every filter on a launch, `Err` carries the rejection reason
+    ///
+    /// `duplicate_of` is what `register_launch` returned for it.
+    pub async fn check(
+        &self,
+        app_state: &AppState,
+        launch: &LaunchInfo,
+        duplicate_of: Option<String>,
+    ) -> Result<TokenMetadata, String> {
+        let mut metadata = match &launch.metadata_account {
+            Some(account) => match fetch_onchain_metadata(app_state, account).await {
+                Ok(metadata) => metadata,
+                // Not on the RPC node yet, the create event carries the same fields
+                Err(e) if !launch.name.is_empty() => {
+                    filter_logger().log(format!("{}, using the create event's metadata", e).yellow().to_string());
+                    TokenMetadata::default()
+                }
+                Err(e) => return Err(format!("metadata unavailable: {}", e)),
+            },
+            None => TokenMetadata::default(),
+        };
+        // Fall back to what the create event carried
+        if metadata.name.is_empty() {
+            metadata.name = launch.name.clone();
+        }
+        if metadata.symbol.is_empty() {
+            metadata.symbol = launch.symbol.clone();
+        }
+        if metadata.uri.is_empty() {
+            metadata.uri = launch.uri.clone();
+        }
+
+        self.check_names(&metadata.name, &metadata.symbol)?;
+        if let Some(other_mint) = duplicate_of {
+            return Err(format!("ticker '{}' already launched as {}", metadata.symbol, other_mint));
+        }
+
+        if !self.needs_json() {
+            return Ok(metadata);
+        }
+        if metadata.uri.is_empty() {
+            return Err("no metadata URI".to_string());
+        }
+        let json = fetch_metadata_json(&metadata.uri, self.fetch_timeout_ms)
+            .await
+            .map_err(|e| format!("metadata JSON unavailable: {}", e))?;
+        apply_metadata_json(&mut metadata, &json);
+
+        if self.require_twitter && metadata.twitter.is_none() {
+            return Err("no twitter link".to_string());
+        }
+        if self.require_telegram && metadata.telegram.is_none() {
+            return Err("no telegram link".to_string());
+        }
+        if self.require_website && metadata.website.is_none() {
+            return Err("no website".to_string());
+        }
+        if self.require_image && metadata.image.is_none() {
+            return Err("no image".to_string());
+        }
+
+        Ok(metadata)
+    }
+
+    /// Name and symbol allow and deny lists, `Err` carries the rejection reason
+    pub fn check_names(&self, name: &str, symbol: &str) -> Result<(), String> {
+        if let Some(regex) = &self.name_allow {
+            if !regex.is_match(name) {
+                return Err(format!("name '{}' not in allow list", name));
+            }
+        }
+        if let Some(regex) = &self.name_deny {
+            if regex.is_match(name) {
+                return Err(format!("name '{}' matches deny list", name));
+            }
+        }
+        if let Some(regex) = &self.symbol_allow {
+            if !regex.is_match(symbol) {
+                return Err(format!("symbol '{}' not in allow list", symbol));
+            }
+        }
+        if let Some(regex) = &self.symbol_deny {
+            if regex.is_match(symbol) {
+                return Err(format!("symbol '{}' matches deny list", symbol));
+            }
+        }
+        Ok(())
+    }
+
+    /// Remember the ticker in `recent` at `now` (unix seconds), returning the other mint when it was
+    /// used within the window
+    pub fn register_ticker(&self, recent: &mut HashMap<String, (String, i64)>, symbol: &str, mint: &str, now: i64) -> Option<String> {
+        if self.duplicate_ticker_window_secs == 0 || symbol.is_empty() {
+            return None;
+        }
+        let window = self.duplicate_ticker_window_secs as i64;
+        recent.retain(|_, (_, seen)| now - *seen < window);
+
+        let key = symbol.trim().to_uppercase();
+        let previous = recent
+            .get(&key)
+            .map(|entry| entry.0.clone())
+            .filter(|previous_mint| previous_mint != mint);
+        recent.insert(key, (mint.to_string(), now));
+        previous
+    }
+
+    /// Whether any filter needs the off-chain metadata JSON
+    pub fn needs_json(&self) -> bool {
+        self.require_twitter || self.require_telegram || self.require_website || self.require_image
+    }
+}
+
+/// Read name, symbol and URI from a Metaplex metadata account
+pub async fn fetch_onchain_metadata(app_state: &AppState, metadata_account: &str) -> Result<TokenMetadata> {
+    let address = Pubkey::from_str(metadata_account)
+        .map_err(|e| anyhow!("Invalid metadata account {}: {}", metadata_account, e))?;
+    let data = app_state.rpc_nonblocking_client
+        .get_account_data(&address)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch metadata {}: {}", metadata_account, e))?;
+
+    // key (1), update authority (32) and mint (32) precede the strings, which are null padded
+    let mut offset = 1 + 32 + 32;
+    let mut next = || read_string(&data, &mut offset).map(|s| s.trim_end_matches('\0').trim().to_string());
+    Ok(TokenMetadata {
+        name: next().ok_or_else(|| anyhow!("Truncated metadata name"))?,
+        symbol: next().ok_or_else(|| anyhow!("Truncated metadata symbol"))?,
+        uri: next().ok_or_else(|| anyhow!("Truncated metadata uri"))?,
+        ..Default::default()
+    })
+}
+
+/// Fetch the off-chain metadata JSON, giving up after `timeout_ms`
+pub async fn fetch_metadata_json(uri: &str, timeout_ms: u64) -> Result<Value> {
+    let url = match uri.strip_prefix("ipfs://") {
+        Some(cid) => format!("https://ipfs.io/ipfs/{}", cid),
+        None => uri.to_string(),
+    };
+    let json = METADATA_HTTP_CLIENT
+        .get(&url)
+        .timeout(Duration::from_millis(timeout_ms))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Value>()
+        .await?;
+    Ok(json)
+}
+
+/// Socials and image from the metadata JSON; launchpads put them at the top level or under `extensions`
+fn apply_metadata_json(metadata: &mut TokenMetadata, json: &Value) {
+    let field = |name: &str| -> Option<String> {
+        json.get(name)
+            .or_else(|| json.get("extensions").and_then(|ext| ext.get(name)))
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    metadata.twitter = field("twitter");
+    metadata.telegram = field("telegram");
+    metadata.website = field("website");
+    metadata.image = field("image");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter() -> LaunchFilterConfig {
+        LaunchFilterConfig {
+            name_allow: None,
+            name_deny: None,
+            symbol_allow: None,
+            symbol_deny: None,
+            require_twitter: false,
+            require_telegram: false,
+            require_website: false,
+            require_image: false,
+            duplicate_ticker_window_secs: 600,
+            fetch_timeout_ms: 1500,
+        }
+    }
+
+    #[test]
+    fn check_names_applies_allow_and_deny_lists() {
+        assert!(filter().check_names("Anything", "ANY").is_ok());
+
+        let config = LaunchFilterConfig {
+            name_allow: Some(Regex::new("(?i)cat").unwrap()),
+            name_deny: Some(Regex::new("(?i)rug").unwrap()),
+            symbol_allow: Some(Regex::new("^[A-Z]{2,6}$").unwrap()),
+            symbol_deny: Some(Regex::new("^SCAM").unwrap()),
+            ..filter()
+        };
+        let cases = [
+            ("Cat Coin", "CAT", None),
+            ("Dog Coin", "DOG", Some("name 'Dog Coin' not in allow list")),
+            ("Cat Rug", "CAT", Some("name 'Cat Rug' matches deny list")),
+            ("Cat Coin", "cat", Some("symbol 'cat' not in allow list")),
+            ("Cat Coin", "SCAMCT", Some("symbol 'SCAMCT' matches deny list")),
+        ];
+        for (name, symbol, expected) in cases {
+            assert_eq!(config.check_names(name, symbol).err().as_deref(), expected, "{} / {}", name, symbol);
+        }
+    }
+
+    #[test]
+    fn register_ticker_reports_reuse_within_the_window() {
+        let config = filter();
+        let mut recent = HashMap::new();
+
+        assert_eq!(config.register_ticker(&mut recent, "PEPE", "mint1", 1_000), None);
+        // Same mint seen again is not a duplicate of itself
+        assert_eq!(config.register_ticker(&mut recent, "PEPE", "mint1", 1_010), None);
+        // Case and surrounding spaces do not make a new ticker
+        assert_eq!(config.register_ticker(&mut recent, " pepe ", "mint2", 1_100), Some("mint1".to_string()));
+        // The reuse is remembered, so a third launch is compared with the second
+        assert_eq!(config.register_ticker(&mut recent, "PEPE", "mint3", 1_200), Some("mint2".to_string()));
+        // Past the window the ticker is free again
+        assert_eq!(config.register_ticker(&mut recent, "PEPE", "mint4", 1_200 + 600), None);
+        assert_eq!(config.register_ticker(&mut recent, "", "mint5", 2_000), None);
+    }
+
+    #[test]
+    fn register_ticker_is_off_without_a_window() {
+        let config = LaunchFilterConfig { duplicate_ticker_window_secs: 0, ..filter() };
+        let mut recent = HashMap::new();
+        assert_eq!(config.register_ticker(&mut recent, "PEPE", "mint1", 1_000), None);
+        assert_eq!(config.register_ticker(&mut recent, "PEPE", "mint2", 1_001), None);
+        assert!(recent.is_empty());
+    }
+}
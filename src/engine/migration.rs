@@ -0,0 +1,374 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use anyhow::{anyhow, Result};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use colored::Colorize;
+use dashmap::DashMap;
+use futures_util::stream::StreamExt;
+use futures_util::SinkExt;
+use lazy_static::lazy_static;
+use tokio::time::{interval, sleep};
+use yellowstone_grpc_client::{ClientTlsConfig, GeyserGrpcClient};
+use yellowstone_grpc_proto::geyser::{
+    subscribe_update::UpdateOneof, CommitmentLevel, SubscribeRequest, SubscribeRequestFilterTransactions,
+    SubscribeRequestPing,
+};
+
+use crate::dex::pump_fun::PUMP_PROGRAM;
+use crate::dex::pump_swap::PUMP_SWAP_PROGRAM;
+use crate::engine::comprehensive_selling::{self, ComprehensiveSelling};
+use crate::engine::copy_trading::is_buying_enabled;
+use crate::engine::creator_registry::{self, CreatorVerdict};
+use crate::engine::paper;
+use crate::engine::position_sizing::SizingDecision;
+use crate::engine::risk_management;
+use crate::engine::rug_check::{self, RugCheckConfig};
+use crate::engine::swap::{execute_swap, SwapDirection, SwapProtocol};
+use crate::engine::transaction_parser::{self, DexType, MigrationInfo, MigrationStage, TradeInfoFromToken};
+use crate::library::logger::Logger;
+use crate::utilities::telegram;
+
+// How long a migration's stage is remembered to drop duplicate events
+const MIGRATION_MEMORY: Duration = Duration::from_secs(600);
+
+// Migration stage last seen per mint and when, and mints bought on their new pool
+lazy_static! {
+    static ref MIGRATIONS: Arc<DashMap<String, (MigrationStage, Instant)>> = Arc::new(DashMap::new());
+    static ref MIGRATION_BUYS: Arc<DashMap<String, f64>> = Arc::new(DashMap::new());
+    static ref WATCHER_STARTED: AtomicBool = AtomicBool::new(false);
+    pub static ref MIGRATION_CONFIG: MigrationConfig = MigrationConfig::from_env();
+}
+
+/// Buying freshly migrated PumpSwap pools
+#[derive(Debug, Clone)]
+pub struct MigrationConfig {
+    pub snipe_enabled: bool,
+    pub buy_amount_sol: f64,
+    pub min_pool_sol: f64,    // skip pools seeded with less SOL
+    pub max_positions: usize, // open migration buys at once
+    pub rug_check: RugCheckConfig,
+}
+
+impl MigrationConfig {
+    /// Load from `MIGRATION_SNIPE_ENABLED`, `MIGRATION_BUY_AMOUNT`, `MIGRATION_MIN_POOL_SOL` and
+    /// `MIGRATION_MAX_POSITIONS`; the rug check shares the sniper's `RUG_*` settings
+    pub fn from_env() -> Self {
+        Self {
+            snipe_enabled: std::env::var("MIGRATION_SNIPE_ENABLED")
+                .ok()
+                .and_then(|v| v.parse::<bool>().ok())
+                .unwrap_or(false),
+            buy_amount_sol: std::env::var("MIGRATION_BUY_AMOUNT")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(0.01),
+            min_pool_sol: std::env::var("MIGRATION_MIN_POOL_SOL")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(0.0),
+            max_positions: std::env::var("MIGRATION_MAX_POSITIONS")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(3),
+            rug_check: RugCheckConfig::from_env(),
+        }
+    }
+}
+
+/// Follow PumpFun curves completing and migrating to PumpSwap; later calls are no-ops
+///
+/// Held PumpFun positions are moved to PumpSwap once their pool is created, so exits never
+/// target a curve that no longer trades. With `MIGRATION_SNIPE_ENABLED` new pools are bought too.
+pub fn start_migration_watcher(
+    yellowstone_grpc_http: String,
+    yellowstone_grpc_token: String,
+    selling: Arc<ComprehensiveSelling>,
+) {
+    if WATCHER_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let logger = Logger::new("[MIGRATION] => ".bright_blue().to_string());
+    let config = &*MIGRATION_CONFIG;
+    if config.snipe_enabled {
+        logger.log(format!(
+            "Buying migrated pools for {} SOL | min pool {} SOL | max {} positions",
+            config.buy_amount_sol, config.min_pool_sol, config.max_positions
+        ));
+    }
+
+    tokio::spawn(async move {
+        loop {
+            match run_migration_stream(&yellowstone_grpc_http, &yellowstone_grpc_token, selling.clone(), &logger).await {
+                Ok(_) => logger.log("Migration stream closed, reconnecting...".yellow().to_string()),
+                Err(e) => logger.log(format!("Migration stream error: {}, reconnecting...", e).red().to_string()),
+            };
+            sleep(Duration::from_secs(2)).await;
+        }
+    });
+}
+
+/// PumpFun positions whose curve completion has to be caught
+fn held_curve_mints() -> Vec<String> {
+    let mut mints: Vec<String> = comprehensive_selling::get_bought_token_mints()
+        .into_iter()
+        .filter(|mint| {
+            comprehensive_selling::get_bought_token(mint)
+                .is_some_and(|info| info.protocol == SwapProtocol::PumpFun)
+        })
+        .collect();
+    mints.sort();
+    mints
+}
+
+fn build_subscribe_request(curve_mints: &[String]) -> SubscribeRequest {
+    let mut transactions = HashMap::new();
+    // Migrations are the PumpFun program calling into PumpSwap
+    transactions.insert("migrations".to_string(), SubscribeRequestFilterTransactions {
+        vote: Some(false),
+        failed: Some(false),
+        signature: None,
+        account_include: vec![PUMP_PROGRAM.to_string()],
+        account_exclude: Vec::new(),
+        account_required: vec![PUMP_SWAP_PROGRAM.to_string()],
+    });
+    if !curve_mints.is_empty() {
+        transactions.insert("curves".to_string(), SubscribeRequestFilterTransactions {
+            vote: Some(false),
+            failed: Some(false),
+            signature: None,
+            account_include: curve_mints.to_vec(),
+            account_exclude: Vec::new(),
+            account_required: vec![PUMP_PROGRAM.to_string()],
+        });
+    }
+    SubscribeRequest {
+        transactions,
+        commitment: Some(CommitmentLevel::Processed as i32),
+        ..Default::default()
+    }
+}
+
+async fn run_migration_stream(
+    yellowstone_grpc_http: &str,
+    yellowstone_grpc_token: &str,
+    selling: Arc<ComprehensiveSelling>,
+    logger: &Logger,
+) -> Result<()> {
+    let mut client = GeyserGrpcClient::build_from_shared(yellowstone_grpc_http.to_string())?
+        .x_token(Some(yellowstone_grpc_token.to_string()))?
+        .tls_config(ClientTlsConfig::new().with_native_roots())?
+        .connect()
+        .await?;
+    let (mut subscribe_tx, mut stream) = client.subscribe().await?;
+
+    let mut curve_mints = held_curve_mints();
+    subscribe_tx.send(build_subscribe_request(&curve_mints)).await?;
+    logger.log("Subscribed to PumpFun migrations".green().to_string());
+
+    let mut refresh = interval(Duration::from_secs(5));
+
+    loop {
+        tokio::select! {
+            message = stream.next() => {
+                let Some(message) = message else { return Ok(()) };
+                let update = message.map_err(|e| anyhow!("{}", e))?;
+                match update.update_oneof {
+                    Some(UpdateOneof::Transaction(txn)) => {
+                        if let Some(migration) = transaction_parser::parse_migration_transaction(&txn) {
+                            let selling = selling.clone();
+                            tokio::spawn(async move {
+                                handle_migration(&selling, migration).await;
+                            });
+                        }
+                    }
+                    Some(UpdateOneof::Ping(_)) => {
+                        subscribe_tx.send(SubscribeRequest {
+                            ping: Some(SubscribeRequestPing { id: 1 }),
+                            ..Default::default()
+                        }).await?;
+                    }
+                    _ => {}
+                }
+            }
+            _ = refresh.tick() => {
+                // Every migration on chain passes through here, keep only recent ones
+                MIGRATIONS.retain(|_, (_, seen)| seen.elapsed() < MIGRATION_MEMORY);
+                let mints = held_curve_mints();
+                if mints != curve_mints {
+                    curve_mints = mints;
+                    subscribe_tx.send(build_subscribe_request(&curve_mints)).await?;
+                }
+            }
+        }
+    }
+}
+
+async fn handle_migration(selling: &ComprehensiveSelling, migration: MigrationInfo) {
+    let logger = Logger::new("[MIGRATION] => ".bright_blue().to_string());
+
+    let previous = MIGRATIONS.insert(migration.mint.clone(), (migration.stage.clone(), Instant::now()));
+    if previous.map(|(stage, _)| stage).as_ref() == Some(&migration.stage) {
+        return;
+    }
+    creator_registry::record_migration(&migration.mint);
+
+    match migration.stage {
+        MigrationStage::CurveComplete => {
+            logger.log(format!("Curve of {} completed, waiting for its PumpSwap pool", migration.mint));
+        }
+        MigrationStage::PoolCreated => {
+            logger.log(format!(
+                "{} migrated to pool {} | {:.2} SOL liquidity",
+                migration.mint,
+                migration.pool.as_deref().unwrap_or("?"),
+                migration.pool_quote_amount.unwrap_or(0) as f64 / 1_000_000_000.0
+            ));
+            if let Some(price) = migration.pool_price_in_sol() {
+                comprehensive_selling::update_token_price(&migration.mint, price);
+            }
+        }
+    }
+
+    // Until the pool exists sells stay on the completed curve, fail there and are retried by the monitor
+    if migration.stage != MigrationStage::PoolCreated {
+        return;
+    }
+    if comprehensive_selling::migrate_position(&migration.mint, migration.pool.as_deref()) {
+        logger.log(format!("Position {} now exits through PumpSwap", migration.mint).green().to_string());
+        return;
+    }
+
+    if MIGRATION_CONFIG.snipe_enabled {
+        if let Err(e) = buy_migrated_pool(selling, &migration, &logger).await {
+            logger.log(format!("{}", e).red().to_string());
+        }
+    }
+}
+
+/// Buy the first trades of a freshly created pool
+async fn buy_migrated_pool(selling: &ComprehensiveSelling, migration: &MigrationInfo, logger: &Logger) -> Result<()> {
+    let config = &*MIGRATION_CONFIG;
+    let pool_sol = migration.pool_quote_amount.unwrap_or(0) as f64 / 1_000_000_000.0;
+
+    if !is_buying_enabled() {
+        logger.log(format!("Buying disabled, skipping migrated {}", migration.mint).yellow().to_string());
+        return Ok(());
+    }
+    if comprehensive_selling::get_bought_token(&migration.mint).is_some() {
+        return Ok(());
+    }
+    if pool_sol < config.min_pool_sol {
+        logger.log(format!(
+            "Skipping {}: pool seeded with {:.2} SOL < {} SOL",
+            migration.mint, pool_sol, config.min_pool_sol
+        ).yellow().to_string());
+        return Ok(());
+    }
+
+    let mut buy_amount_sol = config.buy_amount_sol;
+    // Known when the launch was seen by the sniper stream and is still followed
+    if let Some(creator) = creator_registry::launch_creator(&migration.mint) {
+        match creator_registry::evaluate_creator(&creator) {
+            CreatorVerdict::Blocked(reason) => {
+                logger.log(format!("Rejected migrated {}: creator {} blocked, {}", migration.mint, creator, reason).yellow().to_string());
+                return Ok(());
+            }
+            CreatorVerdict::Preferred(reason) => {
+                buy_amount_sol *= creator_registry::CREATOR_CONFIG.preferred_multiplier;
+                logger.log(format!("Preferred creator {} ({}), buying {} SOL", creator, reason, buy_amount_sol));
+            }
+            CreatorVerdict::Unknown | CreatorVerdict::Neutral => {}
+        }
+    }
+
+    // The pool's opening reserves price the entry
+    let mut trade_info = TradeInfoFromToken {
+        dex_type: DexType::PumpSwap,
+        slot: migration.slot,
+        signature: migration.signature.clone(),
+        mint: migration.mint.clone(),
+        is_buy: true,
+        pool: migration.pool.clone(),
+        pool_base_token_reserves: migration.pool_base_amount,
+        pool_quote_token_reserves: migration.pool_quote_amount,
+        ..Default::default()
+    };
+    paper::observe_trade(&trade_info);
+
+    if config.rug_check.enabled {
+        match rug_check::check_mint(selling.app_state(), &config.rug_check, &trade_info).await {
+            Ok(verdict) if !risk_management::veto_rug_check(&verdict) => {
+                logger.log(format!("Rug check {}: {}", migration.mint, verdict.summary()));
+            }
+            Ok(verdict) => {
+                logger.log(format!("Rejected migrated {}: {}", migration.mint, verdict.summary()).yellow().to_string());
+                return Ok(());
+            }
+            Err(e) => {
+                logger.log(format!("Rejected migrated {}: rug check failed, {}", migration.mint, e).yellow().to_string());
+                return Ok(());
+            }
+        }
+    }
+
+    let mut exposure = match risk_management::approve_new_position(&migration.mint, None, &SwapProtocol::PumpSwap) {
+        Ok(exposure) => exposure,
+        Err(status) => {
+            logger.log(format!("Risk engine blocked {}: {}", migration.mint, status).yellow().to_string());
+            return Ok(());
+        }
+    };
+    if let Some(max_position_sol) = exposure.max_size() {
+        buy_amount_sol = buy_amount_sol.min(max_position_sol);
+    }
+    if buy_amount_sol <= 0.0 {
+        return Ok(());
+    }
+    exposure.resize(buy_amount_sol);
+
+    // Forget buys whose positions have been closed
+    MIGRATION_BUYS.retain(|mint, _| comprehensive_selling::get_bought_token(mint).is_some());
+    if MIGRATION_BUYS.len() >= config.max_positions {
+        logger.log(format!("Max {} migration positions open, skipping {}", config.max_positions, migration.mint).yellow().to_string());
+        return Ok(());
+    }
+    if MIGRATION_BUYS.insert(migration.mint.clone(), buy_amount_sol).is_some() {
+        return Ok(());
+    }
+
+    let mint_pubkey = Pubkey::from_str(&migration.mint)
+        .map_err(|e| anyhow!("Invalid mint {}: {}", migration.mint, e))?;
+    let amount = (buy_amount_sol * 1_000_000_000.0) as u64;
+    let signature = match execute_swap(
+        &SwapProtocol::PumpSwap,
+        SwapDirection::Buy,
+        &mint_pubkey,
+        amount,
+        selling.swap_config().slippage,
+    ).await {
+        Ok(signature) => signature,
+        Err(e) => {
+            MIGRATION_BUYS.remove(&migration.mint);
+            return Err(anyhow!("Migration buy of {} failed: {}", migration.mint, e));
+        }
+    };
+    logger.log(format!("Bought migrated {} for {} SOL: {}", migration.mint, buy_amount_sol, signature).green().to_string());
+    risk_management::record_new_position();
+
+    trade_info.signature = signature.clone();
+    trade_info.sol_amount = Some(amount);
+    if let Err(e) = telegram::send_trade_notification(&trade_info, "PumpSwap", "MIGRATION BUY").await {
+        logger.log(format!("Failed to send Telegram notification: {}", e).yellow().to_string());
+    }
+
+    let monitoring = selling.start_monitoring(&migration.mint, &signature, trade_info, SizingDecision {
+        amount_sol: buy_amount_sol,
+        reason: format!("migration buy, pool seeded with {:.2} SOL", pool_sol),
+    }).await;
+    // The position now carries the exposure
+    drop(exposure);
+    monitoring
+}
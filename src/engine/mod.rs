@@ -1,6 +1,20 @@
 pub mod copy_trading;
 pub mod monitor;
+pub mod backtest;
+pub mod circuit_breaker;
 pub mod comprehensive_selling;
+pub mod creator_registry;
+pub mod dump_detector;
+pub mod journal;
+pub mod launch_filter;
+pub mod migration;
+pub mod paper;
 pub mod risk_management;
+pub mod position_sizing;
+pub mod position_store;
+pub mod rug_check;
+pub mod sniper;
+pub mod target_profiles;
+pub mod target_scoring;
 pub mod swap;
 pub mod transaction_parser;
@@ -31,4 +31,157 @@ pub struct TokenTrackingInfo {
     pub top_pnl: f64,
     pub last_sell_time: Instant,
     pub completed_intervals: HashSet<String>,
-}
\ No newline at end of file
+}
+
+impl RetracementLevel {
+    /// Key under which a fired level is stored in `TokenTrackingInfo::completed_intervals`
+    pub fn key(&self) -> String {
+        format!("retracement_{}_{}", self.threshold, self.percentage)
+    }
+}
+
+impl TokenTrackingInfo {
+    pub fn new() -> Self {
+        Self {
+            top_pnl: 0.0,
+            last_sell_time: Instant::now(),
+            completed_intervals: HashSet::new(),
+        }
+    }
+
+    /// Mark a level as fired so it never triggers again for this position
+    pub fn complete(&mut self, level: &RetracementLevel) {
+        self.completed_intervals.insert(level.key());
+        self.last_sell_time = Instant::now();
+    }
+}
+
+impl Default for TokenTrackingInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse a retracement ladder from `threshold:percentage:sell_amount` triples, e.g. `20:10:50,50:15:50,100:20:100`
+///
+/// A level arms once PnL has reached `threshold`% and fires when PnL falls `percentage` points below
+/// the top PnL, selling `sell_amount`% of what is left of the position.
+pub fn parse_retracement_levels(value: &str) -> Result<Vec<RetracementLevel>, String> {
+    let mut levels = Vec::new();
+
+    for triple in value.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        let parts: Vec<&str> = triple.split(':').map(str::trim).collect();
+        if parts.len() != 3 {
+            return Err(format!("expected threshold:percentage:sell_amount, got {}", triple));
+        }
+        let parse = |s: &str| s.parse::<u64>().map_err(|e| format!("invalid number in {}: {}", triple, e));
+        let level = RetracementLevel {
+            threshold: parse(parts[0])?,
+            percentage: parse(parts[1])?,
+            sell_amount: parse(parts[2])?,
+        };
+        if level.percentage == 0 || level.sell_amount == 0 || level.sell_amount > 100 {
+            return Err(format!("level out of range: {}", triple));
+        }
+        // Levels are keyed by threshold and pullback, a second one would never fire
+        if levels.iter().any(|l: &RetracementLevel| l.key() == level.key()) {
+            return Err(format!("duplicate level: {}", triple));
+        }
+        levels.push(level);
+    }
+
+    // Highest threshold first so the deepest armed level is considered before shallower ones
+    levels.sort_by_key(|level| std::cmp::Reverse(level.threshold));
+    Ok(levels)
+}
+
+/// Update `top_pnl` with the current PnL and return the level that should fire now, if any
+///
+/// The returned level is not marked as completed; call `TokenTrackingInfo::complete` once the sell landed.
+pub fn check_retracement_levels(
+    tracking: &mut TokenTrackingInfo,
+    current_pnl: f64,
+    levels: &[RetracementLevel],
+) -> Option<RetracementLevel> {
+    if current_pnl > tracking.top_pnl {
+        tracking.top_pnl = current_pnl;
+    }
+
+    let pullback = tracking.top_pnl - current_pnl;
+    levels
+        .iter()
+        .find(|level| {
+            tracking.top_pnl >= level.threshold as f64
+                && pullback >= level.percentage as f64
+                && !tracking.completed_intervals.contains(&level.key())
+        })
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_sorts_levels() {
+        let levels = parse_retracement_levels("20:10:50, 100:20:100,50:15:50").unwrap();
+        let thresholds: Vec<u64> = levels.iter().map(|l| l.threshold).collect();
+        assert_eq!(thresholds, vec![100, 50, 20]);
+        assert_eq!(levels[2].percentage, 10);
+        assert_eq!(levels[2].sell_amount, 50);
+        assert!(parse_retracement_levels("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_levels() {
+        for value in ["20:10", "20:10:50:1", "a:10:50", "20:-1:50", "20:0:50", "20:10:0", "20:10:150"] {
+            assert!(parse_retracement_levels(value).is_err(), "{} should be rejected", value);
+        }
+    }
+
+    #[test]
+    fn rejects_duplicate_levels() {
+        assert!(parse_retracement_levels("20:10:50,50:15:100,20:10:100").is_err());
+        // Same threshold with another pullback is a different level
+        assert_eq!(parse_retracement_levels("20:10:50,20:5:25").unwrap().len(), 2);
+    }
+
+    /// Walk a PnL path, selling `sell_amount`% of what is left whenever a level fires
+    ///
+    /// Stops once the position is sold out. Returns the fired levels as (step, threshold) and the % left.
+    fn replay(levels: &str, path: &[f64]) -> (Vec<(usize, u64)>, f64) {
+        let levels = parse_retracement_levels(levels).unwrap();
+        let mut tracking = TokenTrackingInfo::new();
+        let mut remaining = 100.0;
+        let mut fired = Vec::new();
+        for (step, pnl) in path.iter().enumerate() {
+            if remaining <= 0.0 {
+                break;
+            }
+            if let Some(level) = check_retracement_levels(&mut tracking, *pnl, &levels) {
+                remaining -= remaining * level.sell_amount as f64 / 100.0;
+                tracking.complete(&level);
+                fired.push((step, level.threshold));
+            }
+        }
+        (fired, remaining)
+    }
+
+    #[test]
+    fn price_paths() {
+        let ladder = "20:10:50,50:15:100";
+        let cases: [(&str, &[f64], &[(usize, u64)], f64); 6] = [
+            ("no trigger below the threshold", &[5.0, 15.0, 2.0, -10.0], &[], 100.0),
+            ("rise and retrace", &[5.0, 15.0, 25.0, 18.0, 14.0], &[(4, 20)], 50.0),
+            ("retrace below entry", &[10.0, 30.0, 25.0, 15.0, -5.0, -20.0], &[(3, 20)], 50.0),
+            ("a level fires once", &[25.0, 14.0, 13.0, 25.0, 12.0], &[(1, 20)], 50.0),
+            ("recovery after a partial exit", &[25.0, 14.0, 60.0, 50.0, 44.0], &[(1, 20), (4, 50)], 0.0),
+            ("deepest armed level first", &[60.0, 44.0, 30.0], &[(1, 50)], 0.0),
+        ];
+        for (name, path, expected, expected_remaining) in cases {
+            let (fired, remaining) = replay(ladder, path);
+            assert_eq!(fired, expected, "{}", name);
+            assert!((remaining - expected_remaining).abs() < 1e-9, "{}: {} left", name, remaining);
+        }
+    }
+}
@@ -0,0 +1,483 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use colored::Colorize;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use once_cell::sync::OnceCell;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio::time::sleep;
+
+use crate::engine::swap::{SwapDirection, SwapProtocol};
+use crate::engine::transaction_parser::TradeInfoFromToken;
+use crate::error::ClientError;
+use crate::library::logger::Logger;
+use crate::tx_processor::fill::{Fill, LAMPORTS_PER_SIGNATURE};
+use crate::tx_processor::tx::{get_unit_limit, get_unit_price};
+
+// A fresh PumpFun bonding curve, assumed until a token's first trade is observed
+pub const PUMP_FUN_INITIAL_VIRTUAL_SOL: u64 = 30_000_000_000;
+pub const PUMP_FUN_INITIAL_VIRTUAL_TOKENS: u64 = 1_073_000_000_000_000;
+const ATA_RENT_LAMPORTS: u64 = 2_039_280;
+
+lazy_static! {
+    static ref PAPER_MODE: AtomicBool = AtomicBool::new(false);
+    pub static ref PAPER_CONFIG: PaperConfig = PaperConfig::from_env();
+    static ref PAPER_BOOK: Mutex<Option<PaperBook>> = Mutex::new(None);
+    // Latest observed reserves per mint as (SOL lamports, raw tokens)
+    static ref POOL_RESERVES: DashMap<String, (u64, u64)> = DashMap::new();
+    // Simulated fills by paper signature, picked up by `fill::fetch_fill`
+    static ref PAPER_FILLS: DashMap<String, Fill> = DashMap::new();
+    static ref PAPER_SIGNATURES: AtomicU64 = AtomicU64::new(0);
+}
+
+// Snapshots of the paper book waiting to be saved, written off the runtime
+static PAPER_BOOK_WRITER: OnceCell<UnboundedSender<PaperBook>> = OnceCell::new();
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SlippageModel {
+    Amm,   // price impact of the order against the observed reserves
+    Fixed, // the quoted spot price
+}
+
+/// How paper fills are simulated
+#[derive(Debug, Clone)]
+pub struct PaperConfig {
+    pub starting_sol: f64,
+    pub latency_ms: u64,         // delay between sending and the simulated landing
+    pub latency_jitter_ms: u64,  // random extra delay, up to this much
+    pub slippage_model: SlippageModel,
+    pub extra_slippage_bps: u64, // charged on top of the model for competing flow
+    pub venue_fee_bps: u64,
+    pub book_file: String,
+}
+
+impl PaperConfig {
+    /// Load from `PAPER_STARTING_SOL`, `PAPER_LATENCY_MS`, `PAPER_LATENCY_JITTER_MS`, `PAPER_SLIPPAGE_MODEL`,
+    /// `PAPER_SLIPPAGE_BPS`, `PAPER_VENUE_FEE_BPS` and `PAPER_BOOK_FILE`
+    pub fn from_env() -> Self {
+        Self {
+            starting_sol: std::env::var("PAPER_STARTING_SOL")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(10.0),
+            latency_ms: std::env::var("PAPER_LATENCY_MS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(400),
+            latency_jitter_ms: std::env::var("PAPER_LATENCY_JITTER_MS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(200),
+            slippage_model: match std::env::var("PAPER_SLIPPAGE_MODEL").unwrap_or_default().to_lowercase().as_str() {
+                "fixed" => SlippageModel::Fixed,
+                _ => SlippageModel::Amm,
+            },
+            extra_slippage_bps: std::env::var("PAPER_SLIPPAGE_BPS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(50),
+            venue_fee_bps: std::env::var("PAPER_VENUE_FEE_BPS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(100),
+            book_file: std::env::var("PAPER_BOOK_FILE").unwrap_or_else(|_| "paper_book.json".to_string()),
+        }
+    }
+}
+
+/// The paper wallet: SOL and token balances moved only by simulated fills
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PaperBook {
+    pub sol_lamports: u64,
+    pub tokens: HashMap<String, u64>, // raw balance by mint, an entry means the token account exists
+    pub fees_sol: f64,                // network fees and rent paid
+    pub venue_fees_sol: f64,
+    pub trades: usize,
+}
+
+/// What a simulated order fills at
+#[derive(Debug, Clone, Copy)]
+pub struct SimulatedFill {
+    pub amount_out: u64,            // raw tokens on a buy, lamports on a sell, net of the venue fee
+    pub quoted_out: u64,            // the same at the pre-trade spot price
+    pub venue_fee: u64,             // lamports
+    pub reserves_after: (u64, u64), // (SOL lamports, raw tokens)
+}
+
+/// Replace the landing layer with the simulator for the rest of the run
+pub fn enable_paper_mode() {
+    PAPER_MODE.store(true, Ordering::SeqCst);
+}
+
+pub fn is_paper_mode() -> bool {
+    PAPER_MODE.load(Ordering::SeqCst)
+}
+
+/// `path` with its file name prefixed by `paper_` in paper mode, so paper runs never touch the live books
+pub fn paper_path(path: &str) -> String {
+    if !is_paper_mode() {
+        return path.to_string();
+    }
+    let path = Path::new(path);
+    let file_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    path.with_file_name(format!("paper_{}", file_name)).to_string_lossy().to_string()
+}
+
+/// Remember the reserves a trade left behind; simulated fills price against them
+pub fn observe_trade(trade: &TradeInfoFromToken) {
+    if !is_paper_mode() {
+        return;
+    }
+    if let Some(reserves) = trade.pool_reserves().filter(|(sol, tokens)| *sol > 0 && *tokens > 0) {
+        POOL_RESERVES.insert(trade.mint.clone(), reserves);
+    }
+}
+
+/// Fill `amount` (lamports on a buy, raw tokens on a sell) against `reserves`
+pub fn simulate_fill(
+    reserves: (u64, u64),
+    direction: &SwapDirection,
+    amount: u64,
+    model: &SlippageModel,
+    extra_slippage_bps: u64,
+    venue_fee_bps: u64,
+) -> Option<SimulatedFill> {
+    let (sol_reserve, token_reserve) = (reserves.0 as u128, reserves.1 as u128);
+    if sol_reserve == 0 || token_reserve == 0 || amount == 0 {
+        return None;
+    }
+    let amount = amount as u128;
+    let keep = |value: u128, bps: u64| value * 10_000u128.saturating_sub(bps as u128) / 10_000;
+
+    match direction {
+        SwapDirection::Buy => {
+            let venue_fee = amount * venue_fee_bps as u128 / 10_000;
+            let sol_in = amount - venue_fee;
+            let quoted_out = sol_in * token_reserve / sol_reserve;
+            let curve_out = token_reserve * sol_in / (sol_reserve + sol_in);
+            let modelled = match model {
+                SlippageModel::Amm => curve_out,
+                SlippageModel::Fixed => quoted_out,
+            };
+            let amount_out = keep(modelled, extra_slippage_bps).min(token_reserve.saturating_sub(1));
+            Some(SimulatedFill {
+                amount_out: amount_out as u64,
+                quoted_out: quoted_out as u64,
+                venue_fee: venue_fee as u64,
+                reserves_after: ((sol_reserve + sol_in) as u64, (token_reserve - amount_out) as u64),
+            })
+        }
+        SwapDirection::Sell => {
+            let quoted_gross = amount * sol_reserve / token_reserve;
+            let curve_gross = sol_reserve * amount / (token_reserve + amount);
+            let modelled = match model {
+                SlippageModel::Amm => curve_gross,
+                SlippageModel::Fixed => quoted_gross,
+            };
+            let gross = keep(modelled, extra_slippage_bps).min(sol_reserve.saturating_sub(1));
+            let venue_fee = gross * venue_fee_bps as u128 / 10_000;
+            Some(SimulatedFill {
+                amount_out: (gross - venue_fee) as u64,
+                quoted_out: keep(quoted_gross, venue_fee_bps) as u64,
+                venue_fee: venue_fee as u64,
+                reserves_after: ((sol_reserve - gross) as u64, (token_reserve + amount) as u64),
+            })
+        }
+    }
+}
+
+fn paper_book() -> MutexGuard<'static, Option<PaperBook>> {
+    PAPER_BOOK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// The paper book, loaded on first use
+fn load_paper_book(book: &mut Option<PaperBook>) -> &mut PaperBook {
+    let config = &*PAPER_CONFIG;
+    book.get_or_insert_with(|| {
+        std::fs::read_to_string(&config.book_file)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_else(|| PaperBook {
+                sol_lamports: (config.starting_sol * 1_000_000_000.0) as u64,
+                ..Default::default()
+            })
+    })
+}
+
+/// Read the paper book
+fn read_paper_book<R>(f: impl FnOnce(&PaperBook) -> R) -> R {
+    let mut guard = paper_book();
+    f(load_paper_book(&mut guard))
+}
+
+/// Change the paper book, queueing a save when `f` succeeds
+fn update_paper_book<R, E>(f: impl FnOnce(&mut PaperBook) -> Result<R, E>) -> Result<R, E> {
+    let mut guard = paper_book();
+    let book = load_paper_book(&mut guard);
+    let result = f(book)?;
+    queue_save(book.clone());
+    Ok(result)
+}
+
+/// Queue a snapshot of the paper book, starting the writer task on first use
+fn queue_save(book: PaperBook) {
+    let writer = PAPER_BOOK_WRITER.get_or_init(|| {
+        let (sender, mut receiver) = unbounded_channel::<PaperBook>();
+        tokio::spawn(async move {
+            let logger = Logger::new("[PAPER] => ".yellow().to_string());
+            while let Some(mut book) = receiver.recv().await {
+                // Only the latest snapshot matters, skip the ones queued behind it
+                while let Ok(newer) = receiver.try_recv() {
+                    book = newer;
+                }
+                let result = tokio::task::spawn_blocking(move || save_paper_book(&book)).await;
+                match result {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => {
+                        logger.log(e.red().to_string());
+                    }
+                    Err(e) => {
+                        logger.log(format!("Paper book write panicked: {}", e).red().to_string());
+                    }
+                }
+            }
+        });
+        sender
+    });
+    let _ = writer.send(book);
+}
+
+/// Write the paper book to `PAPER_BOOK_FILE`; blocks, trading tasks go through `queue_save`
+fn save_paper_book(book: &PaperBook) -> Result<(), String> {
+    let config = &*PAPER_CONFIG;
+    serde_json::to_string_pretty(book)
+        .map_err(|e| e.to_string())
+        .and_then(|data| std::fs::write(&config.book_file, data).map_err(|e| e.to_string()))
+        .map_err(|e| format!("Failed to save paper book {}: {}", config.book_file, e))
+}
+
+/// SOL in the paper wallet
+pub fn sol_balance() -> f64 {
+    read_paper_book(|book| book.sol_lamports as f64 / 1_000_000_000.0)
+}
+
+/// Raw paper balance of `mint`
+pub fn token_balance(mint: &str) -> u64 {
+    read_paper_book(|book| book.tokens.get(mint).copied().unwrap_or(0))
+}
+
+/// One line describing the paper wallet
+pub fn summary() -> String {
+    read_paper_book(|book| format!(
+        "Paper wallet {:.4} SOL | {} tokens held | {} trades | fees {:.4} SOL | venue fees {:.4} SOL",
+        book.sol_lamports as f64 / 1_000_000_000.0,
+        book.tokens.values().filter(|balance| **balance > 0).count(),
+        book.trades,
+        book.fees_sol,
+        book.venue_fees_sol
+    ))
+}
+
+/// Base plus priority fee of one swap at the configured `UNIT_PRICE` and `UNIT_LIMIT`
+pub fn network_fee_lamports() -> u64 {
+    LAMPORTS_PER_SIGNATURE + get_unit_price() * get_unit_limit() as u64 / 1_000_000
+}
+
+/// The simulated fill of a paper signature, once
+pub fn take_fill(signature: &str) -> Option<Fill> {
+    PAPER_FILLS.remove(signature).map(|(_, fill)| fill)
+}
+
+/// Move a simulated fill of `amount` through the paper wallet, refusing what it cannot pay or deliver
+fn apply_fill(
+    book: &mut PaperBook,
+    direction: &SwapDirection,
+    mint: &str,
+    amount: u64,
+    simulated: &SimulatedFill,
+    network_fee: u64,
+) -> Result<Fill, ClientError> {
+    match direction {
+        SwapDirection::Buy => {
+            let rent = if book.tokens.contains_key(mint) { 0 } else { ATA_RENT_LAMPORTS };
+            let cost = amount + network_fee + rent;
+            if book.sol_lamports < cost {
+                return Err(ClientError::Other(format!(
+                    "Paper wallet holds {:.4} SOL, {:.4} SOL needed",
+                    book.sol_lamports as f64 / 1_000_000_000.0,
+                    cost as f64 / 1_000_000_000.0
+                )));
+            }
+            book.sol_lamports -= cost;
+            *book.tokens.entry(mint.to_string()).or_default() += simulated.amount_out;
+            book.fees_sol += (network_fee + rent) as f64 / 1_000_000_000.0;
+            book.venue_fees_sol += simulated.venue_fee as f64 / 1_000_000_000.0;
+            book.trades += 1;
+            Ok(Fill {
+                token_amount: simulated.amount_out,
+                sol_amount: amount as f64 / 1_000_000_000.0,
+                rent_sol: rent as f64 / 1_000_000_000.0,
+                ..Default::default()
+            })
+        }
+        SwapDirection::Sell => {
+            let held = book.tokens.get(mint).copied().unwrap_or(0);
+            if held < amount {
+                return Err(ClientError::Other(format!("Paper wallet holds {} of {}, {} to sell", held, mint, amount)));
+            }
+            // The token account stays open, like a real partial or full sell
+            book.tokens.insert(mint.to_string(), held - amount);
+            book.sol_lamports = (book.sol_lamports + simulated.amount_out).saturating_sub(network_fee);
+            book.fees_sol += network_fee as f64 / 1_000_000_000.0;
+            book.venue_fees_sol += simulated.venue_fee as f64 / 1_000_000_000.0;
+            book.trades += 1;
+            Ok(Fill {
+                token_amount: amount,
+                sol_amount: simulated.amount_out as f64 / 1_000_000_000.0,
+                ..Default::default()
+            })
+        }
+    }
+}
+
+/// Simulate one of our swaps in place of sending it; same contract as `execute_swap`
+///
+/// The order waits out the configured latency, then fills against the reserves observed by then and
+/// fails like the real swap would when the fill is worse than `slippage` basis points.
+pub async fn simulate_swap(
+    protocol: &SwapProtocol,
+    direction: &SwapDirection,
+    token_mint: &Pubkey,
+    amount: u64,
+    slippage: u64,
+) -> Result<String, ClientError> {
+    let config = &*PAPER_CONFIG;
+    if !matches!(protocol, SwapProtocol::PumpFun | SwapProtocol::PumpSwap) {
+        return Err(ClientError::Other(format!("Paper fills are not simulated for {:?}", protocol)));
+    }
+    let jitter = if config.latency_jitter_ms > 0 {
+        rand::thread_rng().gen_range(0..=config.latency_jitter_ms)
+    } else {
+        0
+    };
+    sleep(Duration::from_millis(config.latency_ms + jitter)).await;
+
+    let mint = token_mint.to_string();
+    let observed = POOL_RESERVES.get(&mint).map(|reserves| *reserves);
+    let reserves = match observed {
+        Some(reserves) => reserves,
+        // Only a launch buy may assume the fresh curve, anything else would fill at a made-up price
+        None if *protocol == SwapProtocol::PumpFun && *direction == SwapDirection::Buy => {
+            Logger::new("[PAPER] => ".yellow().to_string()).log(format!(
+                "No trade of {} observed yet, filling the buy against a fresh bonding curve",
+                mint
+            ).yellow().to_string());
+            (PUMP_FUN_INITIAL_VIRTUAL_SOL, PUMP_FUN_INITIAL_VIRTUAL_TOKENS)
+        }
+        None => return Err(ClientError::Other(format!("No observed reserves for {}, refusing to fill", mint))),
+    };
+    let simulated = simulate_fill(reserves, direction, amount, &config.slippage_model, config.extra_slippage_bps, config.venue_fee_bps)
+        .ok_or_else(|| ClientError::InvalidData(format!("Cannot simulate {:?} of {} for {}", direction, amount, mint)))?;
+    if simulated.amount_out == 0 {
+        return Err(ClientError::Other(format!("Paper {:?} of {} for {} fills nothing", direction, mint, amount)));
+    }
+    let min_out = simulated.quoted_out as u128 * 10_000u128.saturating_sub(slippage as u128) / 10_000;
+    if (simulated.amount_out as u128) < min_out {
+        return Err(ClientError::Other(format!(
+            "Paper {:?} of {} exceeded {} bps slippage: {} out, {} quoted",
+            direction, mint, slippage, simulated.amount_out, simulated.quoted_out
+        )));
+    }
+
+    let network_fee = network_fee_lamports();
+    let signature = format!(
+        "paper-{}-{}",
+        chrono::Utc::now().timestamp_millis(),
+        PAPER_SIGNATURES.fetch_add(1, Ordering::SeqCst)
+    );
+    let mut fill = update_paper_book(|book| apply_fill(book, direction, &mint, amount, &simulated, network_fee))?;
+
+    fill.signature = signature.clone();
+    fill.price = fill.sol_amount / (fill.token_amount as f64 / 1_000_000.0);
+    fill.base_fee_sol = LAMPORTS_PER_SIGNATURE as f64 / 1_000_000_000.0;
+    fill.priority_fee_sol = (network_fee - LAMPORTS_PER_SIGNATURE) as f64 / 1_000_000_000.0;
+    fill.venue_fee_sol = simulated.venue_fee as f64 / 1_000_000_000.0;
+    POOL_RESERVES.insert(mint, simulated.reserves_after);
+    PAPER_FILLS.insert(signature.clone(), fill);
+    Ok(signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 30 SOL against 1,000,000 tokens of 6 decimals
+    const RESERVES: (u64, u64) = (30_000_000_000, 1_000_000_000_000);
+
+    #[test]
+    fn amm_buy_pays_price_impact_and_fee() {
+        let fill = simulate_fill(RESERVES, &SwapDirection::Buy, 3_000_000_000, &SlippageModel::Amm, 0, 100).unwrap();
+        assert_eq!(fill.venue_fee, 30_000_000);
+        // 2.97 SOL in: quoted 99,000 tokens at spot, the curve gives 30 * 2.97 / 32.97 of the reserve
+        assert_eq!(fill.quoted_out, 99_000_000_000);
+        assert_eq!(fill.amount_out, 90_081_892_629);
+        assert_eq!(fill.reserves_after, (32_970_000_000, 1_000_000_000_000 - 90_081_892_629));
+    }
+
+    #[test]
+    fn extra_slippage_stays_within_its_bound() {
+        let base = simulate_fill(RESERVES, &SwapDirection::Sell, 10_000_000_000, &SlippageModel::Fixed, 0, 0).unwrap();
+        let slipped = simulate_fill(RESERVES, &SwapDirection::Sell, 10_000_000_000, &SlippageModel::Fixed, 50, 0).unwrap();
+        assert_eq!(base.amount_out, base.quoted_out);
+        assert_eq!(slipped.amount_out, base.amount_out * 9_950 / 10_000);
+        // The fixed model fills at spot, whatever the size
+        assert_eq!(base.amount_out, 300_000_000);
+    }
+
+    #[test]
+    fn sell_fee_comes_out_of_the_proceeds() {
+        let fill = simulate_fill(RESERVES, &SwapDirection::Sell, 10_000_000_000, &SlippageModel::Fixed, 0, 100).unwrap();
+        assert_eq!(fill.venue_fee, 3_000_000);
+        assert_eq!(fill.amount_out, 297_000_000);
+        assert_eq!(fill.quoted_out, 297_000_000);
+    }
+
+    #[test]
+    fn nothing_fills_without_reserves_or_amount() {
+        assert!(simulate_fill((0, 1_000), &SwapDirection::Buy, 1_000, &SlippageModel::Amm, 0, 0).is_none());
+        assert!(simulate_fill(RESERVES, &SwapDirection::Sell, 0, &SlippageModel::Amm, 0, 0).is_none());
+    }
+
+    #[test]
+    fn paper_wallet_refuses_what_it_cannot_cover() {
+        let simulated = simulate_fill(RESERVES, &SwapDirection::Buy, 1_000_000_000, &SlippageModel::Amm, 0, 100).unwrap();
+        let mut book = PaperBook { sol_lamports: 1_000_000_000, ..Default::default() };
+        // The buy itself fits, not with the fee and the token account rent on top
+        assert!(apply_fill(&mut book, &SwapDirection::Buy, "mint", 1_000_000_000, &simulated, 5_000).is_err());
+        assert_eq!(book.sol_lamports, 1_000_000_000);
+        assert!(book.tokens.is_empty());
+
+        book.sol_lamports = 2_000_000_000;
+        let fill = apply_fill(&mut book, &SwapDirection::Buy, "mint", 1_000_000_000, &simulated, 5_000).unwrap();
+        assert_eq!(fill.token_amount, simulated.amount_out);
+        assert_eq!(book.sol_lamports, 2_000_000_000 - 1_000_000_000 - 5_000 - ATA_RENT_LAMPORTS);
+
+        let held = book.tokens["mint"];
+        let sell = simulate_fill(RESERVES, &SwapDirection::Sell, held + 1, &SlippageModel::Amm, 0, 100).unwrap();
+        assert!(apply_fill(&mut book, &SwapDirection::Sell, "mint", held + 1, &sell, 5_000).is_err());
+        assert_eq!(book.tokens["mint"], held);
+    }
+
+    #[test]
+    fn buy_never_drains_the_token_reserve() {
+        let fill = simulate_fill((1_000, 1_000), &SwapDirection::Buy, u64::MAX, &SlippageModel::Fixed, 0, 0).unwrap();
+        assert_eq!(fill.amount_out, 999);
+    }
+}
@@ -0,0 +1,340 @@
+use std::sync::Arc;
+use anyhow::{anyhow, Result};
+use anchor_client::solana_sdk::signature::Signer;
+use clap::ValueEnum;
+use colored::Colorize;
+
+use crate::library::config::{AppState, SwapConfig};
+use crate::library::logger::Logger;
+use crate::engine::copy_trading::ConsensusConfig;
+use crate::engine::creator_registry::CreatorVerdict;
+use crate::engine::paper;
+use crate::engine::swap::SwapInType;
+
+/// How the SOL amount of a copy buy is chosen
+#[derive(Debug, Clone, PartialEq)]
+pub enum SizingMode {
+    /// `TOKEN_AMOUNT` SOL, or `TOKEN_AMOUNT`% of our wallet when the amount type is `pct`
+    Fixed,
+    /// A percentage of the SOL the target spent
+    Proportional,
+    /// A fixed amount picked from tiers keyed on the target's buy size
+    Tiered,
+}
+
+impl std::str::FromStr for SizingMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "fixed" => Ok(SizingMode::Fixed),
+            "proportional" => Ok(SizingMode::Proportional),
+            "tiered" => Ok(SizingMode::Tiered),
+            _ => Err(format!("Invalid sizing mode: {}", s)),
+        }
+    }
+}
+
+/// Buy `amount_sol` when the target spent at least `min_target_sol`
+#[derive(Debug, Clone, Copy)]
+pub struct SizeTier {
+    pub min_target_sol: f64,
+    pub amount_sol: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct PositionSizingConfig {
+    pub mode: SizingMode,
+    pub proportion_percentage: f64, // 10.0 = buy 10% of what the target spent
+    pub tiers: Vec<SizeTier>,       // sorted by min_target_sol
+    pub min_amount_sol: f64,
+    pub max_amount_sol: f64,        // 0.0 = no upper clamp
+}
+
+impl Default for PositionSizingConfig {
+    fn default() -> Self {
+        Self {
+            mode: SizingMode::Fixed,
+            proportion_percentage: 10.0,
+            tiers: Vec::new(),
+            min_amount_sol: 0.0,
+            max_amount_sol: 0.0,
+        }
+    }
+}
+
+/// The amount chosen for a buy and why
+#[derive(Debug, Clone, Default)]
+pub struct SizingDecision {
+    pub amount_sol: f64,
+    pub reason: String,
+}
+
+impl PositionSizingConfig {
+    /// Load sizing from `SIZING_MODE`, `SIZING_PROPORTION_PERCENTAGE`, `SIZING_TIERS`,
+    /// `MIN_BUY_AMOUNT` and `MAX_BUY_AMOUNT`
+    pub fn from_env() -> Self {
+        let logger = Logger::new("[POSITION-SIZING] => ".blue().to_string());
+        let mut config = Self::default();
+
+        if let Ok(mode) = std::env::var("SIZING_MODE") {
+            match mode.parse::<SizingMode>() {
+                Ok(mode) => config.mode = mode,
+                Err(e) => {
+                    logger.log(format!("Ignoring SIZING_MODE: {}", e).red().to_string());
+                }
+            }
+        }
+
+        config.proportion_percentage = std::env::var("SIZING_PROPORTION_PERCENTAGE")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(config.proportion_percentage);
+
+        if let Ok(tiers) = std::env::var("SIZING_TIERS") {
+            match parse_size_tiers(&tiers) {
+                Ok(parsed) => config.tiers = parsed,
+                Err(e) => {
+                    logger.log(format!("Ignoring SIZING_TIERS: {}", e).red().to_string());
+                }
+            }
+        }
+        if config.mode == SizingMode::Tiered && config.tiers.is_empty() {
+            logger.log("SIZING_MODE=tiered without SIZING_TIERS, falling back to fixed sizing".red().to_string());
+            config.mode = SizingMode::Fixed;
+        }
+
+        config.min_amount_sol = std::env::var("MIN_BUY_AMOUNT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(config.min_amount_sol);
+
+        config.max_amount_sol = std::env::var("MAX_BUY_AMOUNT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(config.max_amount_sol);
+
+        config
+    }
+
+    /// Pick the SOL amount for copying a buy of `target_sol` SOL
+    ///
+    /// `wallet_balance_sol` is only needed when the swap amount type is `pct`.
+    pub fn calculate(
+        &self,
+        swap_config: &SwapConfig,
+        target_sol: f64,
+        wallet_balance_sol: Option<f64>,
+    ) -> Result<SizingDecision> {
+        let (raw_amount, mut reason) = match self.mode {
+            SizingMode::Fixed => match swap_config.in_type {
+                SwapInType::Qty => (swap_config.amount_in, format!("fixed {} SOL", swap_config.amount_in)),
+                SwapInType::Pct => {
+                    let balance = wallet_balance_sol
+                        .ok_or_else(|| anyhow!("wallet balance required for percentage sizing"))?;
+                    (
+                        balance * swap_config.amount_in / 100.0,
+                        format!("{}% of wallet balance {:.4} SOL", swap_config.amount_in, balance),
+                    )
+                }
+            },
+            SizingMode::Proportional => (
+                target_sol * self.proportion_percentage / 100.0,
+                format!("{}% of target spend {:.4} SOL", self.proportion_percentage, target_sol),
+            ),
+            SizingMode::Tiered => match self.tiers.iter().rev().find(|tier| target_sol >= tier.min_target_sol) {
+                Some(tier) => (
+                    tier.amount_sol,
+                    format!("tier >= {} SOL for target spend {:.4} SOL", tier.min_target_sol, target_sol),
+                ),
+                None => {
+                    return Ok(SizingDecision {
+                        amount_sol: 0.0,
+                        reason: format!("target spend {:.4} SOL below the lowest tier", target_sol),
+                    });
+                }
+            },
+        };
+
+        let mut amount = raw_amount;
+        if amount < self.min_amount_sol {
+            amount = self.min_amount_sol;
+            reason.push_str(&format!(", raised to min {} SOL", self.min_amount_sol));
+        }
+        if self.max_amount_sol > 0.0 && amount > self.max_amount_sol {
+            amount = self.max_amount_sol;
+            reason.push_str(&format!(", capped at max {} SOL", self.max_amount_sol));
+        }
+
+        Ok(SizingDecision { amount_sol: amount.max(0.0), reason })
+    }
+
+    /// Scale a buy for `agreeing` targets and a preferred creator, then cap it
+    ///
+    /// Multipliers never take a buy past the configured maximum; the risk engine's `risk_cap` applies last.
+    pub fn adjust(
+        &self,
+        sizing: &mut SizingDecision,
+        consensus: &ConsensusConfig,
+        agreeing: usize,
+        creator_verdict: &CreatorVerdict,
+        preferred_multiplier: f64,
+        risk_cap: Option<f64>,
+    ) {
+        if agreeing > 1 && consensus.scale_percentage > 0.0 {
+            let multiplier = consensus.multiplier(agreeing);
+            sizing.amount_sol *= multiplier;
+            sizing.reason.push_str(&format!(", x{:.2} for {} agreeing targets", multiplier, agreeing));
+        }
+        if let CreatorVerdict::Preferred(reason) = creator_verdict {
+            sizing.amount_sol *= preferred_multiplier;
+            sizing.reason.push_str(&format!(", x{} for preferred creator ({})", preferred_multiplier, reason));
+        }
+        if self.max_amount_sol > 0.0 && sizing.amount_sol > self.max_amount_sol {
+            sizing.amount_sol = self.max_amount_sol;
+            sizing.reason.push_str(&format!(", capped at max {} SOL", self.max_amount_sol));
+        }
+        if let Some(risk_cap) = risk_cap {
+            if sizing.amount_sol > risk_cap {
+                sizing.amount_sol = risk_cap;
+                sizing.reason.push_str(&format!(", capped at {:.4} SOL by the risk engine", risk_cap));
+            }
+        }
+    }
+
+    /// Whether `calculate` needs our wallet balance
+    pub fn needs_wallet_balance(&self, swap_config: &SwapConfig) -> bool {
+        self.mode == SizingMode::Fixed && swap_config.in_type == SwapInType::Pct
+    }
+}
+
+/// Parse `min_target_sol:amount_sol` pairs, e.g. `0.5:0.01,2:0.05,10:0.1`
+pub fn parse_size_tiers(value: &str) -> Result<Vec<SizeTier>> {
+    let mut tiers = Vec::new();
+
+    for pair in value.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (min_target, amount) = pair
+            .split_once(':')
+            .ok_or_else(|| anyhow!("expected min_target_sol:amount_sol, got {}", pair))?;
+        tiers.push(SizeTier {
+            min_target_sol: min_target.trim().parse::<f64>()
+                .map_err(|e| anyhow!("invalid target size in {}: {}", pair, e))?,
+            amount_sol: amount.trim().parse::<f64>()
+                .map_err(|e| anyhow!("invalid amount in {}: {}", pair, e))?,
+        });
+    }
+
+    tiers.sort_by(|a, b| a.min_target_sol.total_cmp(&b.min_target_sol));
+    Ok(tiers)
+}
+
+/// Parse `SwapInType` from its serialized name (`qty` / `pct`)
+pub fn parse_in_type(value: &str) -> Option<SwapInType> {
+    SwapInType::from_str(value, true).ok()
+}
+
+/// Our wallet's SOL balance
+pub async fn get_wallet_balance_sol(app_state: &Arc<AppState>) -> Result<f64> {
+    if paper::is_paper_mode() {
+        return Ok(paper::sol_balance());
+    }
+    let wallet = app_state.wallet.try_pubkey()
+        .map_err(|e| anyhow!("Failed to get wallet pubkey: {}", e))?;
+    let lamports = app_state.rpc_nonblocking_client
+        .get_balance(&wallet)
+        .await
+        .map_err(|e| anyhow!("Failed to get wallet balance: {}", e))?;
+    Ok(lamports as f64 / 1_000_000_000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::swap::SwapDirection;
+
+    fn swap_config(in_type: SwapInType, amount_in: f64) -> SwapConfig {
+        SwapConfig { swap_direction: SwapDirection::Buy, in_type, amount_in, slippage: 1_000 }
+    }
+
+    fn tiers() -> Vec<SizeTier> {
+        parse_size_tiers("0.5:0.01,2:0.05,10:0.1").unwrap()
+    }
+
+    #[test]
+    fn calculate_sizes_each_mode() {
+        let fixed = PositionSizingConfig::default();
+        let proportional = PositionSizingConfig { mode: SizingMode::Proportional, ..Default::default() };
+        let tiered = PositionSizingConfig { mode: SizingMode::Tiered, tiers: tiers(), ..Default::default() };
+        let clamped = PositionSizingConfig {
+            mode: SizingMode::Proportional,
+            min_amount_sol: 0.02,
+            max_amount_sol: 0.5,
+            ..Default::default()
+        };
+        let qty = swap_config(SwapInType::Qty, 0.2);
+        let pct = swap_config(SwapInType::Pct, 5.0);
+
+        // (config, swap config, target spend, wallet balance, expected SOL)
+        let cases = [
+            (&fixed, &qty, 3.0, None, 0.2),
+            (&fixed, &pct, 3.0, Some(4.0), 0.2),
+            (&proportional, &qty, 3.0, None, 0.3),
+            (&tiered, &qty, 0.1, None, 0.0),
+            (&tiered, &qty, 0.5, None, 0.01),
+            (&tiered, &qty, 9.9, None, 0.05),
+            (&tiered, &qty, 50.0, None, 0.1),
+            (&clamped, &qty, 0.1, None, 0.02),
+            (&clamped, &qty, 20.0, None, 0.5),
+        ];
+        for (config, swap_config, target_sol, balance, expected) in cases {
+            let sizing = config.calculate(swap_config, target_sol, balance).unwrap();
+            assert!(
+                (sizing.amount_sol - expected).abs() < 1e-9,
+                "{:?} for {} SOL: got {} ({})",
+                config.mode, target_sol, sizing.amount_sol, sizing.reason
+            );
+        }
+
+        assert!(fixed.calculate(&pct, 3.0, None).is_err());
+    }
+
+    #[test]
+    fn adjust_scales_then_caps() {
+        let config = PositionSizingConfig { max_amount_sol: 1.0, ..Default::default() };
+        let consensus = ConsensusConfig { scale_percentage: 50.0, max_multiplier: 3.0, ..Default::default() };
+        let preferred = CreatorVerdict::Preferred("migrates".to_string());
+
+        // (base SOL, agreeing targets, creator verdict, risk cap, expected SOL)
+        let cases = [
+            (0.1, 1, &CreatorVerdict::Neutral, None, 0.1),
+            (0.1, 3, &CreatorVerdict::Neutral, None, 0.2),
+            (0.1, 9, &CreatorVerdict::Neutral, None, 0.3),
+            (0.1, 1, &preferred, None, 0.2),
+            (0.1, 3, &preferred, None, 0.4),
+            (0.4, 3, &preferred, None, 1.0),
+            (0.4, 3, &preferred, Some(0.25), 0.25),
+            (0.1, 1, &CreatorVerdict::Neutral, Some(0.25), 0.1),
+        ];
+        for (base, agreeing, verdict, risk_cap, expected) in cases {
+            let mut sizing = SizingDecision { amount_sol: base, reason: String::new() };
+            config.adjust(&mut sizing, &consensus, agreeing, verdict, 2.0, risk_cap);
+            assert!(
+                (sizing.amount_sol - expected).abs() < 1e-9,
+                "{} SOL, {} agreeing, {:?}: got {} ({})",
+                base, agreeing, verdict, sizing.amount_sol, sizing.reason
+            );
+        }
+    }
+
+    #[test]
+    fn parse_size_tiers_sorts_and_rejects_malformed_pairs() {
+        let parsed = parse_size_tiers(" 10:0.1, 0.5:0.01 ,2:0.05,").unwrap();
+        let pairs: Vec<(f64, f64)> = parsed.iter().map(|tier| (tier.min_target_sol, tier.amount_sol)).collect();
+        assert_eq!(pairs, vec![(0.5, 0.01), (2.0, 0.05), (10.0, 0.1)]);
+        assert!(parse_size_tiers("").unwrap().is_empty());
+
+        for malformed in ["0.5", "0.5:abc", "x:0.01", "1:0.1,2"] {
+            assert!(parse_size_tiers(malformed).is_err(), "{}", malformed);
+        }
+    }
+}
@@ -0,0 +1,343 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard};
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use lazy_static::lazy_static;
+use once_cell::sync::OnceCell;
+use rusqlite::{params, Connection};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+use crate::engine::comprehensive_selling::BoughtTokenInfo;
+use crate::engine::monitor::TokenTrackingInfo;
+use crate::engine::paper;
+use crate::engine::swap::SwapProtocol;
+use crate::engine::transaction_parser::DexType;
+use crate::library::logger::Logger;
+
+lazy_static! {
+    static ref POSITION_DB: Mutex<Option<Connection>> = Mutex::new(None);
+    static ref POSITION_DB_FILE: String =
+        paper::paper_path(&std::env::var("POSITION_DB_FILE").unwrap_or_else(|_| "positions.db".to_string()));
+}
+
+// Writes queued by the trading tasks, applied in order off the async runtime
+static POSITION_WRITER: OnceCell<UnboundedSender<PositionWrite>> = OnceCell::new();
+
+enum PositionWrite {
+    Save(Box<StoredPosition>),
+    Delete(String),
+}
+
+/// An open position as written to the position book
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StoredPosition {
+    pub mint: String,
+    pub entry_price: f64,
+    pub entry_amount: f64,
+    pub entry_timestamp: i64,
+    pub highest_price: f64,
+    pub lowest_price_after_highest: f64,
+    pub current_price: f64,
+    pub protocol: String,
+    pub dex_type: String,
+    pub target: String,
+    pub coin_creator: Option<String>,
+    pub pool: Option<String>,
+    pub bonding_curve: String,
+    pub signature: String,
+    pub reached_20_percent: bool,
+    pub sold_percentages: HashMap<String, f64>,
+    pub remaining_amount: f64,
+    pub sizing_reason: String,
+    pub realized_pnl_sol: f64,
+    pub top_pnl: f64,
+    pub completed_intervals: HashSet<String>,
+}
+
+impl StoredPosition {
+    pub fn from_position(info: &BoughtTokenInfo, tracking: Option<&TokenTrackingInfo>) -> Self {
+        let entry_timestamp = chrono::Utc::now().timestamp() - info.entry_time.elapsed().as_secs() as i64;
+        Self {
+            mint: info.token_mint.clone(),
+            entry_price: info.entry_price,
+            entry_amount: info.entry_amount,
+            entry_timestamp,
+            highest_price: info.highest_price,
+            lowest_price_after_highest: info.lowest_price_after_highest,
+            current_price: info.current_price,
+            protocol: format!("{:?}", info.protocol),
+            dex_type: format!("{:?}", info.trade_info.dex_type),
+            target: info.trade_info.target.clone(),
+            coin_creator: info.trade_info.coin_creator.clone(),
+            pool: info.trade_info.pool.clone(),
+            bonding_curve: info.trade_info.bonding_curve.clone(),
+            signature: info.trade_info.signature.clone(),
+            reached_20_percent: info.reached_20_percent,
+            sold_percentages: info.sold_percentages.clone(),
+            remaining_amount: info.remaining_amount,
+            sizing_reason: info.sizing_reason.clone(),
+            realized_pnl_sol: info.realized_pnl_sol,
+            top_pnl: tracking.map(|tracking| tracking.top_pnl).unwrap_or(0.0),
+            completed_intervals: tracking.map(|tracking| tracking.completed_intervals.clone()).unwrap_or_default(),
+        }
+    }
+
+    pub fn swap_protocol(&self) -> SwapProtocol {
+        match self.protocol.as_str() {
+            "PumpFun" => SwapProtocol::PumpFun,
+            "PumpSwap" => SwapProtocol::PumpSwap,
+            "RaydiumLaunchpad" => SwapProtocol::RaydiumLaunchpad,
+            "Auto" => SwapProtocol::Auto,
+            _ => SwapProtocol::Unknown,
+        }
+    }
+
+    pub fn dex_type(&self) -> DexType {
+        match self.dex_type.as_str() {
+            "PumpFun" => DexType::PumpFun,
+            "PumpSwap" => DexType::PumpSwap,
+            "RaydiumLaunchpad" => DexType::RaydiumLaunchpad,
+            _ => DexType::Unknown,
+        }
+    }
+}
+
+fn position_db() -> MutexGuard<'static, Option<Connection>> {
+    POSITION_DB.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Open `POSITION_DB_FILE` and start the writer task; later calls are no-ops
+pub fn open_position_store() -> Result<()> {
+    let mut db = position_db();
+    if db.is_some() {
+        return Ok(());
+    }
+    *db = Some(open_connection(&POSITION_DB_FILE)?);
+    drop(db);
+
+    let (sender, mut receiver) = unbounded_channel::<PositionWrite>();
+    if POSITION_WRITER.set(sender).is_ok() {
+        tokio::spawn(async move {
+            let logger = Logger::new("[POSITION-BOOK] => ".cyan().to_string());
+            while let Some(write) = receiver.recv().await {
+                let result = tokio::task::spawn_blocking(move || match write {
+                    PositionWrite::Save(position) => save_position(&position),
+                    PositionWrite::Delete(mint) => delete_position(&mint),
+                }).await;
+                match result {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => {
+                        logger.log(format!("{}", e).red().to_string());
+                    }
+                    Err(e) => {
+                        logger.log(format!("Position book write panicked: {}", e).red().to_string());
+                    }
+                }
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Open the position book at `path`, creating the table on first use
+fn open_connection(path: &str) -> Result<Connection> {
+    let connection = Connection::open(path)
+        .map_err(|e| anyhow!("Failed to open position book {}: {}", path, e))?;
+    create_table(&connection).map_err(|e| anyhow!("Failed to create position book {}: {}", path, e))?;
+    Ok(connection)
+}
+
+fn create_table(connection: &Connection) -> rusqlite::Result<()> {
+    connection.execute_batch(
+        "CREATE TABLE IF NOT EXISTS positions (
+            mint TEXT PRIMARY KEY,
+            entry_price REAL NOT NULL,
+            entry_amount REAL NOT NULL,
+            entry_timestamp INTEGER NOT NULL,
+            highest_price REAL NOT NULL,
+            lowest_price_after_highest REAL NOT NULL,
+            current_price REAL NOT NULL,
+            protocol TEXT NOT NULL,
+            dex_type TEXT NOT NULL,
+            target TEXT NOT NULL,
+            coin_creator TEXT,
+            pool TEXT,
+            bonding_curve TEXT NOT NULL,
+            signature TEXT NOT NULL,
+            reached_20_percent INTEGER NOT NULL,
+            sold_percentages TEXT NOT NULL,
+            remaining_amount REAL NOT NULL,
+            sizing_reason TEXT NOT NULL,
+            realized_pnl_sol REAL NOT NULL,
+            top_pnl REAL NOT NULL,
+            completed_intervals TEXT NOT NULL,
+            updated_at INTEGER NOT NULL
+        );",
+    )
+}
+
+/// Queue an insert or replace of a position; no-op while the store is not open
+pub fn queue_save(position: StoredPosition) {
+    if let Some(writer) = POSITION_WRITER.get() {
+        let _ = writer.send(PositionWrite::Save(Box::new(position)));
+    }
+}
+
+/// Queue forgetting a closed position; no-op while the store is not open
+pub fn queue_delete(mint: &str) {
+    if let Some(writer) = POSITION_WRITER.get() {
+        let _ = writer.send(PositionWrite::Delete(mint.to_string()));
+    }
+}
+
+/// Insert or replace a position; no-op while the store is not open
+///
+/// Blocks on SQLite, trading tasks go through `queue_save`.
+pub fn save_position(position: &StoredPosition) -> Result<()> {
+    let db = position_db();
+    let Some(connection) = db.as_ref() else { return Ok(()) };
+    insert_position(connection, position)
+}
+
+fn insert_position(connection: &Connection, position: &StoredPosition) -> Result<()> {
+    connection.execute(
+        "INSERT OR REPLACE INTO positions (
+            mint, entry_price, entry_amount, entry_timestamp, highest_price, lowest_price_after_highest,
+            current_price, protocol, dex_type, target, coin_creator, pool, bonding_curve, signature,
+            reached_20_percent, sold_percentages, remaining_amount, sizing_reason, realized_pnl_sol,
+            top_pnl, completed_intervals, updated_at
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22)",
+        params![
+            position.mint,
+            position.entry_price,
+            position.entry_amount,
+            position.entry_timestamp,
+            position.highest_price,
+            position.lowest_price_after_highest,
+            position.current_price,
+            position.protocol,
+            position.dex_type,
+            position.target,
+            position.coin_creator,
+            position.pool,
+            position.bonding_curve,
+            position.signature,
+            position.reached_20_percent,
+            serde_json::to_string(&position.sold_percentages)?,
+            position.remaining_amount,
+            position.sizing_reason,
+            position.realized_pnl_sol,
+            position.top_pnl,
+            serde_json::to_string(&position.completed_intervals)?,
+            chrono::Utc::now().timestamp(),
+        ],
+    ).map_err(|e| anyhow!("Failed to save position {}: {}", position.mint, e))?;
+    Ok(())
+}
+
+/// Forget a closed position; blocks on SQLite, trading tasks go through `queue_delete`
+pub fn delete_position(mint: &str) -> Result<()> {
+    let db = position_db();
+    let Some(connection) = db.as_ref() else { return Ok(()) };
+    connection
+        .execute("DELETE FROM positions WHERE mint = ?1", params![mint])
+        .map_err(|e| anyhow!("Failed to delete position {}: {}", mint, e))?;
+    Ok(())
+}
+
+/// Every position in the store
+pub fn load_positions() -> Result<Vec<StoredPosition>> {
+    let db = position_db();
+    let connection = db.as_ref().ok_or_else(|| anyhow!("Position book not open"))?;
+    read_positions(connection)
+}
+
+fn read_positions(connection: &Connection) -> Result<Vec<StoredPosition>> {
+    let mut statement = connection.prepare(
+        "SELECT mint, entry_price, entry_amount, entry_timestamp, highest_price, lowest_price_after_highest,
+            current_price, protocol, dex_type, target, coin_creator, pool, bonding_curve, signature,
+            reached_20_percent, sold_percentages, remaining_amount, sizing_reason, realized_pnl_sol,
+            top_pnl, completed_intervals
+        FROM positions",
+    )?;
+    let rows = statement.query_map([], |row| {
+        let sold_percentages: String = row.get(15)?;
+        let completed_intervals: String = row.get(20)?;
+        Ok(StoredPosition {
+            mint: row.get(0)?,
+            entry_price: row.get(1)?,
+            entry_amount: row.get(2)?,
+            entry_timestamp: row.get(3)?,
+            highest_price: row.get(4)?,
+            lowest_price_after_highest: row.get(5)?,
+            current_price: row.get(6)?,
+            protocol: row.get(7)?,
+            dex_type: row.get(8)?,
+            target: row.get(9)?,
+            coin_creator: row.get(10)?,
+            pool: row.get(11)?,
+            bonding_curve: row.get(12)?,
+            signature: row.get(13)?,
+            reached_20_percent: row.get(14)?,
+            sold_percentages: serde_json::from_str(&sold_percentages).unwrap_or_default(),
+            remaining_amount: row.get(16)?,
+            sizing_reason: row.get(17)?,
+            realized_pnl_sol: row.get(18)?,
+            top_pnl: row.get(19)?,
+            completed_intervals: serde_json::from_str(&completed_intervals).unwrap_or_default(),
+        })
+    })?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| anyhow!("Failed to read position book: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(mint: &str) -> StoredPosition {
+        StoredPosition {
+            mint: mint.to_string(),
+            entry_price: 0.000000031,
+            entry_amount: 0.25,
+            entry_timestamp: 1_700_000_000,
+            highest_price: 0.000000045,
+            lowest_price_after_highest: 0.00000004,
+            current_price: 0.000000041,
+            protocol: "PumpSwap".to_string(),
+            dex_type: "PumpSwap".to_string(),
+            target: "target".to_string(),
+            coin_creator: Some("creator".to_string()),
+            pool: None,
+            bonding_curve: "curve".to_string(),
+            signature: "signature".to_string(),
+            reached_20_percent: true,
+            sold_percentages: HashMap::from([("tp_1".to_string(), 25.0)]),
+            remaining_amount: 75.0,
+            sizing_reason: "fixed 0.25 SOL".to_string(),
+            realized_pnl_sol: 0.031,
+            top_pnl: 45.2,
+            completed_intervals: HashSet::from(["30s".to_string()]),
+        }
+    }
+
+    #[test]
+    fn positions_survive_a_save_and_load() {
+        let connection = Connection::open_in_memory().unwrap();
+        create_table(&connection).unwrap();
+        let first = position("mint1");
+        let mut second = position("mint2");
+        insert_position(&connection, &first).unwrap();
+        insert_position(&connection, &second).unwrap();
+        // Saving again replaces the row
+        second.remaining_amount = 40.0;
+        second.pool = Some("pool".to_string());
+        insert_position(&connection, &second).unwrap();
+
+        let mut loaded = read_positions(&connection).unwrap();
+        loaded.sort_by(|a, b| a.mint.cmp(&b.mint));
+        assert_eq!(loaded, vec![first, second]);
+        assert_eq!(loaded[1].swap_protocol(), SwapProtocol::PumpSwap);
+        assert_eq!(loaded[1].dex_type(), DexType::PumpSwap);
+    }
+}
@@ -1,11 +1,39 @@
-use std::collections::HashMap;
-use std::time::Instant;
-use anyhow::Result;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use anyhow::{anyhow, Result};
+use dashmap::DashMap;
+use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use colored::Colorize;
-use crate::library::logger::Logger;
+use crate::library::{config::AppState, logger::Logger};
+use crate::engine::comprehensive_selling;
+use crate::engine::paper;
+use crate::engine::position_sizing::get_wallet_balance_sol;
+use crate::engine::rug_check::RugCheckVerdict;
+use crate::engine::swap::SwapProtocol;
+use crate::engine::transaction_parser::TradeInfoFromToken;
 // Removed dynamic_selling_strategy imports
 
+// Engine shared by every buy and sell path, and its configuration readable without the lock
+lazy_static! {
+    pub static ref RISK_CONFIG: RiskManagementConfig = RiskManagementConfig::from_env();
+    static ref RISK_ENGINE: Arc<Mutex<RiskManagementEngine>> =
+        Arc::new(Mutex::new(RiskManagementEngine::new(RISK_CONFIG.clone())));
+    static ref RISK_STARTED: AtomicBool = AtomicBool::new(false);
+    // Recent prices and SOL flow of every token seen trading, by mint
+    static ref TOKEN_FLOWS: Arc<DashMap<String, TokenFlow>> = Arc::new(DashMap::new());
+    static ref RISK_STATE_FILE: String =
+        paper::paper_path(&std::env::var("RISK_STATE_FILE").unwrap_or_else(|_| "risk_state.json".to_string()));
+    // Number of the latest state snapshot taken, and of the last one written to disk
+    static ref STATE_SNAPSHOTS: AtomicU64 = AtomicU64::new(0);
+    static ref STATE_WRITTEN: Mutex<u64> = Mutex::new(0);
+    // Exposure approved for buys that are not positions yet, by reservation id
+    static ref PENDING_EXPOSURE: Arc<DashMap<u64, PendingExposure>> = Arc::new(DashMap::new());
+    static ref NEXT_RESERVATION: AtomicU64 = AtomicU64::new(0);
+}
+
 /// Simple market condition enum
 #[derive(Debug, Clone, PartialEq)]
 pub enum MarketCondition {
@@ -39,12 +67,63 @@ impl Default for RealTimeTokenMetrics {
 pub struct RiskManagementConfig {
     pub max_position_size_percentage: f64,        // 20.0 = 20% of portfolio
     pub max_total_exposure_percentage: f64,       // 80.0 = 80% of portfolio
+    pub max_target_exposure_percentage: f64,      // 40.0 = 40% of portfolio in one target's copies
+    pub max_venue_exposure_percentage: f64,       // 60.0 = 60% of portfolio on one venue
     pub max_daily_loss_percentage: f64,           // -5.0 = -5% daily loss limit
     pub max_drawdown_percentage: f64,             // -25.0 = -25% max drawdown
     pub high_volatility_threshold: f64,           // 30.0 = 30% volatility
     pub volatility_position_reducer: f64,         // 0.5 = 50% reduction in high volatility
     pub max_positions_per_hour: usize,            // 10 positions per hour
     pub emergency_stop_loss_percentage: f64,     // -30.0 = -30% emergency stop
+    pub volatility_window: usize,                 // 30 = volatility over the last 30 trade prices
+    pub flow_window_secs: u64,                    // 60 = net flow over the last minute
+    pub bear_flow_threshold: f64,                 // -0.5 = BearDump when sells outweigh buys 3:1
+    pub bull_flow_threshold: f64,                 // 0.5 = BullRun when buys outweigh sells 3:1
+    pub day_start_hour_utc: u32,                  // 0 = daily PnL resets at 00:00 UTC
+}
+
+impl RiskManagementConfig {
+    /// Defaults overridden by `RISK_MAX_POSITION_PERCENTAGE`, `RISK_MAX_EXPOSURE_PERCENTAGE`,
+    /// `RISK_MAX_TARGET_EXPOSURE_PERCENTAGE`, `RISK_MAX_VENUE_EXPOSURE_PERCENTAGE`,
+    /// `RISK_MAX_DAILY_LOSS_PERCENTAGE`, `RISK_MAX_DRAWDOWN_PERCENTAGE`, `RISK_HIGH_VOLATILITY`,
+    /// `RISK_VOLATILITY_REDUCER`, `RISK_MAX_POSITIONS_PER_HOUR`, `RISK_EMERGENCY_STOP_LOSS_PERCENTAGE`, `RISK_VOLATILITY_WINDOW`,
+    /// `RISK_FLOW_WINDOW_SECS`, `RISK_BEAR_FLOW`, `RISK_BULL_FLOW` and `RISK_DAY_START_HOUR_UTC`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let f64_var = |name: &str, default: f64| {
+            std::env::var(name).ok().and_then(|v| v.parse::<f64>().ok()).unwrap_or(default)
+        };
+        Self {
+            max_position_size_percentage: f64_var("RISK_MAX_POSITION_PERCENTAGE", defaults.max_position_size_percentage),
+            max_total_exposure_percentage: f64_var("RISK_MAX_EXPOSURE_PERCENTAGE", defaults.max_total_exposure_percentage),
+            max_target_exposure_percentage: f64_var("RISK_MAX_TARGET_EXPOSURE_PERCENTAGE", defaults.max_target_exposure_percentage),
+            max_venue_exposure_percentage: f64_var("RISK_MAX_VENUE_EXPOSURE_PERCENTAGE", defaults.max_venue_exposure_percentage),
+            max_daily_loss_percentage: f64_var("RISK_MAX_DAILY_LOSS_PERCENTAGE", defaults.max_daily_loss_percentage),
+            max_drawdown_percentage: f64_var("RISK_MAX_DRAWDOWN_PERCENTAGE", defaults.max_drawdown_percentage),
+            high_volatility_threshold: f64_var("RISK_HIGH_VOLATILITY", defaults.high_volatility_threshold),
+            volatility_position_reducer: f64_var("RISK_VOLATILITY_REDUCER", defaults.volatility_position_reducer),
+            max_positions_per_hour: std::env::var("RISK_MAX_POSITIONS_PER_HOUR")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(defaults.max_positions_per_hour),
+            emergency_stop_loss_percentage: f64_var("RISK_EMERGENCY_STOP_LOSS_PERCENTAGE", defaults.emergency_stop_loss_percentage),
+            volatility_window: std::env::var("RISK_VOLATILITY_WINDOW")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(defaults.volatility_window),
+            flow_window_secs: std::env::var("RISK_FLOW_WINDOW_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(defaults.flow_window_secs),
+            bear_flow_threshold: f64_var("RISK_BEAR_FLOW", defaults.bear_flow_threshold),
+            bull_flow_threshold: f64_var("RISK_BULL_FLOW", defaults.bull_flow_threshold),
+            day_start_hour_utc: std::env::var("RISK_DAY_START_HOUR_UTC")
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .filter(|hour| *hour < 24)
+                .unwrap_or(defaults.day_start_hour_utc),
+        }
+    }
 }
 
 impl Default for RiskManagementConfig {
@@ -52,12 +131,19 @@ impl Default for RiskManagementConfig {
         Self {
             max_position_size_percentage: 20.0,
             max_total_exposure_percentage: 80.0,
+            max_target_exposure_percentage: 40.0,
+            max_venue_exposure_percentage: 60.0,
             max_daily_loss_percentage: -5.0,
             max_drawdown_percentage: -25.0,
             high_volatility_threshold: 30.0,
             volatility_position_reducer: 0.5,
             max_positions_per_hour: 10,
             emergency_stop_loss_percentage: -30.0,
+            volatility_window: 30,
+            flow_window_secs: 60,
+            bear_flow_threshold: -0.5,
+            bull_flow_threshold: 0.5,
+            day_start_hour_utc: 0,
         }
     }
 }
@@ -84,16 +170,289 @@ pub struct TradePerformance {
     pub hourly_trade_count: usize,
 }
 
+/// Recent trade prices and SOL flow of a token
+#[derive(Debug, Clone, Default)]
+pub struct TokenFlow {
+    pub prices: VecDeque<f64>,
+    pub flows: VecDeque<(i64, f64)>, // timestamp, signed SOL (buys positive)
+    pub signatures: VecDeque<String>, // recent trades, several streams can deliver the same one
+    pub last_seen: i64,
+}
+
+impl TokenFlow {
+    /// Add a trade seen at `now` (unix seconds), trades without a price are ignored
+    pub fn record(&mut self, now: i64, trade: &TradeInfoFromToken, config: &RiskManagementConfig) {
+        let Some(price) = trade.price_in_sol() else { return };
+        let sol = trade.sol_amount.or(trade.quote_amount_out).unwrap_or(0) as f64 / 1_000_000_000.0;
+        let signed_sol = if trade.is_buy { sol } else { -sol };
+        self.last_seen = now;
+        self.prices.push_back(price);
+        while self.prices.len() > config.volatility_window.max(2) {
+            self.prices.pop_front();
+        }
+        self.flows.push_back((now, signed_sol));
+        while matches!(self.flows.front(), Some((at, _)) if now - at > config.flow_window_secs as i64) {
+            self.flows.pop_front();
+        }
+    }
+
+    /// Volatility and market condition as of `now`
+    pub fn metrics(&self, now: i64, config: &RiskManagementConfig) -> RealTimeTokenMetrics {
+        let window = config.flow_window_secs as i64;
+        let (buy_sol, sell_sol) = self.flows
+            .iter()
+            .filter(|(at, _)| now - at <= window)
+            .fold((0.0, 0.0), |(buys, sells), (_, sol)| {
+                if *sol >= 0.0 { (buys + sol, sells) } else { (buys, sells - sol) }
+            });
+        RealTimeTokenMetrics {
+            volatility_score: realized_volatility(&self.prices.iter().copied().collect::<Vec<_>>()),
+            market_condition: classify_flow(buy_sol, sell_sol, config),
+            ..Default::default()
+        }
+    }
+}
+
+/// Whether the portfolio state allows another buy, `Err` names the limit that blocks it
+///
+/// `daily_pnl` and `current_drawdown` are percentages, negative when losing.
+pub fn check_risk_limits(
+    config: &RiskManagementConfig,
+    hourly_trade_count: usize,
+    daily_pnl: f64,
+    current_drawdown: f64,
+    market_condition: &MarketCondition,
+) -> Result<(), String> {
+    if hourly_trade_count >= config.max_positions_per_hour {
+        return Err(format!("Hourly trade limit reached ({}/{})", hourly_trade_count, config.max_positions_per_hour));
+    }
+    if daily_pnl < config.max_daily_loss_percentage {
+        return Err(format!("Daily loss limit reached ({:.2}%)", daily_pnl));
+    }
+    if current_drawdown < config.max_drawdown_percentage {
+        return Err(format!("Max drawdown reached ({:.2}%)", current_drawdown));
+    }
+    if *market_condition == MarketCondition::BearDump {
+        return Err("Bear dump detected".to_string());
+    }
+    Ok(())
+}
+
+/// Largest buy in SOL for `portfolio_value`, reduced for volatile tokens and losing streaks
+pub fn position_size_limit(
+    config: &RiskManagementConfig,
+    portfolio_value: f64,
+    volatility_score: f64,
+    consecutive_wins: usize,
+    consecutive_losses: usize,
+) -> f64 {
+    let base_size = portfolio_value * (config.max_position_size_percentage / 100.0);
+    let volatility_adjustment = if volatility_score > config.high_volatility_threshold {
+        config.volatility_position_reducer
+    } else {
+        1.0
+    };
+    let performance_adjustment = if consecutive_losses >= 3 {
+        0.5  // Reduce size after losses
+    } else if consecutive_wins >= 5 {
+        1.2  // Increase size after wins
+    } else {
+        1.0
+    };
+    (base_size * volatility_adjustment * performance_adjustment).max(0.0)
+}
+
+/// Realized volatility in percent: standard deviation of the log returns between consecutive prices
+pub fn realized_volatility(prices: &[f64]) -> f64 {
+    let returns: Vec<f64> = prices
+        .windows(2)
+        .filter(|pair| pair[0] > 0.0 && pair[1] > 0.0)
+        .map(|pair| (pair[1] / pair[0]).ln())
+        .collect();
+    if returns.len() < 2 {
+        return 0.0;
+    }
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+    variance.sqrt() * 100.0
+}
+
+/// Classify net SOL flow: (buys - sells) / (buys + sells) against the bear / bull thresholds
+pub fn classify_flow(buy_sol: f64, sell_sol: f64, config: &RiskManagementConfig) -> MarketCondition {
+    let total = buy_sol + sell_sol;
+    if total <= 0.0 {
+        return MarketCondition::Normal;
+    }
+    let net_flow = (buy_sol - sell_sol) / total;
+    if net_flow <= config.bear_flow_threshold {
+        MarketCondition::BearDump
+    } else if net_flow >= config.bull_flow_threshold {
+        MarketCondition::BullRun
+    } else {
+        MarketCondition::Normal
+    }
+}
+
+/// Trading day a timestamp belongs to when days start at `day_start_hour_utc`
+pub fn trading_day(timestamp: i64, day_start_hour_utc: u32) -> String {
+    use chrono::TimeZone;
+    let shifted = timestamp - day_start_hour_utc as i64 * 3600;
+    chrono::Utc
+        .timestamp_opt(shifted, 0)
+        .single()
+        .map(|at| at.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// Cost basis in SOL of the open positions, in total and by token, target and venue
+#[derive(Debug, Clone, Default)]
+pub struct ExposureBook {
+    pub total: f64,
+    pub by_token: HashMap<String, f64>,
+    pub by_target: HashMap<String, f64>,
+    pub by_venue: HashMap<String, f64>,
+}
+
+impl ExposureBook {
+    pub fn add(&mut self, mint: &str, target: &str, venue: &SwapProtocol, cost_basis: f64) {
+        self.total += cost_basis;
+        *self.by_token.entry(mint.to_string()).or_insert(0.0) += cost_basis;
+        if !target.is_empty() {
+            *self.by_target.entry(target.to_string()).or_insert(0.0) += cost_basis;
+        }
+        *self.by_venue.entry(format!("{:?}", venue)).or_insert(0.0) += cost_basis;
+    }
+
+    /// The open positions of the position book, plus the buys approved but not yet opened
+    pub fn current() -> Self {
+        let mut book = Self::default();
+        for mint in comprehensive_selling::get_bought_token_mints() {
+            if let Some(info) = comprehensive_selling::get_bought_token(&mint) {
+                let cost_basis = info.entry_amount * info.remaining_amount / 100.0;
+                book.add(&mint, &info.trade_info.target, &info.protocol, cost_basis);
+            }
+        }
+        for pending in PENDING_EXPOSURE.iter() {
+            book.add(&pending.mint, &pending.target, &pending.venue, pending.amount_sol);
+        }
+        book
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PendingExposure {
+    mint: String,
+    target: String,
+    venue: SwapProtocol,
+    amount_sol: f64,
+}
+
+/// Exposure held for an approved buy until it becomes a position, released on drop
+///
+/// Keep it alive until the bought position is registered for monitoring, so concurrent
+/// approvals see the buy in the exposure book the whole time.
+pub struct ExposureReservation {
+    id: Option<u64>,
+    max_size: Option<f64>,
+}
+
+impl ExposureReservation {
+    /// Largest size allowed in SOL, `None` when risk management is off
+    pub fn max_size(&self) -> Option<f64> {
+        self.max_size
+    }
+
+    /// Hold exactly the amount about to be bought
+    pub fn resize(&mut self, amount_sol: f64) {
+        if let Some(mut pending) = self.id.and_then(|id| PENDING_EXPOSURE.get_mut(&id)) {
+            pending.amount_sol = amount_sol;
+        }
+    }
+}
+
+impl Drop for ExposureReservation {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            PENDING_EXPOSURE.remove(&id);
+        }
+    }
+}
+
+/// SOL a new buy can add before the tightest exposure cap is hit, with that cap described
+///
+/// Caps are percentages of `portfolio_value`: total, per token, per target (copies only) and per venue.
+pub fn exposure_headroom(
+    portfolio_value: f64,
+    book: &ExposureBook,
+    mint: &str,
+    target: Option<&str>,
+    venue: &SwapProtocol,
+    config: &RiskManagementConfig,
+) -> (f64, String) {
+    let venue = format!("{:?}", venue);
+    let mut limits = vec![
+        ("total exposure".to_string(), book.total, config.max_total_exposure_percentage),
+        (
+            format!("token {} exposure", mint),
+            book.by_token.get(mint).copied().unwrap_or(0.0),
+            config.max_position_size_percentage,
+        ),
+        (
+            format!("venue {} exposure", venue),
+            book.by_venue.get(&venue).copied().unwrap_or(0.0),
+            config.max_venue_exposure_percentage,
+        ),
+    ];
+    if let Some(target) = target.filter(|target| !target.is_empty()) {
+        limits.push((
+            format!("target {} exposure", target),
+            book.by_target.get(target).copied().unwrap_or(0.0),
+            config.max_target_exposure_percentage,
+        ));
+    }
+
+    limits
+        .into_iter()
+        .map(|(name, used, cap_percentage)| {
+            let cap = portfolio_value * cap_percentage / 100.0;
+            (cap - used, format!("{} {:.4} / {:.4} SOL ({}%)", name, used, cap, cap_percentage))
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .unwrap_or((f64::INFINITY, String::new()))
+}
+
+/// Counters persisted across restarts so limits keep applying
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskState {
+    pub hourly_trade_count: usize,
+    pub hour_started_at: i64,
+    pub day: String,           // trading day the daily counters belong to
+    pub day_start_value: f64,  // portfolio value in SOL when the day started
+    pub daily_realized_pnl_sol: f64,
+    pub peak_value: f64,       // high-water mark of the portfolio value in SOL
+    pub consecutive_wins: usize,
+    pub consecutive_losses: usize,
+}
+
 /// Risk management engine
 pub struct RiskManagementEngine {
     config: RiskManagementConfig,
     portfolio_metrics: PortfolioRiskMetrics,
     trade_performance: TradePerformance,
+    hour_started_at: i64,
+    day: String,
+    day_start_value: f64,
+    daily_realized_pnl_sol: f64,
+    unrealized_pnl_sol: f64,
+    peak_value: f64,
+    value_history: VecDeque<f64>, // recent portfolio values for the portfolio volatility
     logger: Logger,
 }
 
 impl RiskManagementEngine {
     pub fn new(config: RiskManagementConfig) -> Self {
+        let day = trading_day(chrono::Utc::now().timestamp(), config.day_start_hour_utc);
         Self {
             config,
             portfolio_metrics: PortfolioRiskMetrics {
@@ -112,6 +471,13 @@ impl RiskManagementEngine {
                 last_trade_time: Instant::now(),
                 hourly_trade_count: 0,
             },
+            hour_started_at: chrono::Utc::now().timestamp(),
+            day,
+            day_start_value: 0.0,
+            daily_realized_pnl_sol: 0.0,
+            unrealized_pnl_sol: 0.0,
+            peak_value: 0.0,
+            value_history: VecDeque::new(),
             logger: Logger::new("[RISK-MGMT] => ".red().to_string()),
         }
     }
@@ -124,77 +490,59 @@ impl RiskManagementEngine {
         token_metrics: &RealTimeTokenMetrics,
     ) -> Result<f64> {
         self.logger.log(format!(
-            "🎯 Calculating position size | Portfolio: ${:.2}",
+            "🎯 Calculating position size | Portfolio: {:.4} SOL",
             portfolio_value
         ).blue().to_string());
         
-        // Base position size
-        let base_size = portfolio_value * (self.config.max_position_size_percentage / 100.0);
-        
-        // Adjust for volatility
-        let volatility_adjustment = if token_metrics.volatility_score > self.config.high_volatility_threshold {
-            self.config.volatility_position_reducer
-        } else {
-            1.0
-        };
-        
-        // Adjust for performance
-        let performance_adjustment = if self.trade_performance.consecutive_losses >= 3 {
-            0.5  // Reduce size after losses
-        } else if self.trade_performance.consecutive_wins >= 5 {
-            1.2  // Increase size after wins
-        } else {
-            1.0
-        };
-        
-        // Calculate final position size
-        let final_size = base_size * volatility_adjustment * performance_adjustment;
-        
+        let final_size = position_size_limit(
+            &self.config,
+            portfolio_value,
+            token_metrics.volatility_score,
+            self.trade_performance.consecutive_wins,
+            self.trade_performance.consecutive_losses,
+        );
         self.logger.log(format!(
-            "📊 Position size: Base ${:.2} -> Final ${:.2} (Vol: {:.2}x, Perf: {:.2}x)",
-            base_size, final_size, volatility_adjustment, performance_adjustment
+            "📊 Position size: {:.4} SOL (volatility {:.2}, {} wins / {} losses in a row)",
+            final_size,
+            token_metrics.volatility_score,
+            self.trade_performance.consecutive_wins,
+            self.trade_performance.consecutive_losses
         ).cyan().to_string());
-        
-        Ok(final_size.max(0.0))
+        Ok(final_size)
     }
     
     /// Check if we should allow a new position
     pub fn should_allow_new_position(&mut self, token_metrics: &RealTimeTokenMetrics) -> Result<bool> {
-        // Check hourly trade limit
-        if self.trade_performance.hourly_trade_count >= self.config.max_positions_per_hour {
-            self.logger.log(format!(
-                "🚫 BLOCKED: Hourly trade limit reached ({}/{})",
-                self.trade_performance.hourly_trade_count, 
-                self.config.max_positions_per_hour
-            ).red().to_string());
-            return Ok(false);
-        }
-        
-        // Check portfolio risk limits
-        if self.portfolio_metrics.daily_pnl < self.config.max_daily_loss_percentage {
-            self.logger.log(format!(
-                "🚫 BLOCKED: Daily loss limit reached ({:.2}%)",
-                self.portfolio_metrics.daily_pnl
-            ).red().to_string());
-            return Ok(false);
-        }
-        
-        if self.portfolio_metrics.current_drawdown < self.config.max_drawdown_percentage {
-            self.logger.log(format!(
-                "🚫 BLOCKED: Max drawdown reached ({:.2}%)",
-                self.portfolio_metrics.current_drawdown
-            ).red().to_string());
-            return Ok(false);
+        let allowed = check_risk_limits(
+            &self.config,
+            self.trade_performance.hourly_trade_count,
+            self.portfolio_metrics.daily_pnl,
+            self.portfolio_metrics.current_drawdown,
+            &token_metrics.market_condition,
+        );
+        match allowed {
+            Ok(()) => {
+                self.logger.log("✅ Position allowed".green().to_string());
+                Ok(true)
+            }
+            Err(reason) => {
+                self.logger.log(format!("🚫 BLOCKED: {}", reason).red().to_string());
+                Ok(false)
+            }
         }
-        
-        // Check market conditions
-        if token_metrics.market_condition == MarketCondition::BearDump {
-            self.logger.log("🚫 BLOCKED: Bear dump detected".red().to_string());
-            return Ok(false);
+    }
+
+    /// Check if a rug check verdict should stop the buy
+    pub fn should_veto_rug_check(&self, verdict: &RugCheckVerdict) -> bool {
+        if verdict.is_safe() {
+            return false;
         }
-        
-        self.logger.log("✅ Position allowed".green().to_string());
-        Ok(true)
+        self.logger.log(format!(
+            "🚫 BLOCKED: Rug check failed for {} ({})",
+            verdict.mint,
+            verdict.summary()
+        ).red().to_string());
+        true
     }
     
     /// Update portfolio metrics
@@ -211,19 +559,32 @@ impl RiskManagementEngine {
             .map(|metrics| metrics.unrealized_pnl_usd)
             .sum();
         
-        // Update drawdown
+        // Drawdown from the high-water mark of the marked-to-market value
         let current_total = portfolio_value + total_unrealized_pnl;
-        self.portfolio_metrics.current_drawdown = if portfolio_value > 0.0 {
-            ((current_total - portfolio_value) / portfolio_value) * 100.0
+        self.peak_value = self.peak_value.max(current_total);
+        self.portfolio_metrics.current_drawdown = if self.peak_value > 0.0 {
+            ((current_total - self.peak_value) / self.peak_value) * 100.0
         } else {
             0.0
         };
         
+        self.value_history.push_back(current_total);
+        while self.value_history.len() > self.config.volatility_window.max(2) {
+            self.value_history.pop_front();
+        }
+        self.portfolio_metrics.portfolio_volatility =
+            realized_volatility(&self.value_history.iter().copied().collect::<Vec<_>>());
+        
+        self.unrealized_pnl_sol = total_unrealized_pnl;
+        self.update_daily_pnl();
+        
         self.logger.log(format!(
-            "📊 Portfolio update: Value: ${:.2} | Exposure: {:.1}% | Drawdown: {:.2}%",
-            portfolio_value,
-            (self.portfolio_metrics.total_exposure / portfolio_value) * 100.0,
-            self.portfolio_metrics.current_drawdown
+            "📊 Portfolio update: Value: {:.4} SOL | Exposure: {:.1}% | Drawdown: {:.2}% | Daily PnL: {:+.2}% | Vol: {:.2}%",
+            current_total,
+            if portfolio_value > 0.0 { (self.portfolio_metrics.total_exposure / portfolio_value) * 100.0 } else { 0.0 },
+            self.portfolio_metrics.current_drawdown,
+            self.portfolio_metrics.daily_pnl,
+            self.portfolio_metrics.portfolio_volatility
         ).cyan().to_string());
     }
     
@@ -238,9 +599,7 @@ impl RiskManagementEngine {
             self.trade_performance.consecutive_wins = 0;
         }
         
-        // Update trade time and counter
         self.trade_performance.last_trade_time = Instant::now();
-        self.trade_performance.hourly_trade_count += 1;
         
         self.logger.log(format!(
             "📈 Trade recorded: PnL: {:+.2}% | Consecutive W/L: {}/{}",
@@ -250,11 +609,17 @@ impl RiskManagementEngine {
         ).blue().to_string());
     }
     
+    /// Today's PnL in percent of the day's start value
+    pub fn daily_pnl(&self) -> f64 {
+        self.portfolio_metrics.daily_pnl
+    }
+    
     /// Get risk management status
     pub fn get_risk_status(&self) -> String {
         format!(
-            "🛡️ Risk Status: Portfolio: ${:.2} | Drawdown: {:.2}% | W/L: {}/{}",
+            "🛡️ Risk Status: Portfolio: {:.4} SOL | Daily PnL: {:+.2}% | Drawdown: {:.2}% | W/L: {}/{}",
             self.portfolio_metrics.total_portfolio_value,
+            self.portfolio_metrics.daily_pnl,
             self.portfolio_metrics.current_drawdown,
             self.trade_performance.consecutive_wins,
             self.trade_performance.consecutive_losses
@@ -264,6 +629,413 @@ impl RiskManagementEngine {
     /// Reset hourly counters
     pub fn reset_hourly_counters(&mut self) {
         self.trade_performance.hourly_trade_count = 0;
+        self.hour_started_at = chrono::Utc::now().timestamp();
         self.logger.log("⏰ Hourly counters reset".blue().to_string());
     }
-} 
\ No newline at end of file
+
+    /// Reset the daily PnL, the current portfolio value becomes the day's reference
+    pub fn reset_daily_counters(&mut self) {
+        self.day = trading_day(chrono::Utc::now().timestamp(), self.config.day_start_hour_utc);
+        self.day_start_value = self.portfolio_metrics.total_portfolio_value + self.unrealized_pnl_sol;
+        self.daily_realized_pnl_sol = 0.0;
+        self.portfolio_metrics.daily_pnl = 0.0;
+        self.logger.log(format!("📅 Daily counters reset for {}", self.day).blue().to_string());
+    }
+
+    /// Count a newly opened position against the hourly limit
+    pub fn record_new_position(&mut self) {
+        self.trade_performance.hourly_trade_count += 1;
+    }
+
+    /// Book the realized PnL of a closed position into the daily PnL and the win / loss streak
+    pub fn record_realized_pnl(&mut self, pnl_sol: f64, pnl_percentage: f64) {
+        self.daily_realized_pnl_sol += pnl_sol;
+        self.update_daily_pnl();
+        self.record_trade_result(pnl_percentage);
+    }
+
+    /// Realized PnL of the day plus the open positions' unrealized PnL, relative to the day's start value
+    ///
+    /// Built from PnL rather than the wallet balance so deposits and withdrawals do not count.
+    fn update_daily_pnl(&mut self) {
+        if self.day_start_value <= 0.0 {
+            self.day_start_value = self.portfolio_metrics.total_portfolio_value;
+        }
+        self.portfolio_metrics.daily_pnl = if self.day_start_value > 0.0 {
+            (self.daily_realized_pnl_sol + self.unrealized_pnl_sol) / self.day_start_value * 100.0
+        } else {
+            0.0
+        };
+    }
+
+    /// Largest buy in SOL allowed for a token, `None` until the portfolio value is known
+    pub fn max_position_size_sol(&mut self, token_mint: &str, token_metrics: &RealTimeTokenMetrics) -> Option<f64> {
+        let portfolio_value = self.portfolio_metrics.total_portfolio_value;
+        if portfolio_value <= 0.0 {
+            return None;
+        }
+        self.calculate_position_size(token_mint, portfolio_value, token_metrics).ok()
+    }
+
+    /// Reset the counters whose hour or UTC day has passed, returns true when something was reset
+    pub fn run_scheduled_resets(&mut self) -> bool {
+        let mut reset = false;
+        if chrono::Utc::now().timestamp() - self.hour_started_at >= 3600 {
+            self.reset_hourly_counters();
+            reset = true;
+        }
+        if self.day != trading_day(chrono::Utc::now().timestamp(), self.config.day_start_hour_utc) {
+            self.reset_daily_counters();
+            reset = true;
+        }
+        reset
+    }
+
+    pub fn state(&self) -> RiskState {
+        RiskState {
+            hourly_trade_count: self.trade_performance.hourly_trade_count,
+            hour_started_at: self.hour_started_at,
+            day: self.day.clone(),
+            day_start_value: self.day_start_value,
+            daily_realized_pnl_sol: self.daily_realized_pnl_sol,
+            peak_value: self.peak_value,
+            consecutive_wins: self.trade_performance.consecutive_wins,
+            consecutive_losses: self.trade_performance.consecutive_losses,
+        }
+    }
+
+    pub fn restore_state(&mut self, state: RiskState) {
+        self.trade_performance.hourly_trade_count = state.hourly_trade_count;
+        self.trade_performance.consecutive_wins = state.consecutive_wins;
+        self.trade_performance.consecutive_losses = state.consecutive_losses;
+        self.hour_started_at = state.hour_started_at;
+        self.day = state.day;
+        self.day_start_value = state.day_start_value;
+        self.daily_realized_pnl_sol = state.daily_realized_pnl_sol;
+        self.peak_value = state.peak_value;
+        self.update_daily_pnl();
+    }
+}
+
+/// Record a trade price and its SOL flow for the token's volatility and market condition
+pub fn record_market_trade(trade: &TradeInfoFromToken) {
+    if trade.price_in_sol().is_none() {
+        return;
+    }
+    // Runs for every trade on the stream, so only the flow map is touched, never the engine lock
+    let mut flow = TOKEN_FLOWS.entry(trade.mint.clone()).or_default();
+    if flow.signatures.contains(&trade.signature) {
+        return;
+    }
+    flow.signatures.push_back(trade.signature.clone());
+    if flow.signatures.len() > 64 {
+        flow.signatures.pop_front();
+    }
+    flow.record(chrono::Utc::now().timestamp(), trade, &RISK_CONFIG);
+}
+
+/// Volatility and market condition of a token from its recorded trades
+pub fn token_metrics(token_mint: &str) -> RealTimeTokenMetrics {
+    TOKEN_FLOWS
+        .get(token_mint)
+        .map(|flow| flow.metrics(chrono::Utc::now().timestamp(), &RISK_CONFIG))
+        .unwrap_or_default()
+}
+
+/// Drop tokens that have not traded for an hour
+fn prune_token_flows() {
+    let cutoff = chrono::Utc::now().timestamp() - 3600;
+    TOKEN_FLOWS.retain(|_, flow| flow.last_seen > cutoff);
+}
+
+/// Lock the shared engine; a panic while holding it does not disable risk checks
+pub fn risk_engine() -> MutexGuard<'static, RiskManagementEngine> {
+    RISK_ENGINE.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Whether buys go through the risk engine, switched with `RISK_MANAGEMENT_ENABLED`
+pub fn is_risk_management_enabled() -> bool {
+    std::env::var("RISK_MANAGEMENT_ENABLED")
+        .ok()
+        .and_then(|v| v.parse::<bool>().ok())
+        .unwrap_or(true)
+}
+
+/// Restore the persisted counters and start the scheduler; later calls are no-ops
+///
+/// Every minute the portfolio value is refreshed from the wallet and the open positions,
+/// hourly / daily counters are reset when due and the state is written to `RISK_STATE_FILE`.
+pub fn init_risk_engine(app_state: Arc<AppState>) {
+    if RISK_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let logger = Logger::new("[RISK-MGMT] => ".red().to_string());
+
+    match read_state_file(&RISK_STATE_FILE) {
+        Ok(Some(state)) => {
+            risk_engine().restore_state(state);
+            logger.log(format!("Restored risk state from {}", *RISK_STATE_FILE));
+        }
+        Ok(None) => {}
+        Err(e) => {
+            logger.log(format!("{}", e).red().to_string());
+        }
+    }
+
+    tokio::spawn(async move {
+        let mut scheduler = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            scheduler.tick().await;
+            match portfolio_snapshot(&app_state).await {
+                Ok((portfolio_value, positions)) => {
+                    risk_engine().update_portfolio_metrics(portfolio_value, &positions);
+                }
+                Err(e) => {
+                    logger.log(format!("Portfolio refresh failed: {}", e).red().to_string());
+                }
+            }
+            prune_token_flows();
+            let status = {
+                let mut engine = risk_engine();
+                engine.run_scheduled_resets();
+                engine.get_risk_status()
+            };
+            logger.log(status);
+            persist_risk_state();
+        }
+    });
+}
+
+/// Wallet SOL plus the cost of open positions, and the open positions valued at their last price
+async fn portfolio_snapshot(app_state: &Arc<AppState>) -> Result<(f64, HashMap<String, RealTimeTokenMetrics>)> {
+    let wallet_balance = get_wallet_balance_sol(app_state).await?;
+    let mut positions = HashMap::new();
+    for mint in comprehensive_selling::get_bought_token_mints() {
+        if let Some(info) = comprehensive_selling::get_bought_token(&mint) {
+            let cost_basis = info.entry_amount * info.remaining_amount / 100.0;
+            let metrics = token_metrics(&mint);
+            positions.insert(mint, RealTimeTokenMetrics {
+                cost_basis,
+                unrealized_pnl_usd: cost_basis * info.calculate_pnl() / 100.0,
+                ..metrics
+            });
+        }
+    }
+    let invested: f64 = positions.values().map(|metrics| metrics.cost_basis).sum();
+    Ok((wallet_balance + invested, positions))
+}
+
+fn read_state_file(path: &str) -> Result<Option<RiskState>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| anyhow!("Failed to parse risk state {}: {}", path, e)),
+        Err(_) => Ok(None),
+    }
+}
+
+fn write_state_file(path: &str, state: &RiskState) -> Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(state)?)
+        .map_err(|e| anyhow!("Failed to write risk state {}: {}", path, e))
+}
+
+/// Snapshot the counters under the lock and write them to `RISK_STATE_FILE` from a blocking task
+///
+/// Snapshots are numbered so a slow write never overwrites a newer one.
+fn persist_risk_state() {
+    let (snapshot, state) = {
+        let engine = risk_engine();
+        (STATE_SNAPSHOTS.fetch_add(1, Ordering::SeqCst) + 1, engine.state())
+    };
+    tokio::task::spawn_blocking(move || {
+        let mut written = STATE_WRITTEN.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if *written >= snapshot {
+            return;
+        }
+        match write_state_file(&RISK_STATE_FILE, &state) {
+            Ok(()) => *written = snapshot,
+            Err(e) => {
+                Logger::new("[RISK-MGMT] => ".red().to_string()).log(format!("{}", e).red().to_string());
+            }
+        }
+    });
+}
+
+/// Gate a buy: `Err` carries the reason it is blocked, `Ok` reserves the largest size allowed
+///
+/// The size is also capped by the exposure left under the total, per-token, per-target and per-venue limits.
+/// Buys are refused until the first portfolio refresh, since every cap is relative to the portfolio value.
+pub fn approve_new_position(token_mint: &str, target: Option<&str>, venue: &SwapProtocol) -> Result<ExposureReservation, String> {
+    if !is_risk_management_enabled() {
+        return Ok(ExposureReservation { id: None, max_size: None });
+    }
+    let metrics = token_metrics(token_mint);
+    // The engine lock is held from reading the book to reserving, so concurrent approvals cannot both take the same headroom
+    let mut engine = risk_engine();
+    match engine.should_allow_new_position(&metrics) {
+        Ok(true) => {}
+        Ok(false) => return Err(engine.get_risk_status()),
+        Err(e) => return Err(e.to_string()),
+    }
+    let Some(max_size) = engine.max_position_size_sol(token_mint, &metrics) else {
+        return Err("portfolio value not known yet".to_string());
+    };
+
+    let book = ExposureBook::current();
+    let portfolio_value = engine.portfolio_metrics.total_portfolio_value;
+    let (headroom, limit) = exposure_headroom(portfolio_value, &book, token_mint, target, venue, &engine.config);
+    if headroom <= 0.0 {
+        engine.logger.log(format!("🚫 BLOCKED: {} reached", limit).red().to_string());
+        return Err(format!("{} reached", limit));
+    }
+    let size = if headroom < max_size {
+        engine.logger.log(format!(
+            "📉 Downsized {} from {:.4} to {:.4} SOL, binding limit: {}",
+            token_mint, max_size, headroom, limit
+        ).yellow().to_string());
+        headroom
+    } else {
+        max_size
+    };
+
+    let id = NEXT_RESERVATION.fetch_add(1, Ordering::Relaxed);
+    PENDING_EXPOSURE.insert(id, PendingExposure {
+        mint: token_mint.to_string(),
+        target: target.unwrap_or_default().to_string(),
+        venue: venue.clone(),
+        amount_sol: size,
+    });
+    Ok(ExposureReservation { id: Some(id), max_size: Some(size) })
+}
+
+/// Count a bought position and persist the counters
+pub fn record_new_position() {
+    if !is_risk_management_enabled() {
+        return;
+    }
+    risk_engine().record_new_position();
+    persist_risk_state();
+}
+
+/// Feed the realized PnL of a closed position and persist the counters
+pub fn record_position_closed(pnl_sol: f64, pnl_percentage: f64) {
+    if !is_risk_management_enabled() {
+        return;
+    }
+    risk_engine().record_realized_pnl(pnl_sol, pnl_percentage);
+    persist_risk_state();
+}
+
+/// Position PnL in percent at which the watchdog market-sells it
+pub fn emergency_stop_loss_percentage() -> f64 {
+    RISK_CONFIG.emergency_stop_loss_percentage
+}
+
+/// Whether the engine vetoes a buy on its rug check verdict
+pub fn veto_rug_check(verdict: &RugCheckVerdict) -> bool {
+    risk_engine().should_veto_rug_check(verdict)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_prices_have_no_volatility() {
+        assert_eq!(realized_volatility(&[1.0; 20]), 0.0);
+        assert_eq!(realized_volatility(&[1.0, 2.0]), 0.0);
+    }
+
+    #[test]
+    fn volatility_grows_with_swing_size() {
+        let calm: Vec<f64> = (0..30).map(|i| if i % 2 == 0 { 1.0 } else { 1.01 }).collect();
+        let wild: Vec<f64> = (0..30).map(|i| if i % 2 == 0 { 1.0 } else { 1.5 }).collect();
+        let calm_vol = realized_volatility(&calm);
+        let wild_vol = realized_volatility(&wild);
+        assert!(calm_vol > 0.0 && calm_vol < 2.0, "calm volatility {}", calm_vol);
+        assert!(wild_vol > 30.0, "wild volatility {}", wild_vol);
+    }
+
+    #[test]
+    fn steady_trend_has_low_volatility() {
+        // Constant 1% steps: identical log returns, so no dispersion around the mean
+        let trend: Vec<f64> = (0..30).map(|i| 1.01f64.powi(i)).collect();
+        assert!(realized_volatility(&trend) < 1e-9);
+    }
+
+    #[test]
+    fn net_flow_classifies_market() {
+        let config = RiskManagementConfig::default();
+        assert_eq!(classify_flow(0.0, 0.0, &config), MarketCondition::Normal);
+        assert_eq!(classify_flow(1.0, 1.0, &config), MarketCondition::Normal);
+        assert_eq!(classify_flow(1.0, 3.0, &config), MarketCondition::BearDump);
+        assert_eq!(classify_flow(3.0, 1.0, &config), MarketCondition::BullRun);
+        assert_eq!(classify_flow(0.0, 2.0, &config), MarketCondition::BearDump);
+    }
+
+    #[test]
+    fn drawdown_is_measured_from_high_water_mark() {
+        let mut engine = RiskManagementEngine::new(RiskManagementConfig::default());
+        let no_positions = HashMap::new();
+        for value in [10.0, 12.0, 9.0] {
+            engine.update_portfolio_metrics(value, &no_positions);
+        }
+        assert!((engine.portfolio_metrics.current_drawdown - -25.0).abs() < 1e-9);
+
+        // Recovering below the peak keeps the drawdown relative to 12 SOL
+        engine.update_portfolio_metrics(11.4, &no_positions);
+        assert!((engine.portfolio_metrics.current_drawdown - -5.0).abs() < 1e-9);
+
+        // A new high clears it
+        engine.update_portfolio_metrics(13.0, &no_positions);
+        assert_eq!(engine.portfolio_metrics.current_drawdown, 0.0);
+        assert_eq!(engine.state().peak_value, 13.0);
+    }
+
+    #[test]
+    fn daily_pnl_includes_unrealized_positions() {
+        let mut engine = RiskManagementEngine::new(RiskManagementConfig::default());
+        engine.update_portfolio_metrics(10.0, &HashMap::new());
+        engine.reset_daily_counters();
+        engine.record_realized_pnl(-0.2, -20.0);
+
+        let mut positions = HashMap::new();
+        positions.insert("mint".to_string(), RealTimeTokenMetrics {
+            cost_basis: 1.0,
+            unrealized_pnl_usd: -0.3,
+            ..Default::default()
+        });
+        engine.update_portfolio_metrics(9.8, &positions);
+        assert!((engine.portfolio_metrics.daily_pnl - -5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn exposure_headroom_picks_the_tightest_cap() {
+        let config = RiskManagementConfig::default();
+        let mut book = ExposureBook::default();
+        book.add("mint_a", "target_1", &SwapProtocol::PumpFun, 1.0);
+        book.add("mint_b", "target_1", &SwapProtocol::PumpFun, 2.5);
+        book.add("mint_c", "", &SwapProtocol::PumpSwap, 3.0);
+
+        // 10 SOL portfolio: total 6.5 / 8, token a 1 / 2, venue PumpFun 3.5 / 6, target 3.5 / 4
+        let (headroom, limit) = exposure_headroom(10.0, &book, "mint_a", Some("target_1"), &SwapProtocol::PumpFun, &config);
+        assert!((headroom - 0.5).abs() < 1e-9);
+        assert!(limit.starts_with("target target_1"), "{}", limit);
+
+        let (headroom, limit) = exposure_headroom(10.0, &book, "mint_d", None, &SwapProtocol::PumpSwap, &config);
+        assert!((headroom - 1.5).abs() < 1e-9);
+        assert!(limit.starts_with("total"), "{}", limit);
+
+        let (headroom, _) = exposure_headroom(5.0, &book, "mint_d", None, &SwapProtocol::PumpSwap, &config);
+        assert!(headroom < 0.0);
+    }
+
+    #[test]
+    fn trading_day_follows_configured_boundary() {
+        // 2024-03-10 05:30:00 UTC
+        let timestamp = 1_710_048_600;
+        assert_eq!(trading_day(timestamp, 0), "2024-03-10");
+        assert_eq!(trading_day(timestamp, 5), "2024-03-10");
+        assert_eq!(trading_day(timestamp, 6), "2024-03-09");
+    }
+}
@@ -0,0 +1,247 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use spl_token_2022::extension::{
+    transfer_fee::TransferFeeConfig, BaseStateWithExtensions, ExtensionType, StateWithExtensionsOwned,
+};
+use spl_token_2022::state::Mint;
+use spl_token_client::token::TokenError;
+
+use crate::dex::pump_fun::PUMP_PROGRAM;
+use crate::engine::transaction_parser::{read_u64, DexType, TradeInfoFromToken};
+use crate::library::config::AppState;
+use crate::tx_processor::token;
+
+// Real token reserves a PumpFun bonding curve starts with (793.1M tokens, 6 decimals)
+const PUMP_INITIAL_REAL_TOKEN_RESERVES: f64 = 793_100_000_000_000.0;
+// A mint created in the slot we saw may not be on the RPC node yet
+const MINT_LOOKUP_ATTEMPTS: u32 = 5;
+const MINT_LOOKUP_DELAY: Duration = Duration::from_millis(200);
+
+/// Limits of the on-chain safety check run before every buy
+#[derive(Debug, Clone)]
+pub struct RugCheckConfig {
+    pub enabled: bool,
+    pub max_transfer_fee_bps: u16,
+    pub max_top_holder_percentage: f64, // largest wallet, pools and curves excluded
+    pub max_top10_percentage: f64,
+    pub min_curve_progress: f64,
+    pub max_curve_progress: f64,
+}
+
+impl RugCheckConfig {
+    /// Load from `RUG_CHECK_ENABLED`, `RUG_MAX_TRANSFER_FEE_BPS`, `RUG_MAX_TOP_HOLDER_PERCENTAGE`,
+    /// `RUG_MAX_TOP10_PERCENTAGE`, `RUG_MIN_CURVE_PROGRESS` and `RUG_MAX_CURVE_PROGRESS`
+    pub fn from_env() -> Self {
+        let f64_var = |name: &str, default: f64| {
+            std::env::var(name).ok().and_then(|v| v.parse::<f64>().ok()).unwrap_or(default)
+        };
+        Self {
+            enabled: std::env::var("RUG_CHECK_ENABLED")
+                .ok()
+                .and_then(|v| v.parse::<bool>().ok())
+                .unwrap_or(true),
+            max_transfer_fee_bps: std::env::var("RUG_MAX_TRANSFER_FEE_BPS")
+                .ok()
+                .and_then(|v| v.parse::<u16>().ok())
+                .unwrap_or(500),
+            max_top_holder_percentage: f64_var("RUG_MAX_TOP_HOLDER_PERCENTAGE", 20.0),
+            max_top10_percentage: f64_var("RUG_MAX_TOP10_PERCENTAGE", 50.0),
+            min_curve_progress: f64_var("RUG_MIN_CURVE_PROGRESS", 0.0),
+            max_curve_progress: f64_var("RUG_MAX_CURVE_PROGRESS", 100.0),
+        }
+    }
+}
+
+/// Result of the safety check on a mint; `risks` lists every failed check
+#[derive(Debug, Clone, Default)]
+pub struct RugCheckVerdict {
+    pub mint: String,
+    pub mint_authority_revoked: bool,
+    pub freeze_authority_absent: bool,
+    pub is_token_2022: bool,
+    pub dangerous_extensions: Vec<String>,
+    pub transfer_fee_bps: Option<u16>,
+    pub top_holder_percentage: Option<f64>,
+    pub top10_percentage: Option<f64>,
+    pub curve_progress: Option<f64>,
+    pub risks: Vec<String>,
+}
+
+impl RugCheckVerdict {
+    pub fn is_safe(&self) -> bool {
+        self.risks.is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        if self.is_safe() {
+            format!(
+                "safe | top holder {:.1}% | top10 {:.1}% | curve {:.1}%",
+                self.top_holder_percentage.unwrap_or(0.0),
+                self.top10_percentage.unwrap_or(0.0),
+                self.curve_progress.unwrap_or(0.0)
+            )
+        } else {
+            self.risks.join(", ")
+        }
+    }
+}
+
+/// Run the safety check on the mint of `trade_info`
+pub async fn check_mint(
+    app_state: &AppState,
+    config: &RugCheckConfig,
+    trade_info: &TradeInfoFromToken,
+) -> Result<RugCheckVerdict> {
+    let mint = Pubkey::from_str(&trade_info.mint)
+        .map_err(|e| anyhow!("Invalid mint {}: {}", trade_info.mint, e))?;
+    let mut verdict = RugCheckVerdict {
+        mint: trade_info.mint.clone(),
+        ..Default::default()
+    };
+
+    let mint_info = load_mint(app_state, &mint).await?;
+
+    verdict.mint_authority_revoked = mint_info.base.mint_authority.is_none();
+    verdict.freeze_authority_absent = mint_info.base.freeze_authority.is_none();
+    if !verdict.mint_authority_revoked {
+        verdict.risks.push("mint authority not revoked".to_string());
+    }
+    if !verdict.freeze_authority_absent {
+        verdict.risks.push("freeze authority set".to_string());
+    }
+
+    let extensions = mint_info.get_extension_types().unwrap_or_default();
+    // Only Token-2022 mints carry extension data; one without any has none of the risks below
+    verdict.is_token_2022 = !extensions.is_empty();
+    for extension in &extensions {
+        let name = match extension {
+            ExtensionType::PermanentDelegate => "permanent delegate",
+            ExtensionType::TransferHook => "transfer hook",
+            ExtensionType::NonTransferable => "non-transferable",
+            _ => continue,
+        };
+        verdict.dangerous_extensions.push(name.to_string());
+        verdict.risks.push(format!("Token-2022 {}", name));
+    }
+    if let Ok(fee_config) = mint_info.get_extension::<TransferFeeConfig>() {
+        let fee_bps = u16::from(fee_config.newer_transfer_fee.transfer_fee_basis_points);
+        verdict.transfer_fee_bps = Some(fee_bps);
+        if fee_bps > config.max_transfer_fee_bps {
+            verdict.risks.push(format!("transfer fee {} bps > {} bps", fee_bps, config.max_transfer_fee_bps));
+        }
+    }
+
+    let supply = mint_info.base.supply;
+    if supply > 0 {
+        let holders = largest_wallet_holders(app_state, &mint).await?;
+        let top = holders.first().map(|(_, amount)| *amount).unwrap_or(0);
+        let top10: u64 = holders.iter().take(10).map(|(_, amount)| amount).sum();
+        let top_pct = top as f64 / supply as f64 * 100.0;
+        let top10_pct = top10 as f64 / supply as f64 * 100.0;
+        verdict.top_holder_percentage = Some(top_pct);
+        verdict.top10_percentage = Some(top10_pct);
+        if top_pct > config.max_top_holder_percentage {
+            verdict.risks.push(format!("top holder owns {:.1}% > {}%", top_pct, config.max_top_holder_percentage));
+        }
+        if top10_pct > config.max_top10_percentage {
+            verdict.risks.push(format!("top 10 holders own {:.1}% > {}%", top10_pct, config.max_top10_percentage));
+        }
+    }
+
+    verdict.curve_progress = curve_progress(app_state, trade_info, &mint).await;
+    if let Some(progress) = verdict.curve_progress {
+        if progress < config.min_curve_progress || progress > config.max_curve_progress {
+            verdict.risks.push(format!(
+                "bonding curve {:.1}% outside {} - {}%",
+                progress, config.min_curve_progress, config.max_curve_progress
+            ));
+        }
+    }
+
+    Ok(verdict)
+}
+
+/// Mint state through the shared mint cache
+///
+/// A launch is seen on the stream before every RPC node has it, so a missing mint is looked up
+/// again for a short while before giving up.
+async fn load_mint(app_state: &AppState, mint: &Pubkey) -> Result<StateWithExtensionsOwned<Mint>> {
+    let mut attempt = 1;
+    loop {
+        match token::get_mint_info(app_state.rpc_nonblocking_client.clone(), app_state.wallet.clone(), *mint).await {
+            Ok(state) => return Ok(state),
+            Err(TokenError::AccountNotFound) if attempt < MINT_LOOKUP_ATTEMPTS => {
+                attempt += 1;
+                tokio::time::sleep(MINT_LOOKUP_DELAY).await;
+            }
+            Err(TokenError::AccountNotFound) => {
+                return Err(anyhow!("Mint {} not visible after {} lookups", mint, MINT_LOOKUP_ATTEMPTS));
+            }
+            Err(e) => return Err(anyhow!("Failed to load mint {}: {}", mint, e)),
+        }
+    }
+}
+
+/// Owners and their summed balances among the largest holders that are wallets, largest first
+///
+/// Token accounts owned by PDAs (bonding curves, pools, vaults) are not holders that can dump. One
+/// wallet may hold several token accounts of the mint, so balances are added up per owner.
+pub async fn largest_wallet_holders(app_state: &AppState, mint: &Pubkey) -> Result<Vec<(Pubkey, u64)>> {
+    let largest = app_state.rpc_nonblocking_client
+        .get_token_largest_accounts(mint)
+        .await
+        .map_err(|e| anyhow!("Failed to get largest accounts of {}: {}", mint, e))?;
+
+    let addresses: Vec<Pubkey> = largest.iter()
+        .filter_map(|account| Pubkey::from_str(&account.address).ok())
+        .collect();
+    let accounts = app_state.rpc_nonblocking_client
+        .get_multiple_accounts(&addresses)
+        .await
+        .map_err(|e| anyhow!("Failed to load holder accounts of {}: {}", mint, e))?;
+
+    let mut balances: HashMap<Pubkey, u64> = HashMap::new();
+    for (holder, account) in largest.iter().zip(accounts) {
+        // Token account layout: mint (32) then owner (32)
+        let Some(owner) = account
+            .as_ref()
+            .and_then(|account| account.data.get(32..64))
+            .and_then(|bytes| Pubkey::try_from(bytes).ok())
+        else {
+            continue;
+        };
+        if !owner.is_on_curve() {
+            continue;
+        }
+        *balances.entry(owner).or_insert(0) += holder.amount.amount.parse::<u64>().unwrap_or(0);
+    }
+    let mut holders: Vec<(Pubkey, u64)> = balances.into_iter().collect();
+    holders.sort_unstable_by_key(|(_, amount)| Reverse(*amount));
+    Ok(holders)
+}
+
+/// PumpFun bonding curve progress in percent, 100 once migrated
+async fn curve_progress(app_state: &AppState, trade_info: &TradeInfoFromToken, mint: &Pubkey) -> Option<f64> {
+    match trade_info.dex_type {
+        DexType::PumpSwap => return Some(100.0),
+        DexType::PumpFun => {}
+        _ => return None,
+    }
+
+    let real_token_reserves = match trade_info.real_token_reserves {
+        Some(reserves) => reserves,
+        None => {
+            let program = Pubkey::from_str(PUMP_PROGRAM).ok()?;
+            let (bonding_curve, _) = Pubkey::find_program_address(&[b"bonding-curve", mint.as_ref()], &program);
+            let data = app_state.rpc_nonblocking_client.get_account_data(&bonding_curve).await.ok()?;
+            // discriminator, virtual token, virtual sol, then real token reserves
+            let mut offset = 8 + 8 + 8;
+            read_u64(&data, &mut offset)?
+        }
+    };
+    Some(((1.0 - real_token_reserves as f64 / PUMP_INITIAL_REAL_TOKEN_RESERVES) * 100.0).clamp(0.0, 100.0))
+}
@@ -0,0 +1,465 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use anyhow::{anyhow, Result};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use colored::Colorize;
+use dashmap::DashMap;
+use futures_util::stream::StreamExt;
+use futures_util::SinkExt;
+use lazy_static::lazy_static;
+use tokio::time::sleep;
+use yellowstone_grpc_client::{ClientTlsConfig, GeyserGrpcClient};
+use yellowstone_grpc_proto::geyser::{
+    subscribe_update::UpdateOneof, CommitmentLevel, SubscribeRequest, SubscribeRequestPing,
+    SubscribeRequestFilterTransactions, SubscribeUpdateTransaction,
+};
+
+use crate::dex::pump_fun::PUMP_PROGRAM;
+use crate::dex::raydium_launchpad::RAYDIUM_LAUNCHPAD_PROGRAM_ID;
+use crate::engine::backtest;
+use crate::engine::circuit_breaker;
+use crate::engine::comprehensive_selling::{self, ComprehensiveSelling};
+use crate::engine::copy_trading::is_buying_enabled;
+use crate::engine::creator_registry::{self, CreatorVerdict};
+use crate::engine::dump_detector;
+use crate::engine::journal;
+use crate::engine::migration;
+use crate::engine::launch_filter::LaunchFilterConfig;
+use crate::engine::position_sizing::SizingDecision;
+use crate::engine::risk_management;
+use crate::engine::rug_check::{self, RugCheckConfig};
+use crate::engine::swap::{execute_swap, SwapDirection, SwapProtocol};
+use crate::engine::transaction_parser::{self, DexType, LaunchInfo, TradeInfoFromToken};
+use crate::library::{
+    config::{AppState, SwapConfig},
+    logger::Logger,
+};
+use crate::utilities::telegram;
+
+// Sniped mints with the SOL committed and when, and the newest slot seen on the stream
+lazy_static! {
+    static ref SNIPER_POSITIONS: Arc<DashMap<String, (f64, Instant)>> = Arc::new(DashMap::new());
+    static ref SNIPER_RESERVE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    static ref LATEST_SLOT: Arc<DashMap<(), u64>> = Arc::new(DashMap::new());
+}
+
+/// Settings of the launch sniper, independent of copy trading
+#[derive(Clone)]
+pub struct SniperConfig {
+    pub yellowstone_grpc_http: String,
+    pub yellowstone_grpc_token: String,
+    pub app_state: Arc<AppState>,
+    pub swap_config: Arc<SwapConfig>,
+    pub buy_amount_sol: f64,
+    pub budget_sol: f64,     // SOL allowed in open sniper positions at once
+    pub max_positions: usize,
+    pub min_dev_buy: f64,
+    pub max_dev_buy: f64,
+    pub max_slot_delay: u64, // skip launches older than this many slots
+    pub filters: LaunchFilterConfig,
+    pub rug_check: RugCheckConfig,
+}
+
+impl SniperConfig {
+    /// Sniper settings from `SNIPER_BUY_AMOUNT`, `SNIPER_BUDGET`, `SNIPER_MAX_POSITIONS` and
+    /// `SNIPER_MAX_SLOT_DELAY`; the dev-buy band comes from `MIN_DEV_BUY` / `MAX_DEV_BUY`
+    pub fn new(
+        yellowstone_grpc_http: String,
+        yellowstone_grpc_token: String,
+        app_state: Arc<AppState>,
+        swap_config: Arc<SwapConfig>,
+        min_dev_buy: f64,
+        max_dev_buy: f64,
+    ) -> Self {
+        Self {
+            yellowstone_grpc_http,
+            yellowstone_grpc_token,
+            app_state,
+            swap_config,
+            buy_amount_sol: std::env::var("SNIPER_BUY_AMOUNT")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(0.01),
+            budget_sol: std::env::var("SNIPER_BUDGET")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(0.1),
+            max_positions: std::env::var("SNIPER_MAX_POSITIONS")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(3),
+            min_dev_buy,
+            max_dev_buy,
+            max_slot_delay: std::env::var("SNIPER_MAX_SLOT_DELAY")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(1),
+            filters: LaunchFilterConfig::from_env(),
+            rug_check: RugCheckConfig::from_env(),
+        }
+    }
+}
+
+/// Whether the sniper is switched on with `SNIPER_ENABLED`
+pub fn is_sniper_enabled() -> bool {
+    std::env::var("SNIPER_ENABLED")
+        .ok()
+        .and_then(|v| v.parse::<bool>().ok())
+        .unwrap_or(false)
+}
+
+/// Watch PumpFun and Launchpad creates and buy launches whose dev buy is inside the band
+pub async fn start_sniper(config: SniperConfig) -> Result<()> {
+    let logger = Logger::new("[SNIPER] => ".bright_magenta().to_string());
+    logger.log(format!(
+        "Sniping launches with dev buy {} - {} SOL | {} SOL per snipe | budget {} SOL | max {} positions | max delay {} slots",
+        config.min_dev_buy,
+        config.max_dev_buy,
+        config.buy_amount_sol,
+        config.budget_sol,
+        config.max_positions,
+        config.max_slot_delay
+    ));
+
+    creator_registry::init_creator_registry();
+    risk_management::init_risk_engine(config.app_state.clone());
+    circuit_breaker::start_circuit_breaker(config.app_state.clone());
+    let selling = Arc::new(ComprehensiveSelling::new(config.app_state.clone(), config.swap_config.clone()));
+    selling.start_stop_loss_watchdog();
+    match selling.restore_positions().await {
+        Ok(restored) if restored > 0 => {
+            logger.log(format!("Restored {} open positions", restored).green().to_string());
+        }
+        Ok(_) => {}
+        Err(e) => {
+            logger.log(format!("Position book not restored: {}", e).red().to_string());
+        }
+    }
+    dump_detector::start_dump_detector(
+        config.yellowstone_grpc_http.clone(),
+        config.yellowstone_grpc_token.clone(),
+        selling.clone(),
+    );
+    migration::start_migration_watcher(
+        config.yellowstone_grpc_http.clone(),
+        config.yellowstone_grpc_token.clone(),
+        selling.clone(),
+    );
+    let config = Arc::new(config);
+
+    loop {
+        match run_launch_stream(config.clone(), selling.clone(), &logger).await {
+            Ok(_) => logger.log("Launch stream closed, reconnecting...".yellow().to_string()),
+            Err(e) => logger.log(format!("Launch stream error: {}, reconnecting...", e).red().to_string()),
+        };
+        sleep(Duration::from_secs(2)).await;
+    }
+}
+
+async fn run_launch_stream(
+    config: Arc<SniperConfig>,
+    selling: Arc<ComprehensiveSelling>,
+    logger: &Logger,
+) -> Result<()> {
+    let mut client = GeyserGrpcClient::build_from_shared(config.yellowstone_grpc_http.clone())?
+        .x_token(Some(config.yellowstone_grpc_token.clone()))?
+        .tls_config(ClientTlsConfig::new().with_native_roots())?
+        .connect()
+        .await?;
+    let (mut subscribe_tx, mut stream) = client.subscribe().await?;
+
+    let mut transactions = HashMap::new();
+    transactions.insert("launches".to_string(), SubscribeRequestFilterTransactions {
+        vote: Some(false),
+        failed: Some(false),
+        signature: None,
+        account_include: vec![PUMP_PROGRAM.to_string(), RAYDIUM_LAUNCHPAD_PROGRAM_ID.to_string()],
+        account_exclude: Vec::new(),
+        account_required: Vec::new(),
+    });
+    subscribe_tx.send(SubscribeRequest {
+        transactions,
+        commitment: Some(CommitmentLevel::Processed as i32),
+        ..Default::default()
+    }).await?;
+    logger.log("Subscribed to launch transactions".green().to_string());
+
+    while let Some(message) = stream.next().await {
+        let update = message.map_err(|e| anyhow!("{}", e))?;
+        circuit_breaker::record_stream_activity();
+        backtest::record_update(&update);
+        match update.update_oneof {
+            Some(UpdateOneof::Transaction(txn)) => {
+                let mut latest = LATEST_SLOT.entry(()).or_insert(0);
+                if txn.slot > *latest {
+                    *latest = txn.slot;
+                }
+                drop(latest);
+
+                let config = config.clone();
+                let selling = selling.clone();
+                tokio::spawn(async move {
+                    process_launch_transaction(config, selling, txn).await;
+                });
+            }
+            Some(UpdateOneof::Ping(_)) => {
+                subscribe_tx.send(SubscribeRequest {
+                    ping: Some(SubscribeRequestPing { id: 1 }),
+                    ..Default::default()
+                }).await?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+async fn process_launch_transaction(
+    config: Arc<SniperConfig>,
+    selling: Arc<ComprehensiveSelling>,
+    txn: SubscribeUpdateTransaction,
+) {
+    let logger = Logger::new("[SNIPER] => ".bright_magenta().to_string());
+
+    let launch = match transaction_parser::parse_launch_transaction(&txn) {
+        Some(launch) => launch,
+        None => {
+            // Every other venue trade still prices the positions we hold and feeds the creator history
+            if let Some(trade) = transaction_parser::parse_transaction_data(&txn) {
+                if let Some(price) = trade.price_in_sol() {
+                    comprehensive_selling::update_token_price(&trade.mint, price);
+                }
+                creator_registry::record_trade(&trade);
+                risk_management::record_market_trade(&trade);
+            }
+            return;
+        }
+    };
+    creator_registry::record_launch(&launch);
+    // Raydium Launchpad swaps are not supported yet, its launches only feed the creator history
+    if launch.dex_type == DexType::RaydiumLaunchpad {
+        return;
+    }
+
+    if let Err(e) = handle_launch(&config, &selling, launch).await {
+        logger.log(format!("{}", e).red().to_string());
+    }
+}
+
+/// Log and journal why a launch is not sniped
+fn skip_launch(logger: &Logger, mint: &str, reason: String) {
+    logger.log(reason.yellow().to_string());
+    journal::record_decision(mint, "", false, &reason);
+}
+
+/// Why a launch is too old to buy, `None` while it is within `max_slot_delay` of the stream
+fn slot_delay_exceeded(config: &SniperConfig, launch: &LaunchInfo) -> Option<String> {
+    let latest_slot = LATEST_SLOT.get(&()).map(|slot| *slot).unwrap_or(launch.slot);
+    (latest_slot > launch.slot + config.max_slot_delay).then(|| format!(
+        "Skipping {}: launched in slot {}, stream is at {}",
+        launch.mint, launch.slot, latest_slot
+    ))
+}
+
+/// Sniper slot and budget claimed for a launch, given back on drop unless the buy went through
+struct SnipeReservation {
+    mint: String,
+    committed: bool,
+}
+
+impl SnipeReservation {
+    /// Lower the SOL held against the budget once the buy size is final
+    fn resize(&self, amount_sol: f64) {
+        if let Some(mut entry) = SNIPER_POSITIONS.get_mut(&self.mint) {
+            entry.0 = amount_sol;
+        }
+    }
+
+    fn commit(&mut self) {
+        self.committed = true;
+    }
+}
+
+impl Drop for SnipeReservation {
+    fn drop(&mut self) {
+        if !self.committed {
+            SNIPER_POSITIONS.remove(&self.mint);
+        }
+    }
+}
+
+/// Check the position limit and budget and claim both for `mint` in one step
+fn reserve_snipe(config: &SniperConfig, mint: &str, amount_sol: f64) -> Result<SnipeReservation, String> {
+    let _guard = SNIPER_RESERVE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    // Forget snipes whose positions have been closed, buys still in flight are kept
+    SNIPER_POSITIONS.retain(|mint, (_, sniped_at)| {
+        comprehensive_selling::get_bought_token(mint).is_some() || sniped_at.elapsed() < Duration::from_secs(60)
+    });
+    if SNIPER_POSITIONS.contains_key(mint) {
+        return Err(format!("Already sniping {}", mint));
+    }
+    if SNIPER_POSITIONS.len() >= config.max_positions {
+        return Err(format!("Max {} sniper positions open, skipping {}", config.max_positions, mint));
+    }
+    let committed: f64 = SNIPER_POSITIONS.iter().map(|entry| entry.value().0).sum();
+    if committed + amount_sol > config.budget_sol {
+        return Err(format!(
+            "Sniper budget {} SOL exhausted ({:.4} SOL committed), skipping {}",
+            config.budget_sol, committed, mint
+        ));
+    }
+    SNIPER_POSITIONS.insert(mint.to_string(), (amount_sol, Instant::now()));
+    Ok(SnipeReservation { mint: mint.to_string(), committed: false })
+}
+
+async fn handle_launch(config: &SniperConfig, selling: &ComprehensiveSelling, launch: LaunchInfo) -> Result<()> {
+    let logger = Logger::new("[SNIPER] => ".bright_magenta().to_string());
+    // Recorded before any gate so launches dropped for any reason still count as recent
+    let duplicate_of = config.filters.register_launch(&launch);
+    let dev_buy = launch.dev_buy_sol();
+
+    logger.log(format!(
+        "Launch {} ({}) on {:?} by {} | dev buy {:.4} SOL | slot {}",
+        launch.mint, launch.symbol, launch.dex_type, launch.creator, dev_buy, launch.slot
+    ));
+
+    if dev_buy < config.min_dev_buy || dev_buy > config.max_dev_buy {
+        skip_launch(&logger, &launch.mint, format!(
+            "Skipping {}: dev buy {:.4} SOL outside {} - {} SOL",
+            launch.mint, dev_buy, config.min_dev_buy, config.max_dev_buy
+        ));
+        return Ok(());
+    }
+
+    if !is_buying_enabled() {
+        skip_launch(&logger, &launch.mint, format!("Buying disabled, skipping {}", launch.mint));
+        return Ok(());
+    }
+
+    let mut buy_amount_sol = config.buy_amount_sol;
+    match creator_registry::evaluate_creator(&launch.creator) {
+        CreatorVerdict::Blocked(reason) => {
+            skip_launch(&logger, &launch.mint, format!("Rejected {}: creator {} blocked, {}", launch.mint, launch.creator, reason));
+            return Ok(());
+        }
+        CreatorVerdict::Preferred(reason) => {
+            buy_amount_sol *= creator_registry::CREATOR_CONFIG.preferred_multiplier;
+            logger.log(format!("Preferred creator {} ({}), buying {} SOL", launch.creator, reason, buy_amount_sol));
+        }
+        CreatorVerdict::Unknown | CreatorVerdict::Neutral => {}
+    }
+
+    // Every filter below may go to the network, a stale launch is dropped before any of them
+    if let Some(reason) = slot_delay_exceeded(config, &launch) {
+        skip_launch(&logger, &launch.mint, reason);
+        return Ok(());
+    }
+
+    let mut reservation = match reserve_snipe(config, &launch.mint, buy_amount_sol) {
+        Ok(reservation) => reservation,
+        Err(reason) => {
+            skip_launch(&logger, &launch.mint, reason);
+            return Ok(());
+        }
+    };
+
+    match config.filters.check(&config.app_state, &launch, duplicate_of).await {
+        Ok(metadata) => {
+            logger.log(format!(
+                "{} passed filters: {} ({}) | twitter: {} | telegram: {} | website: {}",
+                launch.mint,
+                metadata.name,
+                metadata.symbol,
+                metadata.twitter.is_some(),
+                metadata.telegram.is_some(),
+                metadata.website.is_some()
+            ));
+        }
+        Err(reason) => {
+            skip_launch(&logger, &launch.mint, format!("Rejected {}: {}", launch.mint, reason));
+            return Ok(());
+        }
+    }
+    // Metadata fetches can take long enough for the launch to go stale
+    if let Some(reason) = slot_delay_exceeded(config, &launch) {
+        skip_launch(&logger, &launch.mint, reason);
+        return Ok(());
+    }
+
+    // Sniper positions carry no target so they stay out of the target stats
+    let trade_info = launch.dev_buy.clone().unwrap_or_else(|| TradeInfoFromToken {
+        dex_type: launch.dex_type.clone(),
+        slot: launch.slot,
+        signature: launch.signature.clone(),
+        mint: launch.mint.clone(),
+        user: launch.creator.clone(),
+        is_buy: true,
+        ..Default::default()
+    });
+
+    if config.rug_check.enabled {
+        match rug_check::check_mint(&config.app_state, &config.rug_check, &trade_info).await {
+            Ok(verdict) if !risk_management::veto_rug_check(&verdict) => {
+                logger.log(format!("Rug check {}: {}", launch.mint, verdict.summary()));
+            }
+            Ok(verdict) => {
+                skip_launch(&logger, &launch.mint, format!("Rejected {}: {}", launch.mint, verdict.summary()));
+                return Ok(());
+            }
+            Err(e) => {
+                skip_launch(&logger, &launch.mint, format!("Rejected {}: rug check failed, {}", launch.mint, e));
+                return Ok(());
+            }
+        }
+    }
+    if let Some(reason) = slot_delay_exceeded(config, &launch) {
+        skip_launch(&logger, &launch.mint, reason);
+        return Ok(());
+    }
+
+    let mut exposure = match risk_management::approve_new_position(&launch.mint, None, &SwapProtocol::from(&launch.dex_type)) {
+        Ok(exposure) => exposure,
+        Err(status) => {
+            skip_launch(&logger, &launch.mint, format!("Risk engine blocked {}: {}", launch.mint, status));
+            return Ok(());
+        }
+    };
+    if let Some(max_position_sol) = exposure.max_size().filter(|max| buy_amount_sol > *max) {
+        logger.log(format!("Risk engine caps {} at {:.4} SOL", launch.mint, max_position_sol));
+        buy_amount_sol = max_position_sol;
+    }
+    if buy_amount_sol <= 0.0 {
+        skip_launch(&logger, &launch.mint, format!("Skipping {}: no buy size left", launch.mint));
+        return Ok(());
+    }
+
+    reservation.resize(buy_amount_sol);
+    exposure.resize(buy_amount_sol);
+
+    journal::record_decision(&launch.mint, "", true, &format!("snipe {:.4} SOL, dev buy {:.4} SOL", buy_amount_sol, dev_buy));
+    let protocol = SwapProtocol::from(&launch.dex_type);
+    let mint_pubkey = Pubkey::from_str(&launch.mint).map_err(|e| anyhow!("Invalid mint {}: {}", launch.mint, e))?;
+    let amount = (buy_amount_sol * 1_000_000_000.0) as u64;
+
+    let signature = match execute_swap(&protocol, SwapDirection::Buy, &mint_pubkey, amount, config.swap_config.slippage).await {
+        Ok(signature) => signature,
+        Err(e) => return Err(anyhow!("Snipe of {} failed: {}", launch.mint, e)),
+    };
+    reservation.commit();
+    logger.log(format!("Sniped {} for {} SOL: {}", launch.mint, buy_amount_sol, signature).green().to_string());
+    risk_management::record_new_position();
+
+    if let Err(e) = telegram::send_trade_notification(&trade_info, &format!("{:?}", protocol), "SNIPED").await {
+        logger.log(format!("Failed to send Telegram notification: {}", e).yellow().to_string());
+    }
+
+    let monitoring = selling.start_monitoring(&launch.mint, &signature, trade_info, SizingDecision {
+        amount_sol: buy_amount_sol,
+        reason: format!("snipe, dev buy {:.4} SOL", dev_buy),
+    }).await;
+    // The position now carries the exposure
+    drop(exposure);
+    monitoring
+}
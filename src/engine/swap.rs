@@ -46,3 +46,81 @@ impl Default for SwapProtocol {
         SwapProtocol::Auto
     }
 }
+
+impl From<&crate::engine::transaction_parser::DexType> for SwapProtocol {
+    fn from(dex_type: &crate::engine::transaction_parser::DexType) -> Self {
+        match dex_type {
+            crate::engine::transaction_parser::DexType::PumpFun => SwapProtocol::PumpFun,
+            crate::engine::transaction_parser::DexType::PumpSwap => SwapProtocol::PumpSwap,
+            crate::engine::transaction_parser::DexType::RaydiumLaunchpad => SwapProtocol::RaydiumLaunchpad,
+            crate::engine::transaction_parser::DexType::Unknown => SwapProtocol::Unknown,
+        }
+    }
+}
+
+/// Route a swap to the venue that trades the token
+///
+/// `amount` is in lamports for buys and in raw token units for sells, `slippage` in basis points.
+/// Returns the signature of the landed transaction.
+pub async fn execute_swap(
+    protocol: &SwapProtocol,
+    direction: SwapDirection,
+    token_mint: &anchor_client::solana_sdk::pubkey::Pubkey,
+    amount: u64,
+    slippage: u64,
+) -> Result<String, crate::error::ClientError> {
+    use crate::dex::{pump_fun::PumpFun, pump_swap::PumpSwap};
+    use crate::error::ClientError;
+
+    let side = direction.clone();
+    let paper = crate::engine::paper::is_paper_mode();
+    let result = match (protocol, direction) {
+        // Paper runs fill against the observed reserves instead of sending anything
+        _ if paper => {
+            crate::engine::paper::simulate_swap(protocol, &side, token_mint, amount, slippage).await
+        }
+        (SwapProtocol::PumpFun, SwapDirection::Buy) => PumpFun::new().buy(token_mint, amount, slippage).await,
+        (SwapProtocol::PumpFun, SwapDirection::Sell) => PumpFun::new().sell(token_mint, amount, slippage).await,
+        (SwapProtocol::PumpSwap, SwapDirection::Buy) => PumpSwap::new().buy(token_mint, amount, slippage).await,
+        (SwapProtocol::PumpSwap, SwapDirection::Sell) => PumpSwap::new().sell(token_mint, amount, slippage).await,
+        (SwapProtocol::RaydiumLaunchpad, _) => {
+            return Err(ClientError::Other("Raydium Launchpad swaps are not supported yet".to_string()))
+        }
+        (protocol, _) => {
+            return Err(ClientError::InvalidData(format!(
+                "Cannot route swap for unresolved protocol {:?}",
+                protocol
+            )))
+        }
+    };
+    // Only transactions actually sent count towards the circuit breaker, not paper fills or swaps refused before sending
+    if !paper {
+        match &result {
+            Ok(_) => crate::engine::circuit_breaker::record_transaction(true),
+            Err(e) if is_send_failure(e) => crate::engine::circuit_breaker::record_transaction(false),
+            Err(_) => {}
+        }
+    }
+    crate::engine::journal::record_transaction(
+        &token_mint.to_string(),
+        protocol,
+        &side,
+        amount,
+        &result.as_ref().map(String::clone).map_err(|e| e.to_string()),
+    );
+    result
+}
+
+/// Whether a swap failed sending or confirming its transaction rather than before building one
+fn is_send_failure(error: &crate::error::ClientError) -> bool {
+    use crate::error::ClientError;
+
+    matches!(
+        error,
+        ClientError::Send(..)
+            | ClientError::Solana(..)
+            | ClientError::SolanaClientError(_)
+            | ClientError::Jito(..)
+            | ClientError::Timeout(..)
+    )
+}
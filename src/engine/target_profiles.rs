@@ -0,0 +1,257 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use serde::Deserialize;
+
+use crate::engine::copy_trading::MirrorSellMode;
+use crate::engine::swap::SwapProtocol;
+use crate::library::logger::Logger;
+
+// Live profiles keyed by target address, when we last copied each target and its buys in flight
+lazy_static! {
+    static ref TARGET_PROFILES: Arc<DashMap<String, TargetProfile>> = Arc::new(DashMap::new());
+    static ref TARGET_LAST_COPY: Arc<DashMap<String, Instant>> = Arc::new(DashMap::new());
+    static ref TARGET_PENDING_BUYS: Arc<DashMap<String, usize>> = Arc::new(DashMap::new());
+}
+
+/// Copy-trading settings for a single target wallet
+#[derive(Debug, Clone, Deserialize)]
+pub struct TargetProfile {
+    pub address: String,
+    #[serde(default)]
+    pub label: String,
+    /// Fixed SOL amount per copy, overrides the global sizing when set
+    #[serde(default)]
+    pub buy_amount: Option<f64>,
+    /// Slippage in basis points, overrides `SLIPPAGE` when set
+    #[serde(default)]
+    pub slippage: Option<u64>,
+    /// Venues we follow this target on, empty = all
+    #[serde(default)]
+    pub allowed_venues: Vec<SwapProtocol>,
+    #[serde(default)]
+    pub mirror_sells: Option<MirrorSellMode>,
+    /// Open positions copied from this target at the same time, 0 = unlimited
+    #[serde(default)]
+    pub max_open_positions: usize,
+    /// Minimum seconds between two copied buys of this target
+    #[serde(default)]
+    pub cooldown_secs: u64,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl TargetProfile {
+    /// Profile for a target that has no entry in the profile file
+    pub fn with_defaults(address: &str) -> Self {
+        Self {
+            address: address.to_string(),
+            label: String::new(),
+            buy_amount: None,
+            slippage: None,
+            allowed_venues: Vec::new(),
+            mirror_sells: None,
+            max_open_positions: 0,
+            cooldown_secs: 0,
+            enabled: true,
+        }
+    }
+
+    /// Label shown in logs and notifications, falls back to a shortened address
+    pub fn display_name(&self) -> String {
+        if !self.label.is_empty() {
+            return self.label.clone();
+        }
+        short_address(&self.address)
+    }
+
+    pub fn allows_venue(&self, protocol: &SwapProtocol) -> bool {
+        self.allowed_venues.is_empty() || self.allowed_venues.contains(protocol)
+    }
+
+    pub fn mirror_sell_mode(&self) -> MirrorSellMode {
+        self.mirror_sells.unwrap_or(MirrorSellMode::Off)
+    }
+}
+
+fn short_address(address: &str) -> String {
+    let chars: Vec<char> = address.chars().collect();
+    if chars.len() > 8 {
+        let head: String = chars[..4].iter().collect();
+        let tail: String = chars[chars.len() - 4..].iter().collect();
+        format!("{}...{}", head, tail)
+    } else {
+        address.to_string()
+    }
+}
+
+/// Load target profiles from `COPY_TARGETS_FILE` (JSON array, default `targets.json`)
+///
+/// Addresses passed from `COPY_TRADING_TARGET_ADDRESS` that have no profile get one with defaults.
+/// Profiles without `mirror_sells` take `MIRROR_SELL_MODE`.
+pub fn load_target_profiles(env_addresses: &[String]) -> Result<Vec<TargetProfile>> {
+    let logger = Logger::new("[TARGET-PROFILES] => ".cyan().to_string());
+    let path = std::env::var("COPY_TARGETS_FILE").unwrap_or_else(|_| "targets.json".to_string());
+
+    let mut profiles: Vec<TargetProfile> = match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| anyhow!("Failed to parse target profiles in {}: {}", path, e))?,
+        Err(_) => {
+            logger.log(format!("No target profile file at {}, using defaults", path).yellow().to_string());
+            Vec::new()
+        }
+    };
+
+    for address in env_addresses {
+        if !profiles.iter().any(|p| &p.address == address) {
+            profiles.push(TargetProfile::with_defaults(address));
+        }
+    }
+
+    let default_mirror = std::env::var("MIRROR_SELL_MODE")
+        .ok()
+        .and_then(|v| v.parse::<MirrorSellMode>().ok())
+        .unwrap_or_default();
+    for profile in profiles.iter_mut() {
+        profile.mirror_sells.get_or_insert(default_mirror);
+    }
+
+    for profile in &profiles {
+        logger.log(format!(
+            "{} ({}) | enabled: {} | buy: {} | slippage: {} | venues: {:?} | mirror: {:?} | max open: {} | cooldown: {}s",
+            profile.display_name(),
+            profile.address,
+            profile.enabled,
+            profile.buy_amount.map(|a| format!("{} SOL", a)).unwrap_or_else(|| "global".to_string()),
+            profile.slippage.map(|s| s.to_string()).unwrap_or_else(|| "global".to_string()),
+            profile.allowed_venues,
+            profile.mirror_sell_mode(),
+            profile.max_open_positions,
+            profile.cooldown_secs
+        ));
+    }
+
+    Ok(profiles)
+}
+
+/// Make profiles available to the trade path and notifications
+pub fn register_target_profiles(profiles: &[TargetProfile]) {
+    for profile in profiles {
+        TARGET_PROFILES.insert(profile.address.clone(), profile.clone());
+    }
+}
+
+pub fn get_target_profile(address: &str) -> Option<TargetProfile> {
+    TARGET_PROFILES.get(address).map(|entry| entry.value().clone())
+}
+
+/// All registered profiles
+pub fn get_target_profiles() -> Vec<TargetProfile> {
+    TARGET_PROFILES.iter().map(|entry| entry.value().clone()).collect()
+}
+
+/// Label of a target for display, the shortened address when it has no profile
+pub fn target_label(address: &str) -> String {
+    get_target_profile(address)
+        .map(|profile| profile.display_name())
+        .unwrap_or_else(|| short_address(address))
+}
+
+/// Enable or pause copying of a target
+pub fn set_target_enabled(address: &str, enabled: bool) -> bool {
+    match TARGET_PROFILES.get_mut(address) {
+        Some(mut profile) => {
+            profile.enabled = enabled;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Seconds left before the target may be copied again, `None` when it is off cooldown
+pub fn cooldown_remaining(profile: &TargetProfile) -> Option<u64> {
+    if profile.cooldown_secs == 0 {
+        return None;
+    }
+    let last = TARGET_LAST_COPY.get(&profile.address).map(|entry| *entry.value())?;
+    let cooldown = Duration::from_secs(profile.cooldown_secs);
+    let elapsed = last.elapsed();
+    if elapsed >= cooldown {
+        None
+    } else {
+        Some((cooldown - elapsed).as_secs().max(1))
+    }
+}
+
+/// Why a target's buy may not be copied right now
+#[derive(Debug, Clone, PartialEq)]
+pub enum CopyBlock {
+    /// Seconds left on the target's cooldown
+    Cooldown(u64),
+    /// Open plus in-flight positions already at the profile's limit
+    MaxOpenPositions(usize),
+}
+
+/// A copy slot claimed for a target at decision time
+///
+/// Counts as an open position for `max_open_positions` until dropped. The cooldown starts when the
+/// slot is claimed and is rolled back on drop unless `commit` was called after the buy landed.
+pub struct CopyReservation {
+    address: String,
+    previous_copy: Option<Instant>,
+    committed: bool,
+}
+
+impl CopyReservation {
+    /// Keep the cooldown, the buy went through
+    pub fn commit(&mut self) {
+        self.committed = true;
+    }
+}
+
+impl Drop for CopyReservation {
+    fn drop(&mut self) {
+        if let Some(mut pending) = TARGET_PENDING_BUYS.get_mut(&self.address) {
+            *pending = pending.saturating_sub(1);
+        }
+        if !self.committed {
+            match self.previous_copy {
+                Some(previous) => {
+                    TARGET_LAST_COPY.insert(self.address.clone(), previous);
+                }
+                None => {
+                    TARGET_LAST_COPY.remove(&self.address);
+                }
+            }
+        }
+    }
+}
+
+/// Check the cooldown and open position limit of a target and claim a copy slot in one step
+///
+/// `open_positions` are the target's positions already held; buys in flight are added on top, so a
+/// burst of buys from one target cannot get past either limit before the first one lands.
+pub fn reserve_copy(profile: &TargetProfile, open_positions: usize) -> Result<CopyReservation, CopyBlock> {
+    // The pending entry serializes reservations of the same target
+    let mut pending = TARGET_PENDING_BUYS.entry(profile.address.clone()).or_insert(0);
+    if let Some(remaining) = cooldown_remaining(profile) {
+        return Err(CopyBlock::Cooldown(remaining));
+    }
+    if profile.max_open_positions > 0 && open_positions + *pending >= profile.max_open_positions {
+        return Err(CopyBlock::MaxOpenPositions(profile.max_open_positions));
+    }
+    *pending += 1;
+    let previous_copy = TARGET_LAST_COPY.insert(profile.address.clone(), Instant::now());
+    Ok(CopyReservation {
+        address: profile.address.clone(),
+        previous_copy,
+        committed: false,
+    })
+}
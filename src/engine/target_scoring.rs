@@ -0,0 +1,374 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::paper;
+use crate::engine::target_profiles;
+use crate::engine::transaction_parser::TradeInfoFromToken;
+use crate::library::logger::Logger;
+use crate::utilities::telegram;
+
+// How often changed stats are written to `TARGET_STATS_FILE`
+const STATS_FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+// A target's own round trip still open after this long is no longer scored
+const OWN_OPEN_MAX_AGE_SECS: i64 = 7 * 24 * 3600;
+
+// Performance stats per target address, and whether they changed since the last write
+lazy_static! {
+    static ref TARGET_STATS: Arc<DashMap<String, TargetStats>> = Arc::new(DashMap::new());
+    static ref STATS_DIRTY: AtomicBool = AtomicBool::new(false);
+    pub static ref SCORING_CONFIG: TargetScoringConfig = TargetScoringConfig::from_env();
+}
+
+/// Closed copied position, PnL estimated from the last observed price at each sell
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CopiedTrade {
+    pub mint: String,
+    pub pnl_sol: f64,
+    pub pnl_percentage: f64,
+    pub closed_at: i64,
+}
+
+/// Target's own open position as seen on chain
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ObservedPosition {
+    pub sol_spent: f64,
+    pub sol_received: f64,
+    pub tokens: u64,
+    #[serde(default)]
+    pub opened_at: i64, // unix seconds of the first buy
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TargetStats {
+    pub address: String,
+    /// Most recent closed copies, capped at the scoring window
+    pub recent_copies: VecDeque<CopiedTrade>,
+    pub copied_trades: u64,
+    pub copied_wins: u64,
+    pub copied_pnl_sol: f64,
+    /// The target's own round trips, from their buys and sells
+    pub own_open: HashMap<String, ObservedPosition>,
+    pub own_trades: u64,
+    pub own_wins: u64,
+    pub own_pnl_sol: f64,
+    pub auto_paused: bool,
+    #[serde(default)]
+    pub paused_reason: String,
+}
+
+impl TargetStats {
+    fn new(address: &str) -> Self {
+        Self {
+            address: address.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn copied_win_rate(&self) -> f64 {
+        if self.copied_trades == 0 {
+            return 0.0;
+        }
+        self.copied_wins as f64 / self.copied_trades as f64 * 100.0
+    }
+
+    pub fn own_win_rate(&self) -> f64 {
+        if self.own_trades == 0 {
+            return 0.0;
+        }
+        self.own_wins as f64 / self.own_trades as f64 * 100.0
+    }
+
+    /// Sum and average PnL of the copies in the rolling window
+    pub fn rolling_pnl(&self) -> (f64, f64) {
+        if self.recent_copies.is_empty() {
+            return (0.0, 0.0);
+        }
+        let total: f64 = self.recent_copies.iter().map(|t| t.pnl_sol).sum();
+        let avg_pct = self.recent_copies.iter().map(|t| t.pnl_percentage).sum::<f64>()
+            / self.recent_copies.len() as f64;
+        (total, avg_pct)
+    }
+
+    pub fn rolling_win_rate(&self) -> f64 {
+        if self.recent_copies.is_empty() {
+            return 0.0;
+        }
+        let wins = self.recent_copies.iter().filter(|t| t.pnl_sol > 0.0).count();
+        wins as f64 / self.recent_copies.len() as f64 * 100.0
+    }
+}
+
+/// When a target gets paused for poor copied performance
+#[derive(Debug, Clone)]
+pub struct TargetScoringConfig {
+    pub window: usize,                  // number of copied trades in the rolling window
+    pub pause_threshold_sol: Option<f64>, // pause when the window's PnL falls below this, None = never
+    pub stats_file: String,
+    pub report_interval_secs: u64,      // 0 = no periodic Telegram report
+}
+
+impl TargetScoringConfig {
+    /// Load from `TARGET_SCORE_WINDOW`, `TARGET_PAUSE_PNL_SOL`, `TARGET_STATS_FILE` and `TARGET_REPORT_INTERVAL`
+    pub fn from_env() -> Self {
+        Self {
+            window: std::env::var("TARGET_SCORE_WINDOW")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .filter(|w| *w > 0)
+                .unwrap_or(10),
+            pause_threshold_sol: std::env::var("TARGET_PAUSE_PNL_SOL")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok()),
+            stats_file: paper::paper_path(&std::env::var("TARGET_STATS_FILE").unwrap_or_else(|_| "target_stats.json".to_string())),
+            report_interval_secs: std::env::var("TARGET_REPORT_INTERVAL")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(3600),
+        }
+    }
+}
+
+/// Read persisted stats from disk, a missing file means no history yet
+pub fn read_stats_file(path: &str) -> Result<Vec<TargetStats>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| anyhow!("Failed to parse target stats in {}: {}", path, e)),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
+fn write_stats_file(path: &str, stats: &[TargetStats]) -> Result<()> {
+    let content = serde_json::to_string_pretty(stats)?;
+    std::fs::write(path, content).map_err(|e| anyhow!("Failed to write target stats to {}: {}", path, e))
+}
+
+/// Load persisted stats and re-apply pauses to the registered profiles
+pub fn init_target_scoring() -> Result<()> {
+    let config = &*SCORING_CONFIG;
+    let logger = Logger::new("[TARGET-SCORING] => ".cyan().to_string());
+
+    for stats in read_stats_file(&config.stats_file)? {
+        if stats.auto_paused && target_profiles::set_target_enabled(&stats.address, false) {
+            logger.log(format!(
+                "{} stays paused: {}",
+                target_profiles::target_label(&stats.address),
+                stats.paused_reason
+            ).yellow().to_string());
+        }
+        TARGET_STATS.insert(stats.address.clone(), stats);
+    }
+
+    logger.log(format!(
+        "Loaded stats for {} targets | window: {} trades | pause below: {}",
+        TARGET_STATS.len(),
+        config.window,
+        config.pause_threshold_sol.map(|t| format!("{} SOL", t)).unwrap_or_else(|| "disabled".to_string())
+    ));
+    Ok(())
+}
+
+/// Mark the stats changed, the flusher writes them on its next tick
+fn save() {
+    STATS_DIRTY.store(true, Ordering::Relaxed);
+}
+
+/// Write the stats to disk if they changed since the last write
+pub fn flush_stats() {
+    if !STATS_DIRTY.swap(false, Ordering::Relaxed) {
+        return;
+    }
+    let stats: Vec<TargetStats> = TARGET_STATS.iter().map(|entry| entry.value().clone()).collect();
+    if let Err(e) = write_stats_file(&SCORING_CONFIG.stats_file, &stats) {
+        STATS_DIRTY.store(true, Ordering::Relaxed);
+        Logger::new("[TARGET-SCORING] => ".cyan().to_string()).log(e.to_string().red().to_string());
+    }
+}
+
+/// Write changed stats every `STATS_FLUSH_INTERVAL` off the trade path
+pub fn start_stats_flusher() {
+    tokio::spawn(async {
+        let mut flush_interval = tokio::time::interval(STATS_FLUSH_INTERVAL);
+        loop {
+            flush_interval.tick().await;
+            if let Err(e) = tokio::task::spawn_blocking(flush_stats).await {
+                Logger::new("[TARGET-SCORING] => ".cyan().to_string())
+                    .log(format!("Stats flush panicked: {}", e).red().to_string());
+            }
+        }
+    });
+}
+
+/// Track a target's own buy or sell to score their round trips
+pub fn record_target_trade(trade_info: &TradeInfoFromToken) {
+    let sol = trade_info.sol_amount.unwrap_or(0) as f64 / 1_000_000_000.0;
+    let tokens = trade_info.token_amount.unwrap_or(0);
+    if tokens == 0 {
+        return;
+    }
+
+    {
+        let mut stats = TARGET_STATS
+            .entry(trade_info.target.clone())
+            .or_insert_with(|| TargetStats::new(&trade_info.target));
+
+        let now = chrono::Utc::now().timestamp();
+        // Round trips the target never closed, or whose sells we missed, are dropped after a while
+        stats.own_open.retain(|_, position| {
+            // Saved before the age was kept, start its clock now
+            if position.opened_at == 0 {
+                position.opened_at = now;
+            }
+            now - position.opened_at < OWN_OPEN_MAX_AGE_SECS
+        });
+
+        if trade_info.is_buy {
+            let position = stats.own_open.entry(trade_info.mint.clone()).or_insert_with(|| ObservedPosition {
+                opened_at: now,
+                ..Default::default()
+            });
+            position.sol_spent += sol;
+            position.tokens += tokens;
+        } else {
+            // Sells of tokens we never saw bought are not scored
+            let Some(position) = stats.own_open.get_mut(&trade_info.mint) else { return };
+            position.tokens = position.tokens.saturating_sub(tokens);
+            position.sol_received += sol;
+
+            if position.tokens == 0 || trade_info.user_post_token_balance == Some(0) {
+                let pnl = position.sol_received - position.sol_spent;
+                stats.own_open.remove(&trade_info.mint);
+                stats.own_trades += 1;
+                stats.own_pnl_sol += pnl;
+                if pnl > 0.0 {
+                    stats.own_wins += 1;
+                }
+            }
+        }
+    }
+
+    save();
+}
+
+/// Record a closed copied position and pause its target when the rolling PnL is below the threshold
+pub async fn record_copied_close(
+    target: &str,
+    mint: &str,
+    pnl_sol: f64,
+    pnl_percentage: f64,
+) {
+    let logger = Logger::new("[TARGET-SCORING] => ".cyan().to_string());
+    let config = &*SCORING_CONFIG;
+
+    let pause_reason = {
+        let mut stats = TARGET_STATS
+            .entry(target.to_string())
+            .or_insert_with(|| TargetStats::new(target));
+
+        stats.copied_trades += 1;
+        stats.copied_pnl_sol += pnl_sol;
+        if pnl_sol > 0.0 {
+            stats.copied_wins += 1;
+        }
+        stats.recent_copies.push_back(CopiedTrade {
+            mint: mint.to_string(),
+            pnl_sol,
+            pnl_percentage,
+            closed_at: chrono::Utc::now().timestamp(),
+        });
+        while stats.recent_copies.len() > config.window {
+            stats.recent_copies.pop_front();
+        }
+
+        let (rolling_sol, _) = stats.rolling_pnl();
+        match config.pause_threshold_sol {
+            Some(threshold)
+                if !stats.auto_paused
+                    && stats.recent_copies.len() >= config.window
+                    && rolling_sol < threshold =>
+            {
+                let reason = format!(
+                    "copied PnL {:.4} SOL over the last {} trades is below {} SOL",
+                    rolling_sol, config.window, threshold
+                );
+                stats.auto_paused = true;
+                stats.paused_reason = reason.clone();
+                Some(reason)
+            }
+            _ => None,
+        }
+    };
+
+    logger.log(format!(
+        "{} copy of {} closed at {:.4} SOL ({:.2}%)",
+        target_profiles::target_label(target), mint, pnl_sol, pnl_percentage
+    ));
+
+    if let Some(reason) = pause_reason {
+        target_profiles::set_target_enabled(target, false);
+        logger.log(format!("Paused {}: {}", target_profiles::target_label(target), reason).red().to_string());
+        if let Err(e) = telegram::send_target_paused_notification(&target_profiles::target_label(target), target, &reason).await {
+            logger.log(format!("Failed to send pause notification: {}", e).yellow().to_string());
+        }
+    }
+
+    save();
+}
+
+/// Clear an automatic pause, returns false when the target has no stats
+pub fn resume_target(target: &str) -> bool {
+    let found = match TARGET_STATS.get_mut(target) {
+        Some(mut stats) => {
+            stats.auto_paused = false;
+            stats.paused_reason.clear();
+            true
+        }
+        None => false,
+    };
+    if found {
+        target_profiles::set_target_enabled(target, true);
+        save();
+        flush_stats();
+    }
+    found
+}
+
+/// Snapshot of all target stats
+pub fn get_target_stats() -> Vec<TargetStats> {
+    TARGET_STATS.iter().map(|entry| entry.value().clone()).collect()
+}
+
+/// One line per target, best rolling PnL first
+pub fn format_target_report(stats: &[TargetStats]) -> Vec<String> {
+    let mut stats = stats.to_vec();
+    stats.sort_by(|a, b| b.rolling_pnl().0.total_cmp(&a.rolling_pnl().0));
+
+    stats
+        .iter()
+        .map(|s| {
+            let (rolling_sol, rolling_pct) = s.rolling_pnl();
+            format!(
+                "{} ({}){} | copies: {} ({:.0}% win, {:+.4} SOL) | last {}: {:+.4} SOL, avg {:+.2}%, {:.0}% win | own: {} trades, {:.0}% win, {:+.4} SOL",
+                target_profiles::target_label(&s.address),
+                s.address,
+                if s.auto_paused { " [PAUSED]" } else { "" },
+                s.copied_trades,
+                s.copied_win_rate(),
+                s.copied_pnl_sol,
+                s.recent_copies.len(),
+                rolling_sol,
+                rolling_pct,
+                s.rolling_win_rate(),
+                s.own_trades,
+                s.own_win_rate(),
+                s.own_pnl_sol
+            )
+        })
+        .collect()
+}
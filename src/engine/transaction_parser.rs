@@ -7,16 +7,27 @@ use lazy_static;
 use yellowstone_grpc_proto::geyser::SubscribeUpdateTransaction;
 use std::time::Instant;
 use crate::dex::pump_fun::PUMP_PROGRAM;
+use crate::dex::pump_swap::PUMP_SWAP_PROGRAM;
+use crate::dex::raydium_launchpad::RAYDIUM_LAUNCHPAD_PROGRAM_ID;
+use crate::library::config::{
+    PUMP_FUN_BUY_OR_SELL_PROGRAM_DATA_PREFIX, PUMP_FUN_COMPLETE_PROGRAM_DATA_PREFIX, PUMP_FUN_CREATE_LOG_INSTRUCTIONS,
+    PUMP_FUN_PROGRAM_DATA_PREFIX, PUMP_SWAP_BUY_PROGRAM_DATA_PREFIX, PUMP_SWAP_CREATE_POOL_PROGRAM_DATA_PREFIX,
+    PUMP_SWAP_LOG_INSTRUCTION, PUMP_SWAP_SELL_PROGRAM_DATA_PREFIX, RAYDIUM_LAUNCHPAD_CREATE_LOG_INSTRUCTIONS,
+};
+
+const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const METADATA_PROGRAM: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
 // Create a static logger for this module
 lazy_static::lazy_static! {
     static ref LOGGER: Logger = Logger::new("[PARSER] => ".blue().to_string());
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Default)]
 pub enum DexType {
     PumpSwap,
     PumpFun,
     RaydiumLaunchpad,
+    #[default]
     Unknown,
 }
 
@@ -138,6 +149,66 @@ pub struct TradeInfoFromToken {
     pub min_sol_output: Option<u64>,
     pub base_amount_out: Option<u64>,
     pub max_quote_amount_in: Option<u64>,
+
+    // Trader's raw token balance of `mint` before and after the transaction
+    pub user_pre_token_balance: Option<u64>,
+    pub user_post_token_balance: Option<u64>,
+}
+
+impl Default for TradeInfoFromToken {
+    fn default() -> Self {
+        Self {
+            dex_type: DexType::Unknown,
+            slot: 0,
+            signature: String::new(),
+            target: String::new(),
+            mint: String::new(),
+            user: String::new(),
+            timestamp: 0,
+            is_buy: false,
+            price: 0,
+            is_reverse_when_pump_swap: false,
+            base_amount_in_or_base_amount_out: None,
+            min_quote_amount_out: None,
+            user_base_token_reserves: None,
+            user_quote_token_reserves: None,
+            pool_base_token_reserves: None,
+            pool_quote_token_reserves: None,
+            quote_amount_out: None,
+            lp_fee_basis_points: None,
+            lp_fee: None,
+            protocol_fee_basis_points: None,
+            protocol_fee: None,
+            quote_amount_out_without_lp_fee: None,
+            user_quote_amount_out: None,
+            pool: None,
+            user_base_token_account: None,
+            user_quote_token_account: None,
+            protocol_fee_recipient: None,
+            protocol_fee_recipient_token_account: None,
+            coin_creator: None,
+            coin_creator_fee_basis_points: None,
+            coin_creator_fee: None,
+            sol_amount: None,
+            token_amount: None,
+            virtual_sol_reserves: None,
+            virtual_token_reserves: None,
+            real_sol_reserves: None,
+            real_token_reserves: None,
+            bonding_curve: String::new(),
+            volume_change: 0,
+            bonding_curve_info: None,
+            pool_info: None,
+            token_amount_f64: 0.0,
+            amount: None,
+            max_sol_cost: None,
+            min_sol_output: None,
+            base_amount_out: None,
+            max_quote_amount_in: None,
+            user_pre_token_balance: None,
+            user_post_token_balance: None,
+        }
+    }
 }
 /// Helper function to check if transaction contains MintTo instruction
 /// NOTE: This function is no longer used - we now process all transactions regardless of MintTo
@@ -176,3 +247,641 @@ fn has_sell_instruction(txn: &SubscribeUpdateTransaction) -> bool {
     }
     false
 }
+
+impl TradeInfoFromToken {
+    /// Curve or pool reserves after the trade as (SOL lamports, raw tokens)
+    pub fn pool_reserves(&self) -> Option<(u64, u64)> {
+        match self.dex_type {
+            DexType::PumpFun => Some((self.virtual_sol_reserves?, self.virtual_token_reserves?)),
+            DexType::PumpSwap => {
+                let base = self.pool_base_token_reserves?;
+                let quote = self.pool_quote_token_reserves?;
+                if self.is_reverse_when_pump_swap {
+                    Some((base, quote))
+                } else {
+                    Some((quote, base))
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Spot price of the token in SOL, derived from the reserves reported by the trade
+    ///
+    /// Falls back to the executed SOL/token ratio when the venue reports no reserves.
+    pub fn price_in_sol(&self) -> Option<f64> {
+        let (sol_reserve, token_reserve) = match self.dex_type {
+            DexType::PumpFun | DexType::PumpSwap => self.pool_reserves()?,
+            _ => (self.sol_amount?, self.token_amount?),
+        };
+
+        if sol_reserve == 0 || token_reserve == 0 {
+            return None;
+        }
+
+        // SOL has 9 decimals, PumpFun / PumpSwap / Launchpad tokens have 6
+        let sol = sol_reserve as f64 / 1_000_000_000.0;
+        let tokens = token_reserve as f64 / 1_000_000.0;
+        Some(sol / tokens)
+    }
+}
+
+/// Parse a streamed transaction into a trade on one of the supported venues
+///
+/// Venue events emitted in the logs are decoded when present; everything they do not carry
+/// (mint, trader balances, amounts for venues without a decoder) is taken from the balance changes in the meta.
+pub fn parse_transaction_data(txn: &SubscribeUpdateTransaction) -> Option<TradeInfoFromToken> {
+    let start_time = Instant::now();
+    let tx_inner = txn.transaction.as_ref()?;
+    let meta = tx_inner.meta.as_ref()?;
+    if meta.err.is_some() {
+        return None;
+    }
+
+    let account_keys = collect_account_keys(txn);
+    let dex_type = detect_dex_type(&account_keys);
+    if dex_type == DexType::Unknown {
+        return None;
+    }
+    if !has_buy_instruction(txn) && !has_sell_instruction(txn) {
+        return None;
+    }
+
+    let mut trade = TradeInfoFromToken {
+        dex_type: dex_type.clone(),
+        slot: txn.slot,
+        signature: bs58::encode(&tx_inner.signature).into_string(),
+        ..Default::default()
+    };
+
+    match dex_type {
+        DexType::PumpFun => {
+            if let Some(data) = find_program_data(&meta.log_messages, PUMP_FUN_BUY_OR_SELL_PROGRAM_DATA_PREFIX)
+                .and_then(|bytes| decode_pump_fun_trade(&bytes))
+            {
+                apply_pump_fun_data(&mut trade, &data);
+            }
+        }
+        DexType::PumpSwap => {
+            if let Some(bytes) = find_program_data(&meta.log_messages, PUMP_SWAP_BUY_PROGRAM_DATA_PREFIX) {
+                if let Some(data) = decode_pump_swap_event(&bytes) {
+                    apply_pump_swap_data(&mut trade, &data, true);
+                }
+            } else if let Some(bytes) = find_program_data(&meta.log_messages, PUMP_SWAP_SELL_PROGRAM_DATA_PREFIX) {
+                if let Some(data) = decode_pump_swap_event(&bytes) {
+                    apply_pump_swap_data(&mut trade, &data, false);
+                }
+            }
+        }
+        _ => {}
+    }
+
+    apply_balance_changes(&mut trade, meta, &account_keys);
+    if trade.mint.is_empty() || trade.user.is_empty() {
+        return None;
+    }
+
+    if let Some(price) = trade.price_in_sol() {
+        trade.price = (price * 1_000_000_000.0) as u64;
+    }
+
+    LOGGER.debug(format!(
+        "Parsed {:?} {} of {} by {} in {:?}",
+        trade.dex_type,
+        if trade.is_buy { "buy" } else { "sell" },
+        trade.mint,
+        trade.user,
+        start_time.elapsed()
+    ));
+
+    crate::engine::paper::observe_trade(&trade);
+    Some(trade)
+}
+
+/// A new token launch, with the creator's initial buy when it happened in the same transaction
+#[derive(Clone, Debug, Default)]
+pub struct LaunchInfo {
+    pub mint: String,
+    pub creator: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub bonding_curve: String,
+    pub metadata_account: Option<String>,
+    pub dex_type: DexType,
+    pub signature: String,
+    pub slot: u64,
+    pub dev_buy: Option<TradeInfoFromToken>,
+}
+
+impl LaunchInfo {
+    /// SOL the creator spent in the create transaction, 0 when unknown
+    pub fn dev_buy_sol(&self) -> f64 {
+        self.dev_buy
+            .as_ref()
+            .and_then(|trade| trade.sol_amount)
+            .unwrap_or(0) as f64 / 1_000_000_000.0
+    }
+}
+
+/// Parse a PumpFun or Raydium Launchpad create transaction
+pub fn parse_launch_transaction(txn: &SubscribeUpdateTransaction) -> Option<LaunchInfo> {
+    let tx_inner = txn.transaction.as_ref()?;
+    let meta = tx_inner.meta.as_ref()?;
+    if meta.err.is_some() {
+        return None;
+    }
+
+    let account_keys = collect_account_keys(txn);
+    let dex_type = detect_dex_type(&account_keys);
+    let create_instructions = match dex_type {
+        DexType::PumpFun => PUMP_FUN_CREATE_LOG_INSTRUCTIONS,
+        DexType::RaydiumLaunchpad => RAYDIUM_LAUNCHPAD_CREATE_LOG_INSTRUCTIONS,
+        _ => return None,
+    };
+    let is_create = meta.log_messages.iter().any(|log| {
+        log.strip_prefix("Program log: Instruction: ")
+            .is_some_and(|instruction| create_instructions.contains(&instruction))
+    });
+    if !is_create {
+        return None;
+    }
+
+    let mut launch = LaunchInfo {
+        dex_type: dex_type.clone(),
+        signature: bs58::encode(&tx_inner.signature).into_string(),
+        slot: txn.slot,
+        ..Default::default()
+    };
+
+    if dex_type == DexType::PumpFun {
+        if let Some(bytes) = find_program_data(&meta.log_messages, PUMP_FUN_PROGRAM_DATA_PREFIX) {
+            apply_pump_fun_create(&mut launch, &bytes);
+        }
+    }
+
+    // The creator's buy in the same transaction, if any
+    let dev_buy = parse_transaction_data(txn).filter(|trade| trade.is_buy);
+    if let Some(trade) = &dev_buy {
+        if launch.mint.is_empty() {
+            launch.mint = trade.mint.clone();
+        }
+        if launch.creator.is_empty() {
+            launch.creator = trade.user.clone();
+        }
+    }
+    // Launchpad amounts come from balance changes, which include the rent of every account the create opens
+    launch.dev_buy = dev_buy.filter(|trade| trade.mint == launch.mint && dex_type == DexType::PumpFun);
+
+    if launch.creator.is_empty() {
+        launch.creator = account_keys.first()?.clone();
+    }
+    if launch.mint.is_empty() {
+        launch.mint = meta.post_token_balances.iter()
+            .find(|b| b.mint != WSOL_MINT)
+            .map(|b| b.mint.clone())?;
+    }
+
+    launch.metadata_account = find_metadata_account(&account_keys, &launch.mint);
+
+    Some(launch)
+}
+
+/// Stage of a PumpFun token moving from its bonding curve to a PumpSwap pool
+#[derive(Clone, Debug, PartialEq)]
+pub enum MigrationStage {
+    /// The curve sold out (`CompleteEvent`), PumpFun no longer trades the token
+    CurveComplete,
+    /// The PumpSwap pool was created by the `Migrate` instruction
+    PoolCreated,
+}
+
+/// A bonding curve completing or its liquidity landing in a PumpSwap pool
+#[derive(Clone, Debug)]
+pub struct MigrationInfo {
+    pub mint: String,
+    pub stage: MigrationStage,
+    pub bonding_curve: Option<String>,
+    pub pool: Option<String>,
+    pub pool_base_amount: Option<u64>,  // tokens deposited in the pool
+    pub pool_quote_amount: Option<u64>, // lamports deposited in the pool
+    pub signature: String,
+    pub slot: u64,
+}
+
+impl MigrationInfo {
+    /// Opening price of the pool in SOL per token
+    pub fn pool_price_in_sol(&self) -> Option<f64> {
+        let base = self.pool_base_amount.filter(|amount| *amount > 0)? as f64 / 1_000_000.0;
+        let quote = self.pool_quote_amount? as f64 / 1_000_000_000.0;
+        Some(quote / base)
+    }
+}
+
+/// Parse a PumpFun curve completion or PumpFun -> PumpSwap migration transaction
+pub fn parse_migration_transaction(txn: &SubscribeUpdateTransaction) -> Option<MigrationInfo> {
+    let tx_inner = txn.transaction.as_ref()?;
+    let meta = tx_inner.meta.as_ref()?;
+    if meta.err.is_some() {
+        return None;
+    }
+    let signature = bs58::encode(&tx_inner.signature).into_string();
+
+    let is_migrate = meta.log_messages.iter().any(|log| {
+        log.strip_prefix("Program log: Instruction: ") == Some(PUMP_SWAP_LOG_INSTRUCTION)
+    });
+    if is_migrate {
+        let bytes = find_program_data(&meta.log_messages, PUMP_SWAP_CREATE_POOL_PROGRAM_DATA_PREFIX)?;
+        let mut migration = decode_pump_swap_create_pool(&bytes)?;
+        migration.signature = signature;
+        migration.slot = txn.slot;
+        return Some(migration);
+    }
+
+    // Emitted by the buy that sells out the curve: user, mint, bonding curve, timestamp
+    let bytes = find_program_data(&meta.log_messages, PUMP_FUN_COMPLETE_PROGRAM_DATA_PREFIX)?;
+    let mut offset = 8 + 32;
+    let mint = read_pubkey(&bytes, &mut offset)?.to_string();
+    let bonding_curve = read_pubkey(&bytes, &mut offset)?.to_string();
+    Some(MigrationInfo {
+        mint,
+        stage: MigrationStage::CurveComplete,
+        bonding_curve: Some(bonding_curve),
+        pool: None,
+        pool_base_amount: None,
+        pool_quote_amount: None,
+        signature,
+        slot: txn.slot,
+    })
+}
+
+/// Decode a PumpSwap `CreatePoolEvent`
+fn decode_pump_swap_create_pool(data: &[u8]) -> Option<MigrationInfo> {
+    // discriminator, timestamp (8), index (2), pool creator (32)
+    let mut offset = 8 + 8 + 2 + 32;
+    let base_mint = read_pubkey(data, &mut offset)?.to_string();
+    let quote_mint = read_pubkey(data, &mut offset)?.to_string();
+    offset += 1 + 1; // base / quote decimals
+    offset += 8 + 8; // base / quote amount in
+    let pool_base_amount = read_u64(data, &mut offset)?;
+    let pool_quote_amount = read_u64(data, &mut offset)?;
+    offset += 8 + 8 + 8 + 1; // minimum liquidity, initial liquidity, lp tokens out, pool bump
+    let pool = read_pubkey(data, &mut offset)?.to_string();
+
+    // Migrated pools pair the token with WSOL, normally as the base side
+    let (mint, pool_base_amount, pool_quote_amount) = if base_mint == WSOL_MINT {
+        (quote_mint, pool_quote_amount, pool_base_amount)
+    } else {
+        (base_mint, pool_base_amount, pool_quote_amount)
+    };
+    Some(MigrationInfo {
+        mint,
+        stage: MigrationStage::PoolCreated,
+        bonding_curve: None,
+        pool: Some(pool),
+        pool_base_amount: Some(pool_base_amount),
+        pool_quote_amount: Some(pool_quote_amount),
+        signature: String::new(),
+        slot: 0,
+    })
+}
+
+/// Metaplex metadata PDA `["metadata", program, mint]` of `mint`, when the transaction passes it
+fn find_metadata_account(account_keys: &[String], mint: &str) -> Option<String> {
+    let mint = Pubkey::from_str(mint).ok()?;
+    let program = Pubkey::from_str(METADATA_PROGRAM).ok()?;
+    let (pda, _) = Pubkey::find_program_address(&[b"metadata", program.as_ref(), mint.as_ref()], &program);
+    let pda = pda.to_string();
+    account_keys.contains(&pda).then_some(pda)
+}
+
+pub(crate) fn read_string(data: &[u8], offset: &mut usize) -> Option<String> {
+    let len = u32::from_le_bytes(data.get(*offset..*offset + 4)?.try_into().ok()?) as usize;
+    *offset += 4;
+    let bytes = data.get(*offset..*offset + len)?;
+    *offset += len;
+    String::from_utf8(bytes.to_vec()).ok()
+}
+
+/// Decode a PumpFun `CreateEvent`: name, symbol, uri, mint, bonding curve, user and, on newer versions, creator
+fn apply_pump_fun_create(launch: &mut LaunchInfo, data: &[u8]) {
+    let mut offset = 8;
+    let mut decode = || -> Option<()> {
+        launch.name = read_string(data, &mut offset)?;
+        launch.symbol = read_string(data, &mut offset)?;
+        launch.uri = read_string(data, &mut offset)?;
+        launch.mint = read_pubkey(data, &mut offset)?.to_string();
+        launch.bonding_curve = read_pubkey(data, &mut offset)?.to_string();
+        launch.creator = read_pubkey(data, &mut offset)?.to_string();
+        if let Some(creator) = read_pubkey(data, &mut offset) {
+            launch.creator = creator.to_string();
+        }
+        Some(())
+    };
+    if decode().is_none() {
+        LOGGER.debug("Truncated PumpFun CreateEvent".to_string());
+    }
+}
+
+/// Static account keys followed by the keys loaded from address lookup tables
+fn collect_account_keys(txn: &SubscribeUpdateTransaction) -> Vec<String> {
+    let mut keys = Vec::new();
+    if let Some(tx_inner) = &txn.transaction {
+        if let Some(message) = tx_inner.transaction.as_ref().and_then(|t| t.message.as_ref()) {
+            keys.extend(message.account_keys.iter().map(|k| bs58::encode(k).into_string()));
+        }
+        if let Some(meta) = &tx_inner.meta {
+            keys.extend(meta.loaded_writable_addresses.iter().map(|k| bs58::encode(k).into_string()));
+            keys.extend(meta.loaded_readonly_addresses.iter().map(|k| bs58::encode(k).into_string()));
+        }
+    }
+    keys
+}
+
+fn detect_dex_type(account_keys: &[String]) -> DexType {
+    if account_keys.iter().any(|k| k == PUMP_PROGRAM) {
+        DexType::PumpFun
+    } else if account_keys.iter().any(|k| k == PUMP_SWAP_PROGRAM) {
+        DexType::PumpSwap
+    } else if account_keys.iter().any(|k| k == RAYDIUM_LAUNCHPAD_PROGRAM_ID) {
+        DexType::RaydiumLaunchpad
+    } else {
+        DexType::Unknown
+    }
+}
+
+/// Lamports the PumpFun or PumpSwap trade event in `logs` charged as protocol, LP and creator fees
+pub(crate) fn venue_fee_lamports(logs: &[String]) -> u64 {
+    let at = |data: &[u8], mut offset: usize| read_u64(data, &mut offset).unwrap_or(0);
+    if let Some(data) = find_program_data(logs, PUMP_FUN_BUY_OR_SELL_PROGRAM_DATA_PREFIX) {
+        // fee at 169 and creator_fee at 217, both absent on older program versions
+        return at(&data, 169) + at(&data, 217);
+    }
+    let data = find_program_data(logs, PUMP_SWAP_BUY_PROGRAM_DATA_PREFIX)
+        .or_else(|| find_program_data(logs, PUMP_SWAP_SELL_PROGRAM_DATA_PREFIX));
+    match data {
+        // lp_fee, protocol_fee and coin_creator_fee
+        Some(data) => at(&data, 80) + at(&data, 96) + at(&data, 352),
+        None => 0,
+    }
+}
+
+/// Base64-decoded payload of the first `Program data:` log line starting with `prefix`
+pub(crate) fn find_program_data(logs: &[String], prefix: &str) -> Option<Vec<u8>> {
+    logs.iter()
+        .find(|log| log.starts_with(prefix))
+        .and_then(|log| log.strip_prefix("Program data: "))
+        .and_then(|encoded| base64::decode(encoded.trim()).ok())
+}
+
+pub(crate) fn read_u64(data: &[u8], offset: &mut usize) -> Option<u64> {
+    let bytes = data.get(*offset..*offset + 8)?;
+    *offset += 8;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+pub(crate) fn read_pubkey(data: &[u8], offset: &mut usize) -> Option<Pubkey> {
+    let bytes = data.get(*offset..*offset + 32)?;
+    *offset += 32;
+    Pubkey::try_from(bytes).ok()
+}
+
+/// Decode a PumpFun `TradeEvent` (8-byte discriminator followed by the event fields)
+fn decode_pump_fun_trade(data: &[u8]) -> Option<(PumpFunData, Option<String>)> {
+    let mut offset = 8;
+    let mint = read_pubkey(data, &mut offset)?;
+    let sol_amount = read_u64(data, &mut offset)?;
+    let token_amount = read_u64(data, &mut offset)?;
+    let is_buy = *data.get(offset)? != 0;
+    offset += 1;
+    let user = read_pubkey(data, &mut offset)?;
+    let timestamp = read_u64(data, &mut offset)?;
+    let virtual_sol_reserves = read_u64(data, &mut offset)?;
+    let virtual_token_reserves = read_u64(data, &mut offset)?;
+    let real_sol_reserves = read_u64(data, &mut offset)?;
+    let real_token_reserves = read_u64(data, &mut offset)?;
+
+    // Newer program versions append fee_recipient, fee_basis_points, fee and creator
+    offset += 32 + 8 + 8;
+    let creator = read_pubkey(data, &mut offset).map(|c| c.to_string());
+
+    Some((
+        PumpFunData {
+            mint: mint.to_string(),
+            sol_amount,
+            token_amount,
+            is_buy,
+            user: user.to_string(),
+            timestamp,
+            virtual_sol_reserves,
+            virtual_token_reserves,
+            real_sol_reserves,
+            real_token_reserves,
+        },
+        creator,
+    ))
+}
+
+fn apply_pump_fun_data(trade: &mut TradeInfoFromToken, (data, creator): &(PumpFunData, Option<String>)) {
+    trade.mint = data.mint.clone();
+    trade.user = data.user.clone();
+    trade.is_buy = data.is_buy;
+    trade.timestamp = data.timestamp;
+    trade.sol_amount = Some(data.sol_amount);
+    trade.token_amount = Some(data.token_amount);
+    trade.virtual_sol_reserves = Some(data.virtual_sol_reserves);
+    trade.virtual_token_reserves = Some(data.virtual_token_reserves);
+    trade.real_sol_reserves = Some(data.real_sol_reserves);
+    trade.real_token_reserves = Some(data.real_token_reserves);
+    trade.coin_creator = creator.clone();
+    if let Ok(mint) = Pubkey::from_str(&data.mint) {
+        if let Ok(program) = Pubkey::from_str(PUMP_PROGRAM) {
+            let (bonding_curve, _) = Pubkey::find_program_address(&[b"bonding-curve", mint.as_ref()], &program);
+            trade.bonding_curve = bonding_curve.to_string();
+            trade.bonding_curve_info = Some(crate::engine::monitor::BondingCurveInfo {
+                bonding_curve,
+                new_virtual_sol_reserve: data.virtual_sol_reserves,
+                new_virtual_token_reserve: data.virtual_token_reserves,
+            });
+        }
+    }
+}
+
+/// Decode a PumpSwap `BuyEvent` / `SellEvent`; both share the same field layout
+fn decode_pump_swap_event(data: &[u8]) -> Option<(PumpSwapData, Option<String>)> {
+    let mut offset = 8;
+    let mut next = || read_u64(data, &mut offset);
+    let timestamp = next()?;
+    let base_amount_in = next()?;
+    let min_quote_amount_out = next()?;
+    let user_base_token_reserves = next()?;
+    let user_quote_token_reserves = next()?;
+    let pool_base_token_reserves = next()?;
+    let pool_quote_token_reserves = next()?;
+    let quote_amount_out = next()?;
+    let lp_fee_basis_points = next()?;
+    let lp_fee = next()?;
+    let protocol_fee_basis_points = next()?;
+    let protocol_fee = next()?;
+    let quote_amount_out_without_lp_fee = next()?;
+    let user_quote_amount_out = next()?;
+    let mut key = || read_pubkey(data, &mut offset).map(|k| k.to_string());
+    let pool = key()?;
+    let user = key()?;
+    let target_user_base_token_account = key()?;
+    let target_user_quote_token_account = key()?;
+    let protocol_fee_recipient = key()?;
+    let protocol_fee_recipient_token_account = key()?;
+    let coin_creator = key();
+
+    Some((
+        PumpSwapData {
+            timestamp,
+            base_amount_in,
+            min_quote_amount_out,
+            user_base_token_reserves,
+            user_quote_token_reserves,
+            pool_base_token_reserves,
+            pool_quote_token_reserves,
+            quote_amount_out,
+            lp_fee_basis_points,
+            lp_fee,
+            protocol_fee_basis_points,
+            protocol_fee,
+            quote_amount_out_without_lp_fee,
+            user_quote_amount_out,
+            pool,
+            user,
+            target_user_base_token_account,
+            target_user_quote_token_account,
+            protocol_fee_recipient,
+            protocol_fee_recipient_token_account,
+        },
+        coin_creator,
+    ))
+}
+
+fn apply_pump_swap_data(trade: &mut TradeInfoFromToken, (data, creator): &(PumpSwapData, Option<String>), is_buy: bool) {
+    trade.user = data.user.clone();
+    trade.is_buy = is_buy;
+    trade.timestamp = data.timestamp;
+    trade.base_amount_in_or_base_amount_out = Some(data.base_amount_in);
+    trade.min_quote_amount_out = Some(data.min_quote_amount_out);
+    trade.user_base_token_reserves = Some(data.user_base_token_reserves);
+    trade.user_quote_token_reserves = Some(data.user_quote_token_reserves);
+    trade.pool_base_token_reserves = Some(data.pool_base_token_reserves);
+    trade.pool_quote_token_reserves = Some(data.pool_quote_token_reserves);
+    trade.quote_amount_out = Some(data.quote_amount_out);
+    trade.lp_fee_basis_points = Some(data.lp_fee_basis_points);
+    trade.lp_fee = Some(data.lp_fee);
+    trade.protocol_fee_basis_points = Some(data.protocol_fee_basis_points);
+    trade.protocol_fee = Some(data.protocol_fee);
+    trade.quote_amount_out_without_lp_fee = Some(data.quote_amount_out_without_lp_fee);
+    trade.user_quote_amount_out = Some(data.user_quote_amount_out);
+    trade.pool = Some(data.pool.clone());
+    trade.user_base_token_account = Some(data.target_user_base_token_account.clone());
+    trade.user_quote_token_account = Some(data.target_user_quote_token_account.clone());
+    trade.protocol_fee_recipient = Some(data.protocol_fee_recipient.clone());
+    trade.protocol_fee_recipient_token_account = Some(data.protocol_fee_recipient_token_account.clone());
+    trade.coin_creator = creator.clone();
+    trade.sol_amount = Some(data.quote_amount_out);
+    trade.token_amount = Some(data.base_amount_in);
+}
+
+/// Fill mint, trader and amounts from the pre/post balances of the transaction
+fn apply_balance_changes(
+    trade: &mut TradeInfoFromToken,
+    meta: &yellowstone_grpc_proto::prelude::TransactionStatusMeta,
+    account_keys: &[String],
+) {
+    if trade.user.is_empty() {
+        // Fee payer is the trader when no event told us otherwise
+        if let Some(payer) = account_keys.first() {
+            trade.user = payer.clone();
+        }
+    }
+
+    let raw_amount = |balance: &yellowstone_grpc_proto::prelude::TokenBalance| -> u64 {
+        balance.ui_token_amount
+            .as_ref()
+            .and_then(|amount| amount.amount.parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+
+    if trade.mint.is_empty() {
+        let user_mint = meta.post_token_balances.iter()
+            .chain(meta.pre_token_balances.iter())
+            .find(|b| b.owner == trade.user && b.mint != WSOL_MINT)
+            .or_else(|| meta.post_token_balances.iter().find(|b| b.mint != WSOL_MINT))
+            .map(|b| b.mint.clone());
+        match user_mint {
+            Some(mint) => trade.mint = mint,
+            None => return,
+        }
+    }
+
+    let pre = meta.pre_token_balances.iter()
+        .filter(|b| b.owner == trade.user && b.mint == trade.mint)
+        .map(raw_amount)
+        .sum::<u64>();
+    let post = meta.post_token_balances.iter()
+        .filter(|b| b.owner == trade.user && b.mint == trade.mint)
+        .map(raw_amount)
+        .sum::<u64>();
+    trade.user_pre_token_balance = Some(pre);
+    trade.user_post_token_balance = Some(post);
+
+    if trade.token_amount.is_none() {
+        trade.is_buy = post > pre;
+        trade.token_amount = Some(post.abs_diff(pre));
+    }
+
+    if trade.sol_amount.is_none() {
+        if let Some(index) = account_keys.iter().position(|k| *k == trade.user) {
+            if let (Some(pre_lamports), Some(post_lamports)) = (meta.pre_balances.get(index), meta.post_balances.get(index)) {
+                trade.sol_amount = Some(pre_lamports.abs_diff(*post_lamports));
+            }
+        }
+    }
+
+    let decimals = meta.post_token_balances.iter()
+        .find(|b| b.mint == trade.mint)
+        .and_then(|b| b.ui_token_amount.as_ref())
+        .map(|amount| amount.decimals)
+        .unwrap_or(6);
+    trade.token_amount_f64 = trade.token_amount.unwrap_or(0) as f64 / 10f64.powi(decimals as i32);
+    trade.amount = trade.token_amount;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A PumpSwap SellEvent log line at `timestamp` with the given fees
+    fn pump_swap_sell_log(timestamp: u64, lp_fee: u64, protocol_fee: u64, coin_creator_fee: u64) -> String {
+        let mut data = vec![62, 47, 55, 10, 165, 3, 220, 42];
+        let mut u64s = [0u64; 14];
+        u64s[0] = timestamp;
+        u64s[9] = lp_fee;
+        u64s[11] = protocol_fee;
+        for value in u64s {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&[1u8; 7 * 32]); // pool, user, token accounts, fee recipients, coin creator
+        data.extend_from_slice(&0u64.to_le_bytes()); // coin_creator_fee_basis_points
+        data.extend_from_slice(&coin_creator_fee.to_le_bytes());
+        format!("Program data: {}", base64::encode(data))
+    }
+
+    #[test]
+    fn decodes_pump_swap_sells_whatever_the_timestamp() {
+        // Low timestamp bytes 0x00, 0x7f and 0xff all change the 11th base64 character
+        for timestamp in [1_700_000_000u64, 1_700_000_127, 1_700_000_255] {
+            let logs = vec![pump_swap_sell_log(timestamp, 300, 50, 20)];
+            let bytes = find_program_data(&logs, PUMP_SWAP_SELL_PROGRAM_DATA_PREFIX).expect("sell event found");
+            let (data, _) = decode_pump_swap_event(&bytes).expect("sell event decoded");
+            assert_eq!(data.timestamp, timestamp);
+            assert_eq!(venue_fee_lamports(&logs), 370);
+        }
+    }
+}
@@ -81,6 +81,7 @@ impl Config {
             let rpc_client = create_rpc_client().unwrap();
             let rpc_nonblocking_client = create_nonblocking_rpc_client().await.unwrap();
             let nozomi_rpc_client = create_nozomi_nonblocking_rpc_client().await.unwrap();
+            let nozomi = Arc::new(crate::utilities::nozomi::Nozomi::new(nozomi_rpc_client.clone()));
             let zeroslot_rpc_client = create_zeroslot_rpc_client().await.unwrap();
             let wallet: std::sync::Arc<anchor_client::solana_sdk::signature::Keypair> = import_wallet().unwrap();
             let balance = match rpc_nonblocking_client
@@ -95,12 +96,14 @@ impl Config {
 
             let wallet_cloned = wallet.clone();
             let swap_direction = SwapDirection::Buy; //SwapDirection::Sell
-            let in_type = SwapInType::Qty; //SwapInType::Pct
+            // qty: TOKEN_AMOUNT is SOL, pct: TOKEN_AMOUNT is a percentage of the wallet balance
+            let in_type = env::var("TOKEN_AMOUNT_TYPE")
+                .ok()
+                .and_then(|v| crate::engine::position_sizing::parse_in_type(&v))
+                .unwrap_or(SwapInType::Qty);
             let amount_in = import_env_var("TOKEN_AMOUNT")
                 .parse::<f64>()
-                .unwrap_or(0.001_f64); //quantity
-                                        // let in_type = "pct"; //percentage
-                                        // let amount_in = 0.5; //percentage
+                .unwrap_or(0.001_f64);
 
             let swap_config = SwapConfig {
                 swap_direction,
@@ -113,6 +116,7 @@ impl Config {
                 rpc_client,
                 rpc_nonblocking_client,
                 nozomi_rpc_client,
+                nozomi,
                 zeroslot_rpc_client,
                 wallet,
                 protocol_preference: SwapProtocol::default(),
@@ -170,13 +174,17 @@ pub const PUMP_FUN_BUY_LOG_INSTRUCTION: &str = "Buy";
 pub const PUMP_FUN_PROGRAM_DATA_PREFIX: &str = "Program data: G3KpTd7rY3Y";
 pub const PUMP_FUN_SELL_LOG_INSTRUCTION: &str = "Sell";
 pub const PUMP_FUN_BUY_OR_SELL_PROGRAM_DATA_PREFIX: &str = "Program data: vdt/007mYe";
+pub const PUMP_FUN_CREATE_LOG_INSTRUCTIONS: &[&str] = &["Create", "CreateV2"];
+pub const PUMP_FUN_COMPLETE_PROGRAM_DATA_PREFIX: &str = "Program data: X3JhnNQumA";
 
 //TODO: pumpswap
-pub const PUMP_SWAP_LOG_INSTRUCTION: &str = "Migerate";
+// Logged by the PumpFun program when it moves a completed curve into a PumpSwap pool
+pub const PUMP_SWAP_LOG_INSTRUCTION: &str = "Migrate";
+pub const PUMP_SWAP_CREATE_POOL_PROGRAM_DATA_PREFIX: &str = "Program data: sTEM0qB2p3";
 pub const PUMP_SWAP_BUY_LOG_INSTRUCTION: &str = "Buy";
-pub const PUMP_SWAP_BUY_PROGRAM_DATA_PREFIX: &str = "PProgram data: Z/RSHyz1d3";
+pub const PUMP_SWAP_BUY_PROGRAM_DATA_PREFIX: &str = "Program data: Z/RSHyz1d3";
 pub const PUMP_SWAP_SELL_LOG_INSTRUCTION: &str = "Sell";
-pub const PUMP_SWAP_SELL_PROGRAM_DATA_PREFIX: &str = "Program data: Pi83CqUD3Cp";
+pub const PUMP_SWAP_SELL_PROGRAM_DATA_PREFIX: &str = "Program data: Pi83CqUD3C";
 
 //TODO: raydium launchpad
 pub const RAYDIUM_LAUNCHPAD_LOG_INSTRUCTION: &str = "MintTo";
@@ -184,6 +192,7 @@ pub const RAYDIUM_LAUNCHPAD_PROGRAM_DATA_PREFIX: &str = "Program data: G3KpTd7rY
 pub const RAYDIUM_LAUNCHPAD_BUY_LOG_INSTRUCTION: &str = "Buy";
 pub const RAYDIUM_LAUNCHPAD_BUY_OR_SELL_PROGRAM_DATA_PREFIX: &str = "Program data: vdt/007mYe";
 pub const RAYDIUM_LAUNCHPAD_SELL_LOG_INSTRUCTION: &str = "Sell";
+pub const RAYDIUM_LAUNCHPAD_CREATE_LOG_INSTRUCTIONS: &[&str] = &["Initialize", "InitializeV2"];
 
 
 pub const JUPITER_PROGRAM: &str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
@@ -239,6 +248,7 @@ pub struct AppState {
     pub rpc_client: Arc<anchor_client::solana_client::rpc_client::RpcClient>,
     pub rpc_nonblocking_client: Arc<anchor_client::solana_client::nonblocking::rpc_client::RpcClient>,
     pub nozomi_rpc_client: Arc<anchor_client::solana_client::nonblocking::rpc_client::RpcClient>,
+    pub nozomi: Arc<crate::utilities::nozomi::Nozomi>, // one sender so tip accounts keep rotating
     pub zeroslot_rpc_client: Arc<crate::services::zeroslot::ZeroSlotClient>,
     pub wallet: Arc<Keypair>,
     pub protocol_preference: SwapProtocol,
@@ -285,16 +295,15 @@ pub async fn create_nonblocking_rpc_client(
 pub async fn create_nozomi_nonblocking_rpc_client(
 ) -> Result<Arc<anchor_client::solana_client::nonblocking::rpc_client::RpcClient>> {
     let rpc_http = import_env_var("NOZOMI_URL");
-    let rpc_client = anchor_client::solana_client::nonblocking::rpc_client::RpcClient::new_with_commitment(
-        rpc_http,
-        CommitmentConfig::processed(),
-    );
+    let api_key = crate::utilities::nozomi::nozomi_api_key();
+    let rpc_client = crate::utilities::nozomi::nozomi_rpc_client(&rpc_http, api_key.as_deref())
+        .map_err(|e| anyhow::anyhow!("Failed to create Nozomi client: {}", e))?;
     Ok(Arc::new(rpc_client))
 }
 
 pub async fn create_zeroslot_rpc_client() -> Result<Arc<crate::services::zeroslot::ZeroSlotClient>> {
     let client = crate::services::zeroslot::ZeroSlotClient::new(
-        crate::services::zeroslot::ZERO_SLOT_URL
+        &crate::services::zeroslot::zero_slot_url()
     ).map_err(|e| anyhow::anyhow!("Failed to create ZeroSlot client: {}", e))?;
     Ok(Arc::new(client))
 }
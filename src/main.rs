@@ -12,10 +12,20 @@ use anchor_client::solana_sdk::signature::Signer;
 use crate::{
     library::{config::Config, constants::RUN_MSG, cache::WALLET_TOKEN_ACCOUNTS},
     engine::{
-        copy_trading::{start_copy_trading, CopyTradingConfig},
+        backtest,
+        copy_trading::{start_copy_trading, ConsensusConfig, CopyTradingConfig},
+        journal,
+        paper,
+        position_sizing::PositionSizingConfig,
+        position_store,
+        risk_management,
+        rug_check::RugCheckConfig,
+        sniper::{is_sniper_enabled, start_sniper, SniperConfig},
+        target_profiles::{load_target_profiles, register_target_profiles},
+        target_scoring,
         swap::SwapProtocol,
     },
-    utilities::{telegram, cache_maintenance, blockhash_processor::BlockhashProcessor},
+    utilities::{telegram, cache_maintenance, health_check, blockhash_processor::BlockhashProcessor},
     tx_processor::token,
 };
 use crate::library::config::{JUPITER_PROGRAM, OKX_DEX_PROGRAM};
@@ -341,8 +351,144 @@ async fn initialize_target_wallet_token_list(config: &Config, target_addresses:
     Ok(())
 }
 
+/// `backtest <file> [--slippage 300,1000] [--buy-sol 0.1,0.5] [--tp "20:25,50:50;30:100"] [--sl -20,-50]
+/// [--selling-time 0,120] [--format table|csv|jsonl] [--out FILE]`
+///
+/// Sweep values are comma-separated, take-profit ladders are separated by `;`; unset ones come from the env.
+fn run_backtest_command(args: &[String]) -> anyhow::Result<()> {
+    let path = args.get(2)
+        .filter(|arg| !arg.starts_with("--"))
+        .ok_or_else(|| anyhow::anyhow!("usage: backtest <recording> [--slippage ..] [--buy-sol ..] [--tp ..] [--sl ..] [--selling-time ..]"))?;
+    let option = |name: &str| args.iter().position(|arg| arg == name).and_then(|i| args.get(i + 1)).cloned();
+
+    let settings = backtest::BacktestSettings::from_env()?;
+    let mut grid = backtest::SweepGrid::from_env(&settings.selling);
+    if let Some(value) = option("--slippage") {
+        grid.slippage_bps = backtest::parse_sweep(&value)?;
+    }
+    if let Some(value) = option("--buy-sol") {
+        grid.buy_sol = backtest::parse_sweep(&value)?;
+    }
+    if let Some(value) = option("--tp") {
+        grid.take_profit_tiers = backtest::parse_tier_sweep(&value)?;
+    }
+    if let Some(value) = option("--sl") {
+        grid.stop_loss_percentage = backtest::parse_sweep(&value)?;
+    }
+    if let Some(value) = option("--selling-time") {
+        grid.selling_time = backtest::parse_sweep(&value)?;
+    }
+
+    let events = backtest::load_events(path)?;
+    println!(
+        "Replaying {} events from {} over {} parameter sets",
+        events.len(), path, grid.combinations().len()
+    );
+    let results = backtest::run_sweep(&events, &settings, &grid);
+    let output = backtest::format_backtest_report(&results, &option("--format").unwrap_or_else(|| "table".to_string()))?;
+    match option("--out") {
+        Some(out) => {
+            std::fs::write(&out, output)?;
+            println!("Backtest report written to {}", out);
+        }
+        None => print!("{}", output),
+    }
+    Ok(())
+}
+
+/// `targets` prints the stats, `targets resume <address>` clears an automatic pause
+fn run_targets_command(args: &[String]) -> anyhow::Result<()> {
+    if let Ok(profiles) = load_target_profiles(&[]) {
+        register_target_profiles(&profiles);
+    }
+    target_scoring::init_target_scoring()
+        .map_err(|e| anyhow::anyhow!("Failed to load target stats: {}", e))?;
+    if args.get(2).map(String::as_str) == Some("resume") {
+        let address = args.get(3).ok_or_else(|| anyhow::anyhow!("usage: targets resume <address>"))?;
+        if !target_scoring::resume_target(address) {
+            return Err(anyhow::anyhow!("No stats for target {}", address));
+        }
+        println!("Resumed {}", address);
+        return Ok(());
+    }
+    let lines = target_scoring::format_target_report(&target_scoring::get_target_stats());
+    if lines.is_empty() {
+        println!("No target stats recorded yet");
+    }
+    for line in lines {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// `report [--by day|target|venue|mint] [--format table|csv|jsonl] [--out FILE] [--entries]`
+fn run_report_command(args: &[String]) -> anyhow::Result<()> {
+    let option = |name: &str| args.iter().position(|arg| arg == name).and_then(|i| args.get(i + 1)).cloned();
+    let entries = journal::read_journal(&journal::JOURNAL_FILE)?;
+    let output = if args.contains(&"--entries".to_string()) {
+        journal::format_entries(&entries, &option("--format").unwrap_or_else(|| "csv".to_string()))?
+    } else {
+        let open_positions = position_store::open_position_store()
+            .and_then(|_| position_store::load_positions())
+            .unwrap_or_else(|e| {
+                eprintln!("Open positions not included: {}", e);
+                Vec::new()
+            });
+        let day_start_hour_utc = risk_management::RISK_CONFIG.day_start_hour_utc;
+        let mut rows = journal::build_report(&entries, &open_positions, day_start_hour_utc, chrono::Utc::now().timestamp());
+        if let Some(by) = option("--by") {
+            rows.retain(|row| row.dimension == by);
+        }
+        journal::format_report(&rows, &option("--format").unwrap_or_else(|| "table".to_string()))?
+    };
+    match option("--out") {
+        Some(path) => {
+            std::fs::write(&path, output).map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path, e))?;
+            println!("Report written to {}", path);
+        }
+        None => print!("{}", output),
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() {
+    // `--paper` swaps the landing layer for the simulator; set before any book or journal is opened
+    if std::env::args().any(|arg| arg == "--paper") {
+        paper::enable_paper_mode();
+    }
+
+    // `backtest` replays a recording offline, without RPC, wallet or gRPC connections
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("backtest") {
+        dotenv::dotenv().ok();
+        if let Err(e) = run_backtest_command(&args) {
+            eprintln!("Backtest failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // `targets` only reads the stats file, no connections needed
+    if args.get(1).map(String::as_str) == Some("targets") {
+        dotenv::dotenv().ok();
+        if let Err(e) = run_targets_command(&args) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // `report` only reads the journal and the position book
+    if args.get(1).map(String::as_str) == Some("report") {
+        dotenv::dotenv().ok();
+        if let Err(e) = run_report_command(&args) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     /* Initial Settings */
     let config = Config::new().await;
     let config = config.lock().await;
@@ -367,7 +513,6 @@ async fn main() {
     }
 
     // Parse command line arguments
-    let args: Vec<String> = std::env::args().collect();
     if args.len() > 1 {
         // Check for command line arguments
         if args.contains(&"--wrap".to_string()) {
@@ -418,6 +563,17 @@ async fn main() {
         }
     }
 
+    if paper::is_paper_mode() {
+        println!("{}", format!("PAPER TRADING: no transactions are sent | {}", paper::summary()).yellow().bold());
+    }
+
+    // Landing senders refuse a service the health checker reports down; only the bot run sends through one
+    if !paper::is_paper_mode() {
+        if let Err(e) = health_check::initialize_health_check_manager(&config.transaction_landing_mode).await {
+            eprintln!("Failed to start health checks: {}", e);
+        }
+    }
+
     // Initialize Telegram bot
     match telegram::init().await {
         Ok(_) => println!("Telegram bot initialized successfully"),
@@ -470,8 +626,34 @@ async fn main() {
         }
     }
 
+    // Per-target profiles, targets listed only in the profile file are followed too
+    let target_profiles = match load_target_profiles(&target_addresses) {
+        Ok(profiles) => profiles,
+        Err(e) => {
+            eprintln!("Failed to load target profiles: {}", e);
+            return;
+        }
+    };
+    let target_addresses: Vec<String> = target_profiles.iter().map(|p| p.address.clone()).collect();
+
+    // The launch sniper runs on its own stream, next to copy trading or on its own
+    let sniper_config = SniperConfig::new(
+        config.yellowstone_grpc_http.clone(),
+        config.yellowstone_grpc_token.clone(),
+        Arc::new(config.app_state.clone()),
+        Arc::new(config.swap_config.clone()),
+        config.min_dev_buy,
+        config.max_dev_buy,
+    );
+
     if target_addresses.is_empty() {
-        eprintln!("No COPY_TRADING_TARGET_ADDRESS specified. Please set this environment variable.");
+        if is_sniper_enabled() {
+            if let Err(e) = start_sniper(sniper_config).await {
+                eprintln!("Sniper error: {}", e);
+            }
+            return;
+        }
+        eprintln!("No COPY_TRADING_TARGET_ADDRESS specified. Please set this environment variable or add targets to COPY_TARGETS_FILE.");
         return;
     }
     
@@ -518,8 +700,20 @@ async fn main() {
         transaction_landing_mode: config.transaction_landing_mode.clone(),
         max_dev_buy: config.max_dev_buy,
         min_dev_buy: config.min_dev_buy,
+        target_profiles,
+        position_sizing: PositionSizingConfig::from_env(),
+        consensus: ConsensusConfig::from_env(),
+        rug_check: RugCheckConfig::from_env(),
     };
     
+    if is_sniper_enabled() {
+        tokio::spawn(async move {
+            if let Err(e) = start_sniper(sniper_config).await {
+                eprintln!("Sniper error: {}", e);
+            }
+        });
+    }
+
     // Start the copy trading bot
     if let Err(e) = start_copy_trading(copy_trading_config).await {
         eprintln!("Copy trading error: {}", e);
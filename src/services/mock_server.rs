@@ -0,0 +1,58 @@
+use serde_json::Value;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// What the mock server received
+pub(crate) struct MockRequest {
+    pub headers: String, // lowercased
+    pub body: Value,
+}
+
+/// Serve a single JSON-RPC request on a local port, answering with `respond(body)` as (status line, JSON)
+///
+/// Returns the URL to post to and a handle yielding the request once it was answered.
+pub(crate) async fn serve_once<F>(respond: F) -> (String, JoinHandle<MockRequest>)
+where
+    F: FnOnce(&Value) -> (&'static str, String) + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/rpc", listener.local_addr().unwrap());
+    let handle = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buffer = [0u8; 4096];
+        let (header_end, content_length) = loop {
+            let read = socket.read(&mut buffer).await.unwrap();
+            assert!(read > 0, "connection closed before the request was complete");
+            request.extend_from_slice(&buffer[..read]);
+            if let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+                let headers = String::from_utf8_lossy(&request[..end]).to_lowercase();
+                let length = headers
+                    .lines()
+                    .find_map(|line| line.strip_prefix("content-length:"))
+                    .and_then(|value| value.trim().parse::<usize>().ok())
+                    .unwrap_or(0);
+                break (end + 4, length);
+            }
+        };
+        while request.len() < header_end + content_length {
+            let read = socket.read(&mut buffer).await.unwrap();
+            assert!(read > 0, "connection closed before the body was complete");
+            request.extend_from_slice(&buffer[..read]);
+        }
+
+        let headers = String::from_utf8_lossy(&request[..header_end]).to_lowercase();
+        let body: Value = serde_json::from_slice(&request[header_end..header_end + content_length]).unwrap();
+        let (status, reply) = respond(&body);
+        let response = format!(
+            "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            status,
+            reply.len(),
+            reply
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+        MockRequest { headers, body }
+    });
+    (url, handle)
+}
@@ -1,4 +1,4 @@
-// Placeholder for services module
-// This module was removed for public purposes
+pub mod zeroslot;
 
-pub mod zeroslot; 
\ No newline at end of file
+#[cfg(test)]
+pub(crate) mod mock_server;
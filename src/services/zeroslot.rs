@@ -1,26 +1,259 @@
-// Placeholder for zeroslot service module
-// This module was removed for public purposes
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use anchor_client::solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+use reqwest::Client;
+use serde_json::{json, Value};
 
 use crate::error::ClientError;
+use crate::tx_processor::fill;
 
-/// Placeholder constant for ZeroSlot URL
-pub const ZERO_SLOT_URL: &str = "https://placeholder.zeroslot.url";
+/// Default host, override with `ZERO_SLOT_URL`; requests go to its `/rpc` path
+pub const ZERO_SLOT_URL: &str = "https://api.zeroslot.io";
+/// Default tip account, override with `ZERO_SLOT_TIP_ACCOUNT`
+pub const ZERO_SLOT_TIP_ACCOUNT: &str = "6fQaVhYZA4w3MBSXjJ81Vf6W1EDYeUPXpgVQ6UQyU1Av";
+const DEFAULT_TIP_SOL: f64 = 0.001;
 
-/// Placeholder struct for ZeroSlotClient functionality
-pub struct ZeroSlotClient;
+/// JSON-RPC endpoint from `ZERO_SLOT_URL`
+pub fn zero_slot_url() -> String {
+    rpc_endpoint(
+        &std::env::var("ZERO_SLOT_URL")
+            .ok()
+            .filter(|url| !url.is_empty())
+            .unwrap_or_else(|| ZERO_SLOT_URL.to_string()),
+    )
+}
+
+/// `ZERO_SLOT_URL` is a host, `/rpc` is appended to its path unless it is already there
+fn rpc_endpoint(url: &str) -> String {
+    let Ok(mut parsed) = reqwest::Url::parse(url) else {
+        return url.to_string();
+    };
+    let path = parsed.path().trim_end_matches('/');
+    let path = if path.ends_with("/rpc") { path.to_string() } else { format!("{}/rpc", path) };
+    parsed.set_path(&path);
+    parsed.to_string()
+}
+
+/// JSON-RPC client for the 0slot landing service
+///
+/// Transactions must carry a tip transfer to the tip account to be forwarded; `build_transaction`
+/// appends it. The connection is kept warm since 0slot drops connections idle for more than 65s.
+pub struct ZeroSlotClient {
+    endpoint: String,
+    client: Client,
+    tip_account: Pubkey,
+    tip_lamports: u64,
+    request_id: AtomicU64,
+}
 
 impl ZeroSlotClient {
-    /// Creates a new ZeroSlotClient instance
-    pub fn new(_url: &str) -> Result<Self, ClientError> {
-        // Placeholder implementation
-        // Add actual ZeroSlot client initialization here when needed
-        Ok(Self)
-    }
-    
-    /// Placeholder method for ZeroSlot operations
-    pub async fn query(&self) -> Result<(), ClientError> {
-        // Placeholder implementation
-        // Add actual ZeroSlot query logic here when needed
-        Ok(())
-    }
-} 
\ No newline at end of file
+    /// Client for `url` tipping `ZERO_SLOT_TIP_ACCOUNT` with `ZERO_SLOT_TIP_VALUE` SOL (default 0.001)
+    pub fn new(url: &str) -> Result<Self, ClientError> {
+        let tip_account = std::env::var("ZERO_SLOT_TIP_ACCOUNT")
+            .ok()
+            .filter(|account| !account.is_empty())
+            .unwrap_or_else(|| ZERO_SLOT_TIP_ACCOUNT.to_string());
+        let tip_account = Pubkey::from_str(&tip_account)?;
+        let tip_sol = std::env::var("ZERO_SLOT_TIP_VALUE")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(DEFAULT_TIP_SOL);
+        Self::with_tip(url, tip_account, (tip_sol * 1_000_000_000.0) as u64)
+    }
+
+    pub fn with_tip(url: &str, tip_account: Pubkey, tip_lamports: u64) -> Result<Self, ClientError> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .pool_idle_timeout(Duration::from_secs(60))
+            .pool_max_idle_per_host(4)
+            .tcp_keepalive(Duration::from_secs(30))
+            .tcp_nodelay(true)
+            .http2_keep_alive_interval(Duration::from_secs(15))
+            .http2_keep_alive_timeout(Duration::from_secs(5))
+            .http2_keep_alive_while_idle(true)
+            .build()
+            .map_err(|e| ClientError::Send("Failed to build ZeroSlot HTTP client".to_string(), e.to_string()))?;
+        // Measured fills split the tip out of the swap cost
+        fill::register_tip_accounts(&[tip_account.to_string().as_str()]);
+        Ok(Self {
+            endpoint: url.to_string(),
+            client,
+            tip_account,
+            tip_lamports,
+            request_id: AtomicU64::new(1),
+        })
+    }
+
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    pub fn tip_account(&self) -> &Pubkey {
+        &self.tip_account
+    }
+
+    pub fn tip_lamports(&self) -> u64 {
+        self.tip_lamports
+    }
+
+    /// Transfer of the tip from `payer` to the tip account
+    pub fn tip_instruction(&self, payer: &Pubkey) -> Instruction {
+        system_instruction::transfer(payer, &self.tip_account, self.tip_lamports)
+    }
+
+    /// Sign `instructions` with the tip transfer appended
+    pub fn build_transaction(&self, keypair: &Keypair, instructions: &[Instruction], recent_blockhash: Hash) -> Transaction {
+        let mut instructions = instructions.to_vec();
+        instructions.push(self.tip_instruction(&keypair.pubkey()));
+        Transaction::new_signed_with_payer(&instructions, Some(&keypair.pubkey()), &[keypair], recent_blockhash)
+    }
+
+    /// Submit a signed transaction, returns its signature
+    pub async fn send_transaction(&self, transaction: &Transaction) -> Result<String, ClientError> {
+        let serialized = bincode::serialize(transaction)
+            .map_err(|e| ClientError::Send("Failed to serialize transaction".to_string(), e.to_string()))?;
+        let result = self.call("sendTransaction", json!([
+            base64::encode(serialized),
+            { "encoding": "base64", "skipPreflight": true, "maxRetries": 0 }
+        ])).await?;
+        result
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| ClientError::Send("ZeroSlot returned no signature".to_string(), result.to_string()))
+    }
+
+    /// Sign `instructions` with the tip and submit them
+    pub async fn send_instructions(
+        &self,
+        keypair: &Keypair,
+        instructions: &[Instruction],
+        recent_blockhash: Hash,
+    ) -> Result<String, ClientError> {
+        self.send_transaction(&self.build_transaction(keypair, instructions, recent_blockhash)).await
+    }
+
+    /// `getHealth` over the pooled connection, which also keeps it alive
+    pub async fn get_health(&self) -> Result<(), ClientError> {
+        self.call("getHealth", Value::Null).await.map(|_| ())
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value, ClientError> {
+        let mut body = json!({
+            "jsonrpc": "2.0",
+            "id": self.request_id.fetch_add(1, Ordering::Relaxed),
+            "method": method,
+        });
+        if !params.is_null() {
+            body["params"] = params;
+        }
+        let response = self.client
+            .post(&self.endpoint)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| ClientError::Send(format!("ZeroSlot {} request failed", method), e.to_string()))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| ClientError::Send(format!("ZeroSlot {} response unreadable", method), e.to_string()))?;
+        if !status.is_success() {
+            return Err(ClientError::Send(format!("ZeroSlot {} returned HTTP {}", method, status), text));
+        }
+        let mut reply: Value = serde_json::from_str(&text)
+            .map_err(|e| ClientError::Send(format!("ZeroSlot {} returned invalid JSON", method), e.to_string()))?;
+        if let Some(error) = reply.get("error").filter(|error| !error.is_null()) {
+            let message = error.get("message").and_then(Value::as_str).unwrap_or("unknown error");
+            return Err(ClientError::Send(format!("ZeroSlot {} rejected: {}", method, message), error.to_string()));
+        }
+        Ok(reply.get_mut("result").map(Value::take).unwrap_or(Value::Null))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_client::solana_sdk::system_program;
+    use crate::services::mock_server::serve_once;
+
+    fn client(url: &str) -> ZeroSlotClient {
+        ZeroSlotClient::with_tip(url, Pubkey::from_str(ZERO_SLOT_TIP_ACCOUNT).unwrap(), 1_000_000).unwrap()
+    }
+
+    #[tokio::test]
+    async fn sends_base64_transaction_with_tip() {
+        let (url, request) = serve_once(|_| ("200 OK", r#"{"jsonrpc":"2.0","id":1,"result":"5ig"}"#.to_string())).await;
+        let zeroslot = client(&url);
+        let keypair = Keypair::new();
+        let transfer = system_instruction::transfer(&keypair.pubkey(), &Pubkey::new_unique(), 10);
+
+        let signature = zeroslot.send_instructions(&keypair, &[transfer], Hash::default()).await.unwrap();
+        assert_eq!(signature, "5ig");
+
+        let request = request.await.unwrap().body;
+        assert_eq!(request["method"], "sendTransaction");
+        assert_eq!(request["params"][1]["encoding"], "base64");
+        let bytes = base64::decode(request["params"][0].as_str().unwrap()).unwrap();
+        let transaction: Transaction = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(transaction.message.instructions.len(), 2);
+
+        let tip = transaction.message.instructions.last().unwrap();
+        let keys = &transaction.message.account_keys;
+        assert_eq!(keys[tip.program_id_index as usize], system_program::id());
+        assert_eq!(keys[tip.accounts[1] as usize], *zeroslot.tip_account());
+        assert_eq!(u64::from_le_bytes(tip.data[4..12].try_into().unwrap()), 1_000_000);
+    }
+
+    #[tokio::test]
+    async fn maps_rpc_error_to_send() {
+        let (url, _) = serve_once(|_| (
+            "200 OK",
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"tip too low"}}"#.to_string(),
+        )).await;
+        let zeroslot = client(&url);
+        let transaction = zeroslot.build_transaction(&Keypair::new(), &[], Hash::default());
+        match zeroslot.send_transaction(&transaction).await {
+            Err(ClientError::Send(message, _)) => assert!(message.contains("tip too low")),
+            other => panic!("expected a send error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn maps_http_error_to_send() {
+        let (url, _) = serve_once(|_| ("503 Service Unavailable", "{}".to_string())).await;
+        match client(&url).get_health().await {
+            Err(ClientError::Send(message, _)) => assert!(message.contains("503")),
+            other => panic!("expected a send error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn appends_rpc_path_once() {
+        assert_eq!(rpc_endpoint("https://api.zeroslot.io"), "https://api.zeroslot.io/rpc");
+        assert_eq!(rpc_endpoint("https://api.zeroslot.io/"), "https://api.zeroslot.io/rpc");
+        assert_eq!(rpc_endpoint("https://ny.0slot.trade/rpc"), "https://ny.0slot.trade/rpc");
+        assert_eq!(rpc_endpoint("https://ny.0slot.trade/rpc/"), "https://ny.0slot.trade/rpc");
+    }
+
+    #[test]
+    fn keeps_the_query_string() {
+        assert_eq!(rpc_endpoint("https://ny.0slot.trade?api-key=KEY"), "https://ny.0slot.trade/rpc?api-key=KEY");
+        assert_eq!(rpc_endpoint("https://ny.0slot.trade/rpc?api-key=KEY"), "https://ny.0slot.trade/rpc?api-key=KEY");
+    }
+
+    #[tokio::test]
+    async fn maps_unreachable_endpoint_to_send() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/rpc", listener.local_addr().unwrap());
+        drop(listener);
+        assert!(matches!(client(&url).get_health().await, Err(ClientError::Send(_, _))));
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_client::rpc_config::RpcTransactionConfig;
+use anchor_client::solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use lazy_static::lazy_static;
+use solana_transaction_status::{option_serializer::OptionSerializer, UiTransactionEncoding};
+use tokio::time::sleep;
+
+use crate::engine::journal;
+use crate::engine::paper;
+use crate::engine::swap::SwapDirection;
+use crate::engine::transaction_parser::venue_fee_lamports;
+
+pub(crate) const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const FILL_FETCH_ATTEMPTS: usize = 20;
+
+lazy_static! {
+    // Accounts whose lamport increase counts as a tip; extra ones come from `TIP_ACCOUNTS`
+    static ref TIP_ACCOUNTS: RwLock<HashSet<String>> = RwLock::new(
+        std::env::var("TIP_ACCOUNTS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|account| account.trim().to_string())
+            .filter(|account| !account.is_empty())
+            .collect()
+    );
+}
+
+/// Count lamports sent to these accounts as tips in measured fills
+pub fn register_tip_accounts(accounts: &[&str]) {
+    let mut tip_accounts = TIP_ACCOUNTS.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+    tip_accounts.extend(accounts.iter().map(|account| account.to_string()));
+}
+
+/// What one of our swaps actually moved, read from the confirmed transaction
+#[derive(Debug, Clone, Default)]
+pub struct Fill {
+    pub signature: String,
+    pub token_amount: u64,     // raw tokens received on a buy, sent on a sell
+    pub sol_amount: f64,       // SOL paid for / received from the tokens, venue fees included, other costs excluded
+    pub price: f64,            // SOL per token, same unit as `TradeInfoFromToken::price_in_sol`
+    pub base_fee_sol: f64,
+    pub priority_fee_sol: f64,
+    pub tip_sol: f64,
+    pub rent_sol: f64,         // ATA rent paid, negative when the account was closed and refunded
+    pub venue_fee_sol: f64,
+}
+
+impl Fill {
+    pub fn fee_sol(&self) -> f64 {
+        self.base_fee_sol + self.priority_fee_sol
+    }
+
+    /// Percent the fill was worse than `quoted_price`, negative when it was better
+    pub fn slippage_percentage(&self, quoted_price: f64, direction: &SwapDirection) -> f64 {
+        if quoted_price <= 0.0 || self.price <= 0.0 {
+            return 0.0;
+        }
+        match direction {
+            SwapDirection::Buy => (self.price - quoted_price) / quoted_price * 100.0,
+            SwapDirection::Sell => (quoted_price - self.price) / quoted_price * 100.0,
+        }
+    }
+}
+
+/// Lamport movements of one of our swaps, from the wallet's side
+#[derive(Debug, Clone, Copy, Default)]
+struct BalanceChanges {
+    wallet: i64,  // SOL balance change of the wallet
+    wsol: i64,    // token balance change of the wallet's WSOL account, venues may pay or charge in WSOL
+    fee: i64,     // base and priority fee
+    tip: i64,
+    rent: i64,    // rent paid into accounts the swap opened, negative when refunded
+}
+
+/// Lamports paid to the venue for a buy or received from it for a sell
+///
+/// Whatever the wallet lost or gained beyond fees, tips and rent went to or came from the venue.
+fn trade_lamports(direction: &SwapDirection, changes: BalanceChanges) -> i64 {
+    let received = changes.wallet + changes.wsol + changes.fee + changes.tip + changes.rent;
+    match direction {
+        SwapDirection::Buy => -received,
+        SwapDirection::Sell => received,
+    }
+}
+
+/// Fetch our confirmed swap `signature` and split the wallet's balance changes into the fill and its costs
+///
+/// Retries for a few seconds since the transaction is usually not queryable right after it lands.
+pub async fn fetch_fill(
+    rpc_client: Arc<RpcClient>,
+    signature: &str,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    direction: SwapDirection,
+) -> Result<Fill> {
+    if paper::is_paper_mode() {
+        let fill = paper::take_fill(signature).ok_or_else(|| anyhow!("No simulated fill for {}", signature));
+        let confirmation = fill.as_ref().map(|_| ()).map_err(|e| e.to_string());
+        journal::record_confirmation(&mint.to_string(), &direction, signature, confirmation);
+        return fill;
+    }
+    let parsed_signature = Signature::from_str(signature)
+        .map_err(|e| anyhow!("Invalid signature {}: {}", signature, e))?;
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        commitment: Some(CommitmentConfig::confirmed()),
+        max_supported_transaction_version: Some(0),
+    };
+
+    let mut last_error = String::new();
+    let mut confirmed = None;
+    for _ in 0..FILL_FETCH_ATTEMPTS {
+        match rpc_client.get_transaction_with_config(&parsed_signature, config).await {
+            Ok(transaction) => {
+                confirmed = Some(transaction);
+                break;
+            }
+            Err(e) => last_error = e.to_string(),
+        }
+        sleep(Duration::from_millis(500)).await;
+    }
+    let Some(confirmed) = confirmed else {
+        let reason = format!("Transaction {} not found: {}", signature, last_error);
+        journal::record_confirmation(&mint.to_string(), &direction, signature, Err(reason.clone()));
+        return Err(anyhow!(reason));
+    };
+
+    let meta = confirmed.transaction.meta
+        .ok_or_else(|| anyhow!("Transaction {} has no status meta", signature))?;
+    if let Some(err) = meta.err {
+        let reason = format!("Transaction {} failed: {:?}", signature, err);
+        journal::record_confirmation(&mint.to_string(), &direction, signature, Err(reason.clone()));
+        return Err(anyhow!(reason));
+    }
+    journal::record_confirmation(&mint.to_string(), &direction, signature, Ok(()));
+    let transaction = confirmed.transaction.transaction
+        .decode()
+        .ok_or_else(|| anyhow!("Failed to decode transaction {}", signature))?;
+
+    // Static keys first, then the lookup table writable and readonly addresses, as balances are indexed
+    let mut account_keys: Vec<String> = transaction.message
+        .static_account_keys()
+        .iter()
+        .map(|key| key.to_string())
+        .collect();
+    if let OptionSerializer::Some(loaded) = &meta.loaded_addresses {
+        account_keys.extend(loaded.writable.iter().cloned());
+        account_keys.extend(loaded.readonly.iter().cloned());
+    }
+    let lamport_delta = |index: usize| -> i64 {
+        let pre = meta.pre_balances.get(index).copied().unwrap_or(0) as i64;
+        let post = meta.post_balances.get(index).copied().unwrap_or(0) as i64;
+        post - pre
+    };
+
+    let wallet_key = wallet.to_string();
+    let wallet_index = account_keys.iter()
+        .position(|key| *key == wallet_key)
+        .ok_or_else(|| anyhow!("Wallet not in transaction {}", signature))?;
+
+    // Our token account for the mint and its raw balance before and after
+    let mint_key = mint.to_string();
+    let mut token_account = None;
+    let mut pre_tokens = 0u64;
+    let mut post_tokens = 0u64;
+    let mut decimals = 6u8;
+    // Our WSOL account, which some venues pay sell proceeds into or take buys from
+    let mut wsol_account = None;
+    let mut pre_wsol = 0i64;
+    let mut post_wsol = 0i64;
+    let balances = [
+        (Option::<Vec<_>>::from(meta.pre_token_balances.clone()).unwrap_or_default(), true),
+        (Option::<Vec<_>>::from(meta.post_token_balances.clone()).unwrap_or_default(), false),
+    ];
+    for (token_balances, is_pre) in balances {
+        for balance in token_balances {
+            let owner: Option<String> = balance.owner.clone().into();
+            if owner.as_deref() != Some(wallet_key.as_str()) {
+                continue;
+            }
+            let amount = balance.ui_token_amount.amount.parse::<u64>().unwrap_or(0);
+            if balance.mint == mint_key {
+                token_account = Some(balance.account_index as usize);
+                decimals = balance.ui_token_amount.decimals;
+                if is_pre { pre_tokens = amount } else { post_tokens = amount }
+            } else if balance.mint == WSOL_MINT {
+                wsol_account = Some(balance.account_index as usize);
+                if is_pre { pre_wsol = amount as i64 } else { post_wsol = amount as i64 }
+            }
+        }
+    }
+
+    let tip_lamports: i64 = {
+        let tip_accounts = TIP_ACCOUNTS.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        account_keys.iter()
+            .enumerate()
+            .filter(|(_, key)| tip_accounts.contains(*key))
+            .map(|(index, _)| lamport_delta(index).max(0))
+            .sum()
+    };
+    let wsol_delta = post_wsol - pre_wsol;
+    // Lamports of the WSOL account beyond its token balance change are rent
+    let rent_lamports = token_account.map(&lamport_delta).unwrap_or(0)
+        + wsol_account.map(|index| lamport_delta(index) - wsol_delta).unwrap_or(0);
+    let fee = meta.fee as i64;
+    let base_fee = (LAMPORTS_PER_SIGNATURE * transaction.signatures.len() as u64) as i64;
+    let priority_fee = (fee - base_fee).max(0);
+    let wallet_delta = lamport_delta(wallet_index);
+    let logs = Option::<Vec<String>>::from(meta.log_messages.clone()).unwrap_or_default();
+
+    let trade_lamports = trade_lamports(&direction, BalanceChanges {
+        wallet: wallet_delta,
+        wsol: wsol_delta,
+        fee,
+        tip: tip_lamports,
+        rent: rent_lamports,
+    });
+    let token_amount = match direction {
+        SwapDirection::Buy => post_tokens.saturating_sub(pre_tokens),
+        SwapDirection::Sell => pre_tokens.saturating_sub(post_tokens),
+    };
+    if token_amount == 0 {
+        return Err(anyhow!("Transaction {} moved no {} tokens", signature, mint_key));
+    }
+
+    let sol_amount = trade_lamports.max(0) as f64 / 1_000_000_000.0;
+    Ok(Fill {
+        signature: signature.to_string(),
+        token_amount,
+        sol_amount,
+        price: sol_amount / (token_amount as f64 / 10f64.powi(decimals as i32)),
+        base_fee_sol: base_fee as f64 / 1_000_000_000.0,
+        priority_fee_sol: priority_fee as f64 / 1_000_000_000.0,
+        tip_sol: tip_lamports as f64 / 1_000_000_000.0,
+        rent_sol: rent_lamports as f64 / 1_000_000_000.0,
+        venue_fee_sol: venue_fee_lamports(&logs) as f64 / 1_000_000_000.0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buy_excludes_fee_tip_and_rent() {
+        // 0.5 SOL to the venue, 0.000105 fee, 0.001 tip, 0.00203928 ATA rent
+        let changes = BalanceChanges {
+            wallet: -(500_000_000 + 105_000 + 1_000_000 + 2_039_280),
+            fee: 105_000,
+            tip: 1_000_000,
+            rent: 2_039_280,
+            ..Default::default()
+        };
+        assert_eq!(trade_lamports(&SwapDirection::Buy, changes), 500_000_000);
+    }
+
+    #[test]
+    fn sell_counts_rent_refund_and_wsol_proceeds() {
+        // Closing the token account refunds its rent; part of the proceeds land as WSOL
+        let changes = BalanceChanges {
+            wallet: 300_000_000 - 105_000 - 1_000_000 + 2_039_280,
+            wsol: 400_000_000,
+            fee: 105_000,
+            tip: 1_000_000,
+            rent: -2_039_280,
+        };
+        assert_eq!(trade_lamports(&SwapDirection::Sell, changes), 700_000_000);
+    }
+
+    #[test]
+    fn buy_paid_from_wsol() {
+        let changes = BalanceChanges { wallet: -5_000, wsol: -250_000_000, fee: 5_000, ..Default::default() };
+        assert_eq!(trade_lamports(&SwapDirection::Buy, changes), 250_000_000);
+    }
+}
@@ -1,2 +1,3 @@
+pub mod fill;
 pub mod token;
 pub mod tx;
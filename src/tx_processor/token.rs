@@ -96,7 +96,8 @@ pub async fn get_mint_info(
         .ok_or(TokenError::AccountNotFound)
         .inspect_err(|err| println!("{} {}: mint {}", address, err, address))?;
 
-    if account.owner != spl_token::ID {
+    // Token-2022 mints are unpacked with their extensions
+    if account.owner != spl_token::ID && account.owner != spl_token_2022::ID {
         return Err(TokenError::AccountInvalidOwner);
     }
 
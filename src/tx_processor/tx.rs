@@ -1,15 +1,11 @@
 use std::sync::Arc;
 use std::str::FromStr;
-use anyhow::{Result, anyhow};
+use anyhow::Result;
 use colored::Colorize;
-use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
 use anchor_client::solana_sdk::{
     instruction::Instruction,
     signature::Keypair,
-    system_instruction,
-    transaction::Transaction,
     hash::Hash,
-    signature::Signature,
 };
 use std::env;
 use anchor_client::solana_sdk::pubkey::Pubkey;
@@ -21,31 +17,27 @@ use reqwest::{Client, ClientBuilder};
 use base64;
 use std::time::Duration;
 
-use crate::{
-    library::logger::Logger,
-    utilities::{
-        nozomi,
-        zeroslot,
-    },
+use crate::utilities::{
+    nozomi,
+    zeroslot,
 };
-use dotenv::dotenv;
 
 // prioritization fee = UNIT_PRICE * UNIT_LIMIT
-fn get_unit_price() -> u64 {
+pub fn get_unit_price() -> u64 {
     env::var("UNIT_PRICE")
         .ok()
         .and_then(|v| u64::from_str(&v).ok())
         .unwrap_or(20000)
 }
 
-fn get_unit_limit() -> u32 {
+pub fn get_unit_limit() -> u32 {
     env::var("UNIT_LIMIT")
         .ok()
         .and_then(|v| u32::from_str(&v).ok())
         .unwrap_or(200_000)
 }
 
-// Cache the tip value for better performance
+// Nozomi tip in SOL, read once
 static NOZOMI_TIP_VALUE: Lazy<f64> = Lazy::new(|| {
     std::env::var("NOZOMI_TIP_VALUE")
         .ok()
@@ -66,11 +58,49 @@ static HTTP_CLIENT: Lazy<Client> = Lazy::new(|| {
    client
 });
 
-// Get nozomi tip value from env
+// Nozomi tip value from `NOZOMI_TIP_VALUE` (default 0.0015 SOL)
 pub fn get_nozomi_tip() -> f64 {
-    dotenv().ok();
-    std::env::var("NOZOMI_TIP_VALUE")
-        .ok()
-        .and_then(|v| f64::from_str(&v).ok())
-        .unwrap_or(0.0015)
+    *NOZOMI_TIP_VALUE
+}
+
+/// Prepend the compute budget to `instructions` and land them through 0slot with its tip
+///
+/// `unit_price` overrides `UNIT_PRICE` for this transaction. Returns the signature.
+pub async fn new_signed_and_send_zeroslot(
+    zeroslot_client: Arc<crate::services::zeroslot::ZeroSlotClient>,
+    recent_blockhash: Hash,
+    keypair: &Keypair,
+    instructions: Vec<Instruction>,
+    unit_price: Option<u64>,
+) -> Result<String, crate::error::ClientError> {
+    use anchor_client::solana_sdk::compute_budget::ComputeBudgetInstruction;
+
+    let mut budgeted = vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(get_unit_limit()),
+        ComputeBudgetInstruction::set_compute_unit_price(unit_price.unwrap_or_else(get_unit_price)),
+    ];
+    budgeted.extend(instructions);
+    zeroslot::ZeroSlot::new(zeroslot_client)
+        .process(keypair, &budgeted, recent_blockhash)
+        .await
+}
+
+/// Prepend the compute budget to `instructions` and land them through Nozomi with a rotating tip
+///
+/// Pass the sender held in `AppState` so tip accounts keep rotating across calls. Returns the signature.
+pub async fn new_signed_and_send_nozomi(
+    nozomi: &nozomi::Nozomi,
+    recent_blockhash: Hash,
+    keypair: &Keypair,
+    instructions: Vec<Instruction>,
+    unit_price: Option<u64>,
+) -> Result<String, crate::error::ClientError> {
+    use anchor_client::solana_sdk::compute_budget::ComputeBudgetInstruction;
+
+    let mut budgeted = vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(get_unit_limit()),
+        ComputeBudgetInstruction::set_compute_unit_price(unit_price.unwrap_or_else(get_unit_price)),
+    ];
+    budgeted.extend(instructions);
+    nozomi.process(keypair, &budgeted, recent_blockhash).await
 }
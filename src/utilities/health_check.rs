@@ -49,34 +49,31 @@ impl HealthCheckManager {
         }
     }
 
-    /// Start the health check service
-    pub async fn start(&self) -> Result<()> {
+    /// Start checking the services `mode` lands through
+    pub async fn start(&self, mode: &TransactionLandingMode) -> Result<()> {
         self.logger.log("Starting health check service...".green().to_string());
+        let names = mode_services(mode);
         
-        // Initialize all services as unknown
+        // Initialize the checked services as unknown
         let mut services = self.services.write().await;
-        services.insert("zeroslot".to_string(), HealthCheckResult {
-            status: HealthStatus::Unknown,
-            response_time: Duration::from_secs(0),
-            last_checked: Instant::now(),
-            error_message: None,
-        });
-        services.insert("nozomi".to_string(), HealthCheckResult {
-            status: HealthStatus::Unknown,
-            response_time: Duration::from_secs(0),
-            last_checked: Instant::now(),
-            error_message: None,
-        });
+        for name in &names {
+            services.insert(name.to_string(), HealthCheckResult {
+                status: HealthStatus::Unknown,
+                response_time: Duration::from_secs(0),
+                last_checked: Instant::now(),
+                error_message: None,
+            });
+        }
         drop(services);
 
         // Start periodic health checks
-        self.start_periodic_checks().await;
+        self.start_periodic_checks(names).await;
         
         Ok(())
     }
 
-    /// Start periodic health checks for all services
-    async fn start_periodic_checks(&self) {
+    /// Start periodic health checks for `names`
+    async fn start_periodic_checks(&self, names: Vec<&'static str>) {
         let services = self.services.clone();
         let client = self.client.clone();
         let logger = self.logger.clone();
@@ -86,14 +83,15 @@ impl HealthCheckManager {
         
         // ZeroSlot keepalive interval (every 60 seconds to stay within 65-second timeout)
         let mut zeroslot_keepalive_interval = interval(Duration::from_secs(60));
+        let zeroslot_keepalive = names.contains(&"zeroslot");
 
         tokio::spawn(async move {
             loop {
                 tokio::select! {
                     _ = health_interval.tick() => {
-                        Self::check_all_services(&services, &client, &logger).await;
+                        Self::check_all_services(&services, &client, &logger, &names).await;
                     }
-                    _ = zeroslot_keepalive_interval.tick() => {
+                    _ = zeroslot_keepalive_interval.tick(), if zeroslot_keepalive => {
                         Self::zeroslot_keepalive(&client, &logger).await;
                     }
                 }
@@ -101,32 +99,29 @@ impl HealthCheckManager {
         });
     }
 
-    /// Check health of all services
+    /// Check health of `names`
     async fn check_all_services(
         services: &Arc<RwLock<HashMap<String, HealthCheckResult>>>,
         client: &Client,
         logger: &Logger,
+        names: &[&'static str],
     ) {
-        let mut tasks = Vec::new();
-        
-        // Check ZeroSlot
-        tasks.push(tokio::spawn(Self::check_zeroslot_health(client.clone(), logger.clone())));
-        
-        // Check Nozomi
-        tasks.push(tokio::spawn(Self::check_nozomi_health(client.clone(), logger.clone())));
+        let tasks: Vec<_> = names
+            .iter()
+            .map(|name| match *name {
+                "zeroslot" => tokio::spawn(Self::check_zeroslot_health(client.clone(), logger.clone())),
+                _ => tokio::spawn(Self::check_nozomi_health(client.clone(), logger.clone())),
+            })
+            .collect();
 
         // Wait for all health checks to complete
         let results = futures::future::join_all(tasks).await;
         
         // Update service statuses
         let mut services_map = services.write().await;
-        let service_names = ["zeroslot", "nozomi"];
-        
-        for (i, result) in results.iter().enumerate() {
+        for (name, result) in names.iter().zip(results) {
             if let Ok(health_result) = result {
-                if let Some(service_name) = service_names.get(i) {
-                    services_map.insert(service_name.to_string(), health_result.clone());
-                }
+                services_map.insert(name.to_string(), health_result);
             }
         }
     }
@@ -136,8 +131,7 @@ impl HealthCheckManager {
         let start_time = Instant::now();
         
         // Get ZeroSlot URL from environment
-        let zeroslot_url = std::env::var("ZERO_SLOT_URL")
-            .unwrap_or_else(|_| "https://api.zeroslot.io".to_string());
+        let zeroslot_url = crate::services::zeroslot::zero_slot_url();
         
         let health_check_body = json!({
             "jsonrpc": "2.0",
@@ -146,7 +140,7 @@ impl HealthCheckManager {
         });
         
         match client
-            .post(&format!("{}/rpc", zeroslot_url))
+            .post(&zeroslot_url)
             .json(&health_check_body)
             .send()
             .await
@@ -183,8 +177,7 @@ impl HealthCheckManager {
 
     /// ZeroSlot keepalive - send periodic requests to maintain connection
     async fn zeroslot_keepalive(client: &Client, logger: &Logger) {
-        let zeroslot_url = std::env::var("ZERO_SLOT_URL")
-            .unwrap_or_else(|_| "https://api.zeroslot.io".to_string());
+        let zeroslot_url = crate::services::zeroslot::zero_slot_url();
         
         let keepalive_body = json!({
             "jsonrpc": "2.0",
@@ -193,7 +186,7 @@ impl HealthCheckManager {
         });
         
         if let Err(e) = client
-            .post(&format!("{}/rpc", zeroslot_url))
+            .post(&zeroslot_url)
             .json(&keepalive_body)
             .send()
             .await
@@ -218,11 +211,11 @@ impl HealthCheckManager {
             "method": "getHealth"
         });
         
-        match client
-            .post(&nozomi_url)
-            .json(&health_check_body)
-            .send()
-            .await
+        let mut request = client.post(&nozomi_url).json(&health_check_body);
+        if let Some(api_key) = crate::utilities::nozomi::nozomi_api_key() {
+            request = request.header(crate::utilities::nozomi::NOZOMI_API_KEY_HEADER, api_key);
+        }
+        match request.send().await
         {
             Ok(response) => {
                 let response_time = start_time.elapsed();
@@ -264,10 +257,7 @@ impl HealthCheckManager {
     pub async fn get_healthiest_service(&self, mode: &TransactionLandingMode) -> Option<String> {
         let services = self.services.read().await;
         
-        let candidates = match mode {
-            TransactionLandingMode::Zeroslot => vec!["zeroslot"],
-            TransactionLandingMode::Nozomi => vec!["nozomi"],
-        };
+        let candidates = mode_services(mode);
         
         // Find the healthiest service with the fastest response time
         let mut best_service = None;
@@ -300,12 +290,20 @@ impl HealthCheckManager {
     }
 }
 
+/// Services a landing mode sends through
+fn mode_services(mode: &TransactionLandingMode) -> Vec<&'static str> {
+    match mode {
+        TransactionLandingMode::Zeroslot => vec!["zeroslot"],
+        TransactionLandingMode::Nozomi => vec!["nozomi"],
+    }
+}
+
 /// Global health check manager instance
 lazy_static::lazy_static! {
     pub static ref HEALTH_CHECK_MANAGER: HealthCheckManager = HealthCheckManager::new();
 }
 
-/// Initialize and start the health check manager
-pub async fn initialize_health_check_manager() -> Result<()> {
-    HEALTH_CHECK_MANAGER.start().await
+/// Initialize and start the health check manager for the services of `mode`
+pub async fn initialize_health_check_manager(mode: &TransactionLandingMode) -> Result<()> {
+    HEALTH_CHECK_MANAGER.start(mode).await
 } 
\ No newline at end of file
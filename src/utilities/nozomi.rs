@@ -1,27 +1,269 @@
-// Placeholder for nozomi utility module
-// This module was removed for public purposes
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use solana_rpc_client::http_sender::HttpSender;
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_client::rpc_client::RpcClientConfig;
+use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use anchor_client::solana_sdk::{
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+use colored::Colorize;
+use reqwest::header::{HeaderMap, HeaderValue};
+use solana_transaction_status::UiTransactionEncoding;
 
 use crate::error::ClientError;
+use crate::library::logger::Logger;
+use crate::tx_processor::{fill, tx::get_nozomi_tip};
+use crate::utilities::health_check::{HealthCheckResult, HealthStatus, HEALTH_CHECK_MANAGER};
 
-/// Placeholder struct for Nozomi functionality
-pub struct Nozomi;
+pub const NOZOMI_API_KEY_HEADER: &str = "x-api-key";
+
+/// Nozomi tip accounts, override with `NOZOMI_TIP_ACCOUNTS`
+pub const NOZOMI_TIP_ACCOUNTS: [&str; 17] = [
+    "TEMPaMeCRFAS9EKF53Jd6KpHxgL47uWLcpFArU1Fanq",
+    "noz3jAjPiHuBPqiSPkkugaJDkJscPuRhYnSpbi8UvC4",
+    "noz3str9KXfpKknefHji8L1mPgimezaiUyCHYMDv1GE",
+    "noz6uoYCDijhu1V7cutCpwxNiSovEwLdRHPwmgCGDNo",
+    "noz9EPNcT7WH6Sou3sr3GGjHQYVkN3DNirpbvDkv9YJ",
+    "nozc5yT15LazbLTFVZzoNZCwjh3yUtW86LoUyqsBu4L",
+    "nozFrhfnNGoyqwVuwPAW4aaGqempx4PU6g6D9CJMv7Z",
+    "nozievPk7HyK1Rqy1MPJwVQ7qQg2QoJGyP71oeDwbsu",
+    "noznbgwYnBLDHu8wcQVCEw6kDrXkPdKkydGJGNXGvL7",
+    "nozNVWs5N8mgzuD3qigrCG2UoKxZttxzZ85pvAQVrbP",
+    "nozpEGbwx4BcGp6pvEdAh1JoC2CQGZdU6HbNP1v2p6P",
+    "nozrhjhkCr3zXT3BiT4WCodYCUFeQvcdUkM7MqhKqge",
+    "nozrwQtWhEdrA6W8dkbt9gnUaMs52PdAv5byipnadq3",
+    "nozUacTVWub3cL4mJmGCYjKZTnE9RbdY5AP46iQgbPJ",
+    "nozWCyTPppJjRuw2fpzDhhWbW355fzosWSzrrMYB1Qk",
+    "nozWNju6dY353eMkMqURqwQEoM3SFgEKC6psLCSfUne",
+    "nozxNBgWohjR75vdspfxR5H9ceC7XXH99xpxhVGt3Bb",
+];
+
+/// API key from `NOZOMI_API_KEY`
+pub fn nozomi_api_key() -> Option<String> {
+    std::env::var("NOZOMI_API_KEY").ok().filter(|key| !key.is_empty())
+}
+
+/// RPC client for `url` sending `api_key` in the `x-api-key` header on every request
+pub fn nozomi_rpc_client(url: &str, api_key: Option<&str>) -> Result<RpcClient, ClientError> {
+    let mut headers = HeaderMap::new();
+    if let Some(api_key) = api_key {
+        let value = HeaderValue::from_str(api_key)
+            .map_err(|e| ClientError::Send("Invalid Nozomi API key".to_string(), e.to_string()))?;
+        headers.insert(NOZOMI_API_KEY_HEADER, value);
+    }
+    let client = reqwest::Client::builder()
+        .default_headers(headers)
+        .timeout(Duration::from_secs(10))
+        .pool_idle_timeout(Duration::from_secs(60))
+        .tcp_keepalive(Duration::from_secs(30))
+        .tcp_nodelay(true)
+        .build()
+        .map_err(|e| ClientError::Send("Failed to build Nozomi HTTP client".to_string(), e.to_string()))?;
+    Ok(RpcClient::new_sender(
+        HttpSender::new_with_client(url, client),
+        RpcClientConfig::with_commitment(CommitmentConfig::processed()),
+    ))
+}
+
+/// Refuse to send while the health checker reports Nozomi down; unchecked counts as up
+pub fn check_health(health: Option<&HealthCheckResult>) -> Result<(), ClientError> {
+    match health {
+        Some(health) if health.status == HealthStatus::Unhealthy => Err(ClientError::Send(
+            "Nozomi is unhealthy".to_string(),
+            health.error_message.clone().unwrap_or_default(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Lands transactions through Nozomi, tipping a different tip account each time
+pub struct Nozomi {
+    rpc_client: Arc<RpcClient>,
+    tip_accounts: Vec<Pubkey>,
+    tip_lamports: u64,
+    next_tip_account: AtomicUsize,
+    logger: Logger,
+}
 
 impl Nozomi {
-    /// Creates a new Nozomi instance
-    pub fn new() -> Self {
-        Self
+    /// Sender tipping `NOZOMI_TIP_VALUE` SOL to `NOZOMI_TIP_ACCOUNTS` (comma-separated, defaults to the published set)
+    pub fn new(rpc_client: Arc<RpcClient>) -> Self {
+        let configured: Vec<Pubkey> = std::env::var("NOZOMI_TIP_ACCOUNTS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|account| Pubkey::from_str(account.trim()).ok())
+            .collect();
+        let tip_accounts = if configured.is_empty() {
+            NOZOMI_TIP_ACCOUNTS.iter().filter_map(|account| Pubkey::from_str(account).ok()).collect()
+        } else {
+            configured
+        };
+        Self::with_tip_accounts(rpc_client, tip_accounts, (get_nozomi_tip() * 1_000_000_000.0) as u64)
+    }
+
+    pub fn with_tip_accounts(rpc_client: Arc<RpcClient>, tip_accounts: Vec<Pubkey>, tip_lamports: u64) -> Self {
+        // Measured fills split the tip out of the swap cost
+        let accounts: Vec<String> = tip_accounts.iter().map(Pubkey::to_string).collect();
+        fill::register_tip_accounts(&accounts.iter().map(String::as_str).collect::<Vec<_>>());
+        Self {
+            rpc_client,
+            tip_accounts,
+            tip_lamports,
+            next_tip_account: AtomicUsize::new(0),
+            logger: Logger::new("[NOZOMI] => ".cyan().to_string()),
+        }
+    }
+
+    /// Tip account for the next transaction, round-robin
+    pub fn next_tip_account(&self) -> Option<Pubkey> {
+        if self.tip_accounts.is_empty() {
+            return None;
+        }
+        let index = self.next_tip_account.fetch_add(1, Ordering::Relaxed) % self.tip_accounts.len();
+        Some(self.tip_accounts[index])
+    }
+
+    /// Sign `instructions` with a tip transfer appended
+    pub fn build_transaction(
+        &self,
+        keypair: &Keypair,
+        instructions: &[Instruction],
+        recent_blockhash: Hash,
+    ) -> Result<Transaction, ClientError> {
+        let tip_account = self.next_tip_account()
+            .ok_or_else(|| ClientError::Send("No Nozomi tip account".to_string(), "set NOZOMI_TIP_ACCOUNTS".to_string()))?;
+        let mut instructions = instructions.to_vec();
+        instructions.push(system_instruction::transfer(&keypair.pubkey(), &tip_account, self.tip_lamports));
+        Ok(Transaction::new_signed_with_payer(&instructions, Some(&keypair.pubkey()), &[keypair], recent_blockhash))
+    }
+
+    /// Sign `instructions` with the tip and submit them unless Nozomi is reported down, returns the signature
+    pub async fn process(
+        &self,
+        keypair: &Keypair,
+        instructions: &[Instruction],
+        recent_blockhash: Hash,
+    ) -> Result<String, ClientError> {
+        check_health(HEALTH_CHECK_MANAGER.get_service_health("nozomi").await.as_ref())?;
+        let transaction = self.build_transaction(keypair, instructions, recent_blockhash)?;
+        self.send_transaction(&transaction).await
     }
-    
-    /// Placeholder method for Nozomi operations
-    pub async fn process(&self) -> Result<(), ClientError> {
-        // Placeholder implementation
-        // Add actual logic here when needed
-        Ok(())
+
+    pub async fn send_transaction(&self, transaction: &Transaction) -> Result<String, ClientError> {
+        let started = Instant::now();
+        let config = RpcSendTransactionConfig {
+            skip_preflight: true,
+            encoding: Some(UiTransactionEncoding::Base64),
+            max_retries: Some(0),
+            ..RpcSendTransactionConfig::default()
+        };
+        match self.rpc_client.send_transaction_with_config(transaction, config).await {
+            Ok(signature) => {
+                self.logger.log(format!(
+                    "Sent {} with {} lamports tip in {:?}",
+                    signature, self.tip_lamports, started.elapsed()
+                ).green().to_string());
+                Ok(signature.to_string())
+            }
+            Err(e) => {
+                self.logger.log(format!("Send failed after {:?}: {}", started.elapsed(), e).red().to_string());
+                Err(ClientError::Send("Nozomi sendTransaction failed".to_string(), e.to_string()))
+            }
+        }
     }
 }
 
-impl Default for Nozomi {
-    fn default() -> Self {
-        Self::new()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::mock_server::serve_once;
+    use anchor_client::solana_sdk::system_program;
+    use serde_json::json;
+
+    fn tip_accounts() -> Vec<Pubkey> {
+        vec![Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()]
+    }
+
+    fn decode(body: &serde_json::Value) -> Transaction {
+        let bytes = base64::decode(body["params"][0].as_str().unwrap()).unwrap();
+        bincode::deserialize(&bytes).unwrap()
+    }
+
+    #[test]
+    fn default_tip_accounts_are_valid() {
+        for account in NOZOMI_TIP_ACCOUNTS {
+            assert!(Pubkey::from_str(account).is_ok(), "{}", account);
+        }
+    }
+
+    #[test]
+    fn rotates_tip_accounts() {
+        let accounts = tip_accounts();
+        let rpc_client = Arc::new(nozomi_rpc_client("http://127.0.0.1:1", None).unwrap());
+        let nozomi = Nozomi::with_tip_accounts(rpc_client, accounts.clone(), 1_000);
+        let picked: Vec<Pubkey> = (0..4).filter_map(|_| nozomi.next_tip_account()).collect();
+        assert_eq!(picked, vec![accounts[0], accounts[1], accounts[2], accounts[0]]);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn refuses_only_when_unhealthy() {
+        let result = |status| HealthCheckResult {
+            status,
+            response_time: Duration::from_millis(5),
+            last_checked: Instant::now(),
+            error_message: Some("HTTP 502".to_string()),
+        };
+        assert!(check_health(None).is_ok());
+        assert!(check_health(Some(&result(HealthStatus::Unknown))).is_ok());
+        assert!(check_health(Some(&result(HealthStatus::Healthy))).is_ok());
+        assert!(matches!(check_health(Some(&result(HealthStatus::Unhealthy))), Err(ClientError::Send(_, _))));
+    }
+
+    #[tokio::test]
+    async fn sends_with_api_key_and_tip() {
+        let (url, request) = serve_once(|body| {
+            let signature = decode(body).signatures[0].to_string();
+            ("200 OK", json!({ "jsonrpc": "2.0", "id": body["id"], "result": signature }).to_string())
+        }).await;
+        let accounts = tip_accounts();
+        let rpc_client = Arc::new(nozomi_rpc_client(&url, Some("test-key")).unwrap());
+        let nozomi = Nozomi::with_tip_accounts(rpc_client, accounts.clone(), 1_500_000);
+        let keypair = Keypair::new();
+        let transfer = system_instruction::transfer(&keypair.pubkey(), &Pubkey::new_unique(), 10);
+
+        let transaction = nozomi.build_transaction(&keypair, &[transfer], Hash::default()).unwrap();
+        let signature = nozomi.send_transaction(&transaction).await.unwrap();
+        assert_eq!(signature, transaction.signatures[0].to_string());
+
+        let request = request.await.unwrap();
+        assert!(request.headers.contains("x-api-key: test-key"));
+        assert_eq!(request.body["method"], "sendTransaction");
+        let sent = decode(&request.body);
+        let tip = sent.message.instructions.last().unwrap();
+        let keys = &sent.message.account_keys;
+        assert_eq!(keys[tip.program_id_index as usize], system_program::id());
+        assert_eq!(keys[tip.accounts[1] as usize], accounts[0]);
+        assert_eq!(u64::from_le_bytes(tip.data[4..12].try_into().unwrap()), 1_500_000);
+    }
+
+    #[tokio::test]
+    async fn maps_rpc_error_to_send() {
+        let (url, _) = serve_once(|body| {
+            let reply = json!({ "jsonrpc": "2.0", "id": body["id"], "error": { "code": -32002, "message": "tip missing" } });
+            ("200 OK", reply.to_string())
+        }).await;
+        let rpc_client = Arc::new(nozomi_rpc_client(&url, None).unwrap());
+        let nozomi = Nozomi::with_tip_accounts(rpc_client, tip_accounts(), 1_000);
+        let transaction = nozomi.build_transaction(&Keypair::new(), &[], Hash::default()).unwrap();
+        assert!(matches!(nozomi.send_transaction(&transaction).await, Err(ClientError::Send(_, _))));
+    }
+}
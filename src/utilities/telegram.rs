@@ -4,8 +4,9 @@ use std::time::{Duration, Instant};
 use anyhow::{Result, anyhow};
 use colored::Colorize;
 use teloxide::prelude::*;
-use teloxide::types::{ParseMode};
+use teloxide::types::{ParseMode, UpdateKind};
 use crate::library::logger::Logger;
+use crate::engine::paper;
 use crate::engine::transaction_parser::TradeInfoFromToken;
 use dashmap::DashMap;
 
@@ -55,9 +56,14 @@ pub async fn init() -> Result<()> {
     Ok(())
 }
 
-/// Send a message to the configured chat
+/// Send a message to the configured chat, tagged as paper trading in paper mode
 pub async fn send_message(message: &str) -> Result<()> {
     let logger = Logger::new("[TELEGRAM] => ".cyan().bold().to_string());
+    let message = if paper::is_paper_mode() {
+        format!("📝 *PAPER*\n{}", message)
+    } else {
+        message.to_string()
+    };
     
     // Get bot and chat ID from global state
     let bot_ref = TELEGRAM_BOT.get(&());
@@ -93,34 +99,34 @@ pub async fn send_message(message: &str) -> Result<()> {
     }
 }
 
+// Escape special characters for Markdown V2
+fn escape_md(s: &str) -> String {
+    s.replace('_', "\\_")
+     .replace('*', "\\*")
+     .replace('[', "\\[")
+     .replace(']', "\\]")
+     .replace('(', "\\(")
+     .replace(')', "\\)")
+     .replace('~', "\\~")
+     .replace('`', "\\`")
+     .replace('>', "\\>")
+     .replace('#', "\\#")
+     .replace('+', "\\+")
+     .replace('-', "\\-")
+     .replace('=', "\\=")
+     .replace('|', "\\|")
+     .replace('{', "\\{")
+     .replace('}', "\\}")
+     .replace('.', "\\.")
+     .replace('!', "\\!")
+}
+
 /// Format a trade notification message
 pub fn format_trade_notification(
     trade_info: &TradeInfoFromToken,
     protocol: &str,
     action: &str,
 ) -> String {
-    // Escape special characters for Markdown V2
-    let escape_md = |s: &str| -> String {
-        s.replace('_', "\\_")
-         .replace('*', "\\*")
-         .replace('[', "\\[")
-         .replace(']', "\\]")
-         .replace('(', "\\(")
-         .replace(')', "\\)")
-         .replace('~', "\\~")
-         .replace('`', "\\`")
-         .replace('>', "\\>")
-         .replace('#', "\\#")
-         .replace('+', "\\+")
-         .replace('-', "\\-")
-         .replace('=', "\\=")
-         .replace('|', "\\|")
-         .replace('{', "\\{")
-         .replace('}', "\\}")
-         .replace('.', "\\.")
-         .replace('!', "\\!")
-    };
-    
     // Determine emoji based on action
     let emoji = match action {
         "DETECTED" => "👀",
@@ -151,7 +157,7 @@ pub fn format_trade_notification(
         \n\
         Token: `{}`\n\
         Amount: `{}`\n\
-        Target: `{}`\n\
+        Target: *{}* `{}`\n\
         TX: `{}`\n\
         [View on Solscan](https://solscan.io/tx/{})\n\
         Action: *{}*",
@@ -160,6 +166,7 @@ pub fn format_trade_notification(
         escape_md(protocol),
         escape_md(&trade_info.mint),
         escape_md(&token_amount_str),
+        escape_md(&crate::engine::target_profiles::target_label(&trade_info.target)),
         escape_md(&trade_info.target),
         escape_md(&trade_info.signature),
         trade_info.signature,
@@ -179,28 +186,6 @@ pub async fn send_trade_notification(
 
 /// Send an error notification
 pub async fn send_error_notification(error: &str) -> Result<()> {
-    // Escape special characters for Markdown V2
-    let escape_md = |s: &str| -> String {
-        s.replace('_', "\\_")
-         .replace('*', "\\*")
-         .replace('[', "\\[")
-         .replace(']', "\\]")
-         .replace('(', "\\(")
-         .replace(')', "\\)")
-         .replace('~', "\\~")
-         .replace('`', "\\`")
-         .replace('>', "\\>")
-         .replace('#', "\\#")
-         .replace('+', "\\+")
-         .replace('-', "\\-")
-         .replace('=', "\\=")
-         .replace('|', "\\|")
-         .replace('{', "\\{")
-         .replace('}', "\\}")
-         .replace('.', "\\.")
-         .replace('!', "\\!")
-    };
-
     let escaped_error = escape_md(error);
     let message = format!("❌ *ERROR*\n\n{}", escaped_error);
     send_message(&message).await
@@ -219,28 +204,6 @@ pub fn format_copy_trade_notification(
     action: &str,
     elapsed: Option<Duration>,
 ) -> String {
-    // Escape special characters for Markdown V2
-    let escape_md = |s: &str| -> String {
-        s.replace('_', "\\_")
-         .replace('*', "\\*")
-         .replace('[', "\\[")
-         .replace(']', "\\]")
-         .replace('(', "\\(")
-         .replace(')', "\\)")
-         .replace('~', "\\~")
-         .replace('`', "\\`")
-         .replace('>', "\\>")
-         .replace('#', "\\#")
-         .replace('+', "\\+")
-         .replace('-', "\\-")
-         .replace('=', "\\=")
-         .replace('|', "\\|")
-         .replace('{', "\\{")
-         .replace('}', "\\}")
-         .replace('.', "\\.")
-         .replace('!', "\\!")
-    };
-    
     // Determine emoji based on action
     let emoji = match action {
         "COPIED" => "🔄",
@@ -284,6 +247,7 @@ pub fn format_copy_trade_notification(
         \n\
         Token: `{}`\n\
         Amount: `{}`\n\
+        Target: *{}* `{}`\n\
         Target TX: `{}`\n\
         My TX: `{}`\n\
         Time Elapsed: `{}`\n\
@@ -295,6 +259,8 @@ pub fn format_copy_trade_notification(
         escape_md(protocol),
         escape_md(&target_trade.mint),
         escape_md(&token_amount_str),
+        escape_md(&crate::engine::target_profiles::target_label(&target_trade.target)),
+        escape_md(&target_trade.target),
         escape_md(&target_trade.signature),
         escape_md(my_signature),
         escape_md(&elapsed_str),
@@ -327,28 +293,6 @@ pub async fn send_summary_notification(
     active_tokens: Vec<String>,
     total_pnl: f64,
 ) -> Result<()> {
-    // Escape special characters for Markdown V2
-    let escape_md = |s: &str| -> String {
-        s.replace('_', "\\_")
-         .replace('*', "\\*")
-         .replace('[', "\\[")
-         .replace(']', "\\]")
-         .replace('(', "\\(")
-         .replace(')', "\\)")
-         .replace('~', "\\~")
-         .replace('`', "\\`")
-         .replace('>', "\\>")
-         .replace('#', "\\#")
-         .replace('+', "\\+")
-         .replace('-', "\\-")
-         .replace('=', "\\=")
-         .replace('|', "\\|")
-         .replace('{', "\\{")
-         .replace('}', "\\}")
-         .replace('.', "\\.")
-         .replace('!', "\\!")
-    };
-
     // Format active tokens list
     let tokens_str = if active_tokens.is_empty() {
         "None".to_string()
@@ -379,4 +323,106 @@ pub async fn send_summary_notification(
     );
 
     send_message(&message).await
-} 
\ No newline at end of file
+} 
+
+/// Notify that a target was paused for poor copied performance
+pub async fn send_target_paused_notification(label: &str, address: &str, reason: &str) -> Result<()> {
+    let message = format!(
+        "⏸️ *TARGET PAUSED*\n\nTarget: *{}* `{}`\nReason: {}",
+        escape_md(label),
+        escape_md(address),
+        escape_md(reason)
+    );
+    send_message(&message).await
+}
+
+/// Alert that a position is being dumped out of immediately
+pub async fn send_emergency_exit_notification(mint: &str, reason: &str) -> Result<()> {
+    let message = format!(
+        "🚨 *EMERGENCY EXIT*\n\nToken: `{}`\nReason: {}",
+        escape_md(mint),
+        escape_md(reason)
+    );
+    send_message(&message).await
+}
+
+/// Alert that the emergency stop-loss closed a position, with the loss it realized
+pub async fn send_stop_loss_notification(mint: &str, pnl_percentage: f64, realized_pnl_sol: f64, signature: &str) -> Result<()> {
+    let message = format!(
+        "🩸 *EMERGENCY STOP\\-LOSS*\n\nToken: `{}`\nPnL: `{}`\nRealized: `{}`\nTX: `{}`",
+        escape_md(mint),
+        escape_md(&format!("{:+.2}%", pnl_percentage)),
+        escape_md(&format!("{:+.4} SOL", realized_pnl_sol)),
+        escape_md(signature)
+    );
+    send_message(&message).await
+}
+
+/// Announce the circuit breaker halting or resuming buys
+pub async fn send_circuit_breaker_notification(tripped: bool, reason: &str) -> Result<()> {
+    let message = if tripped {
+        format!("🛑 *BUYING HALTED*\n\nReason: {}\nSend /resume to re\\-enable", escape_md(reason))
+    } else {
+        format!("✅ *BUYING RESUMED*\n\nReason: {}", escape_md(reason))
+    };
+    send_message(&message).await
+}
+
+/// Poll the bot for commands from the configured chat and reply with what `handler` returns
+///
+/// Messages from other chats are ignored. No-op when the bot is not initialized.
+pub fn start_command_listener(handler: fn(&str) -> Option<String>) {
+    let bot = TELEGRAM_BOT.get(&()).and_then(|bot| bot.value().clone());
+    let chat_id = CHAT_ID.get(&()).and_then(|chat| chat.value().clone()).and_then(|id| id.parse::<i64>().ok());
+    let (Some(bot), Some(chat_id)) = (bot, chat_id) else { return };
+
+    tokio::spawn(async move {
+        let logger = Logger::new("[TELEGRAM] => ".cyan().bold().to_string());
+        // Commands sent while the bot was down are stale; offset -1 returns only the newest update
+        let mut offset = loop {
+            match bot.get_updates().offset(-1).timeout(0).await {
+                Ok(updates) => break updates.last().map_or(0, |update| update.id + 1),
+                Err(e) => {
+                    logger.log(format!("Failed to skip old commands: {}", e).red().to_string());
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                }
+            }
+        };
+        loop {
+            let updates = match bot.get_updates().offset(offset).timeout(10).await {
+                Ok(updates) => updates,
+                Err(e) => {
+                    logger.log(format!("Failed to poll commands: {}", e).red().to_string());
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    continue;
+                }
+            };
+            for update in updates {
+                offset = update.id + 1;
+                let UpdateKind::Message(message) = update.kind else { continue };
+                if message.chat.id.0 != chat_id {
+                    continue;
+                }
+                let Some(reply) = message.text().and_then(handler) else { continue };
+                logger.log(format!("Command {:?}: {}", message.text().unwrap_or_default(), reply));
+                if let Err(e) = send_message(&escape_md(&reply)).await {
+                    logger.log(format!("Failed to reply to command: {}", e).red().to_string());
+                }
+            }
+        }
+    });
+}
+
+/// Send the per-target performance report, one line per target
+pub async fn send_target_report(lines: &[String]) -> Result<()> {
+    if lines.is_empty() {
+        return Ok(());
+    }
+    let body = lines
+        .iter()
+        .map(|line| format!("• {}", escape_md(line)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let message = format!("📈 *Target Performance*\n\n{}", body);
+    send_message(&message).await
+}
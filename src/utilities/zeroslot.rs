@@ -1,27 +1,48 @@
-// Placeholder for zeroslot utility module
-// This module was removed for public purposes
+use std::sync::Arc;
+use std::time::Instant;
+use anchor_client::solana_sdk::{hash::Hash, instruction::Instruction, signature::Keypair};
+use colored::Colorize;
 
 use crate::error::ClientError;
+use crate::library::logger::Logger;
+use crate::services::zeroslot::ZeroSlotClient;
 
-/// Placeholder struct for ZeroSlot functionality
-pub struct ZeroSlot;
+/// Lands transactions through 0slot, tipping each one
+pub struct ZeroSlot {
+    client: Arc<ZeroSlotClient>,
+    logger: Logger,
+}
 
 impl ZeroSlot {
-    /// Creates a new ZeroSlot instance
-    pub fn new() -> Self {
-        Self
-    }
-    
-    /// Placeholder method for ZeroSlot operations
-    pub async fn process(&self) -> Result<(), ClientError> {
-        // Placeholder implementation
-        // Add actual zero slot logic here when needed
-        Ok(())
+    pub fn new(client: Arc<ZeroSlotClient>) -> Self {
+        Self {
+            client,
+            logger: Logger::new("[ZEROSLOT] => ".cyan().to_string()),
+        }
     }
-}
 
-impl Default for ZeroSlot {
-    fn default() -> Self {
-        Self::new()
+    /// Sign `instructions` with the 0slot tip appended and submit them, returns the signature
+    pub async fn process(
+        &self,
+        keypair: &Keypair,
+        instructions: &[Instruction],
+        recent_blockhash: Hash,
+    ) -> Result<String, ClientError> {
+        let started = Instant::now();
+        match self.client.send_instructions(keypair, instructions, recent_blockhash).await {
+            Ok(signature) => {
+                self.logger.log(format!(
+                    "Sent {} with {} lamports tip in {:?}",
+                    signature,
+                    self.client.tip_lamports(),
+                    started.elapsed()
+                ).green().to_string());
+                Ok(signature)
+            }
+            Err(e) => {
+                self.logger.log(format!("Send failed after {:?}: {}", started.elapsed(), e).red().to_string());
+                Err(e)
+            }
+        }
     }
-} 
\ No newline at end of file
+}